@@ -0,0 +1,95 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Build metadata, for `--version --verbose`.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let manifest_dir = Path::new(&manifest_dir);
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    // Only our own repository, not one we happen to be built in (e.g.,
+    // `cargo install` from a home directory under version control).
+    let commit = if manifest_dir.join(".git").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/index");
+        commit(manifest_dir)
+    } else {
+        None
+    };
+    println!(
+        "cargo:rustc-env=GIT_SLIDES_BUILD_COMMIT={}",
+        commit.as_deref().unwrap_or("unknown")
+    );
+
+    // Honor reproducible builds.
+    let timestamp = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        });
+    println!("cargo:rustc-env=GIT_SLIDES_BUILD_TIMESTAMP={timestamp}");
+
+    println!(
+        "cargo:rustc-env=GIT_SLIDES_BUILD_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            let feature = key.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!(
+        "cargo:rustc-env=GIT_SLIDES_BUILD_FEATURES={}",
+        features.join(" ")
+    );
+}
+
+/// Short hash of `HEAD`, with `-dirty` if tracked files changed.
+fn commit(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut commit = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    let status = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !status.stdout.is_empty() {
+        commit.push_str("-dirty");
+    }
+    Some(commit)
+}
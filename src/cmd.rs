@@ -15,329 +15,5034 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Write as _;
 use std::io::{self, Write as _};
-use std::path::PathBuf;
-use std::{cmp, fs};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{cmp, fs, slice};
 
-use lessify::Pager;
+use git_slides::git::{self, Commit, GitBackend, HistoryOptions};
+use git_slides::{forge, glob, time, toml};
 
-use git_slides::git::{self, Commit};
+use crate::console;
+use crate::hooks;
+use crate::html;
+use crate::i18n::{tr, Language};
+use crate::manifest;
+use crate::pager;
+use crate::screen;
+use crate::store::{self, HistoryCache, Section, Stash, Store, TimelineEntry, Transition};
+use crate::tmux;
+use crate::web::{self, Response};
 
 const STORE_FILE: &str = env!("CARGO_BIN_NAME");
+/// Ref the current slide is published to, and followed from.
+const PUBLISHED_REF: &str = "refs/slides/current";
+/// Speaker notes are kept in `refs/notes/slides`.
+const NOTES_REF: &str = "slides";
+/// Notes ref of annotations (see `annotate`).
+const ANNOTATIONS_REF: &str = "slides-annotations";
 
-const COLOR_RESET: &str = "\x1b[m";
-const COLOR_FAINT: &str = "\x1b[2m";
-const COLOR_YELLOW: &str = "\x1b[33m";
+/// Exit code of `next` and `previous` going past the end or the start,
+/// with `--strict`.
+const EXIT_BOUNDARY: i32 = 3;
+
+/// A command stopped short, after saying why, with the code to exit with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exit(pub i32);
+
+/// Exit with the code of a command that stopped short, for callers
+/// that don't report [`Exit`] themselves.
+pub fn exit_on_error<T>(result: Result<T, Exit>) -> T {
+    result.unwrap_or_else(|Exit(code)| std::process::exit(code))
+}
+
+/// ANSI escape sequences used in output, empty if color is disabled.
+pub struct Colors {
+    pub reset: String,
+    /// Past slides, `(Start)` and `(End)` markers, and progress left.
+    pub dim: String,
+    /// Commit hashes.
+    pub hash: String,
+    /// Time spent over a slide's `Slide-Duration`.
+    pub warning: String,
+}
+
+impl Colors {
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            reset: String::new(),
+            dim: String::new(),
+            hash: String::new(),
+            warning: String::new(),
+        }
+    }
+
+    /// For projectors, that tend to wash out faint text.
+    #[must_use]
+    pub fn high_contrast() -> Self {
+        Self {
+            reset: String::from("\x1b[m"),
+            dim: String::new(),
+            hash: String::from("\x1b[1;33m"),
+            warning: String::from("\x1b[1;31m"),
+        }
+    }
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            reset: String::from("\x1b[m"),
+            dim: String::from("\x1b[2m"),
+            hash: String::from("\x1b[33m"),
+            warning: String::from("\x1b[31m"),
+        }
+    }
+}
+
+/// Behavior switches, resolved from the command line and Git config.
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)] // Independent switches.
+pub struct Options {
+    /// Run `git submodule update --init` after each navigation.
+    pub recurse_submodules: bool,
+    /// Populate Git LFS files after each navigation.
+    pub lfs: Option<Lfs>,
+    /// Default for `start --all-parents`.
+    pub all_parents: bool,
+    /// Patterns stripped from the start of titles, for display.
+    pub title_prefixes: Vec<String>,
+    /// Suppress informational messages.
+    pub quiet: bool,
+    /// Colors used in `status` and `list`.
+    pub colors: Colors,
+    /// Extra columns shown in `status` and `list`.
+    pub columns: Columns,
+    /// Exit with [`EXIT_BOUNDARY`] when going past the end or start.
+    pub strict: bool,
+    /// `next` and `previous` wrap around past the end or start.
+    pub loop_: bool,
+    /// Slides `next` and `previous` move by, without a count.
+    pub step: usize,
+    /// If HEAD left the presentation, go back to the nearest slide.
+    pub recover: bool,
+    /// Show what navigation would do, without doing it.
+    pub dry_run: bool,
+    /// Run `Slide-Exec` commands of slides.
+    pub exec: bool,
+    /// Mirror navigation in tmux.
+    pub tmux: Option<Tmux>,
+    /// Show the current slide in the terminal title.
+    pub terminal_title: bool,
+    /// Restore uncommitted changes of a slide when coming back to it.
+    pub scratch: bool,
+    /// Minimum length of abbreviated hashes, instead of `core.abbrev`.
+    pub abbrev: Option<usize>,
+    /// What to do with uncommitted changes when changing slides.
+    pub dirty_action: DirtyAction,
+    /// Elapsed times to be alerted at, in seconds, in any order.
+    pub checkpoints: Vec<u64>,
+    pub checkpoint_alert: CheckpointAlert,
+    /// Command rendering each slide (e.g., `glow SLIDES.md`).
+    pub viewer: Option<String>,
+    /// Command run after every slide change, with `SLIDE_*` variables.
+    pub on_slide: Option<String>,
+    /// File rewritten with the current slide on every slide change, for
+    /// overlays (e.g., OBS), and its format.
+    pub info_file: Option<PathBuf>,
+    pub info_format: String,
+    /// Path of the slide's file in the tree, for slide renderers.
+    pub slide_file: Option<String>,
+    /// Where to write the slide file's path (e.g., a FIFO), instead of
+    /// printing it.
+    pub slide_file_pipe: Option<PathBuf>,
+    /// Commit URL template of `permalink`, instead of the forge's.
+    pub permalink: Option<String>,
+    /// Language of user-facing messages.
+    pub language: Language,
+    /// Store file, instead of `.git/git-slides`.
+    pub store_file: Option<PathBuf>,
+}
+
+/// Optional columns of `status` and `list`, between hash and title.
+#[derive(Clone, Copy, Default)]
+pub struct Columns {
+    pub author: bool,
+    pub date: bool,
+}
+
+impl Columns {
+    fn any(self) -> bool {
+        self.author || self.date
+    }
+}
+
+/// Arguments to `start`.
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)] // Independent switches.
+pub struct StartOptions {
+    /// Last slide of the presentation (defaults to HEAD).
+    pub ref_: Option<String>,
+    /// Further refs, each adding the commits earlier refs don't have
+    /// as a section (e.g., one branch per chapter).
+    pub more_refs: Vec<String>,
+    /// First slide of the presentation (defaults to the root commit).
+    pub from: Option<String>,
+    /// Follow all parents of merge commits, not only the first one.
+    pub all_parents: bool,
+    /// Only commits whose message matches this regex become slides.
+    pub pattern: Option<String>,
+    /// Present tagged commits matching this pattern, instead of history.
+    pub tags: Option<String>,
+    /// Order of tag-based slides.
+    pub tag_order: TagOrder,
+    /// Present slides listed in this manifest file, instead of history.
+    pub manifest: Option<String>,
+    /// Slide to begin at.
+    pub at: StartAt,
+    /// Fetch the full history first, if the clone is shallow.
+    pub deepen: bool,
+    /// Present in a dedicated worktree.
+    pub worktree: bool,
+    /// Stash uncommitted changes, and restore them on `stop`.
+    pub stash: bool,
+    /// Discard uncommitted changes, after confirmation.
+    pub force: bool,
+    /// Don't ask for confirmation.
+    pub yes: bool,
+    /// Continue the presentation in progress, instead of starting one.
+    pub resume: bool,
+}
+
+/// Slide to begin a presentation at.
+#[derive(Default, Clone, Copy)]
+pub enum StartAt {
+    /// The first slide.
+    #[default]
+    First,
+    /// Slide `n`, starting from 1.
+    Index(usize),
+    /// The slide HEAD is on, when starting.
+    Current,
+}
+
+/// Where to leave HEAD when a presentation stops.
+#[derive(Default, Clone)]
+pub enum StopAt {
+    /// Where it was when the presentation started.
+    #[default]
+    Initial,
+    /// On the current slide.
+    Current,
+    /// On a ref (e.g., an `answers` branch).
+    Ref(String),
+}
+
+/// How a new commit relates to the current slide.
+#[derive(Clone, Copy)]
+enum SlideRewrite {
+    /// The new commit is a new slide, after the current one.
+    Insert,
+    /// The new commit replaces the current slide (e.g., amended).
+    Replace,
+}
+
+/// Arguments to `list`.
+pub struct ListOptions {
+    /// Include message excerpts and `Slide-*` trailers.
+    pub verbose: bool,
+    /// Page the output (if it is a terminal).
+    pub pager: bool,
+    /// Show the size of each slide's diff, from the previous slide.
+    pub stat: bool,
+}
+
+/// Slide to `go` to.
+pub enum GoTarget {
+    /// Slide number, 1-indexed.
+    Index(usize),
+    /// Position in the presentation, from 0% (first slide) to 100%
+    /// (last slide).
+    Percentage(usize),
+    /// Slide containing a commit, given as a hash or any other ref.
+    Ref(String),
+}
+
+/// Output format of `timeline`.
+#[derive(Clone, Copy, Default)]
+pub enum TimelineFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// Output format of `status`.
+#[derive(Default)]
+pub enum StatusFormat {
+    /// Progress bar and surrounding slides.
+    #[default]
+    Full,
+    /// Current slide only, on a single line.
+    Oneline,
+    /// Current slide number and total, for scripts.
+    Index,
+}
+
+/// Order of slides in a tag-based presentation.
+#[derive(Default, Clone, Copy)]
+pub enum TagOrder {
+    /// Natural order of tag names (`slide-2` before `slide-10`).
+    #[default]
+    Name,
+    /// Creation date, oldest first.
+    Date,
+}
+
+/// How to populate Git LFS files after a checkout.
+#[derive(Clone, Copy)]
+pub enum Lfs {
+    /// `git lfs checkout`, using only objects already downloaded.
+    Checkout,
+    /// `git lfs pull`, downloading missing objects first (slower).
+    Pull,
+}
+
+/// What to do with uncommitted changes when changing slides.
+#[derive(Clone, Copy, Default)]
+pub enum DirtyAction {
+    /// Stash them (labeled with the slide).
+    #[default]
+    Stash,
+    /// Refuse to change slides.
+    Block,
+    /// Throw them away.
+    Discard,
+}
+
+/// How to tell a time checkpoint was crossed.
+#[derive(Clone, Copy, Default)]
+pub enum CheckpointAlert {
+    /// Ring the terminal bell.
+    #[default]
+    Bell,
+    /// Send a desktop notification (and ring the bell if it fails).
+    Notify,
+}
+
+/// tmux integration, on every navigation.
+pub struct Tmux {
+    /// Pane to send [`Self::refresh_keys`] to (e.g., one running a
+    /// demo).
+    pub refresh_pane: Option<String>,
+    pub refresh_keys: Vec<String>,
+}
+
+impl Tmux {
+    /// Interrupt, and re-run the last command.
+    pub const DEFAULT_REFRESH_KEYS: [&str; 3] = ["C-c", "Up", "Enter"];
+}
 
 pub struct Cmd {
+    git: Box<dyn GitBackend>,
     git_dir: PathBuf,
+    options: Options,
+    store: OnceCell<Store>,
     history: OnceCell<Vec<Commit>>,
+    sections: OnceCell<Vec<Section>>,
+    abbrev: OnceCell<usize>,
+    decorations: OnceCell<HashMap<String, Vec<String>>>,
 }
 
-impl Cmd {
-    pub fn new(git_dir: PathBuf) -> Self {
-        Self {
-            git_dir,
-            history: OnceCell::new(),
-        }
+impl Cmd {
+    pub fn new(git: Box<dyn GitBackend>, git_dir: PathBuf, options: Options) -> Self {
+        Self {
+            git,
+            git_dir,
+            options,
+            store: OnceCell::new(),
+            history: OnceCell::new(),
+            sections: OnceCell::new(),
+            abbrev: OnceCell::new(),
+            decorations: OnceCell::new(),
+        }
+    }
+
+    pub fn start(&mut self, options: StartOptions) {
+        if options.resume {
+            return self.resume();
+        }
+        // Starting over would lose where to go back to on `stop`.
+        self.ensure_presentation_is_not_started();
+        exit_on_error(self.ensure_no_operation_in_progress());
+
+        let is_dirty = self.check_working_directory(&options);
+
+        // With several refs, the presentation ends on the last one.
+        let head_ref = options.more_refs.last().or(options.ref_.as_ref());
+        let commit_hash = self.resolve_head_commit(head_ref);
+
+        let from = options
+            .from
+            .as_deref()
+            .map(|from| self.resolve_from(from, &commit_hash));
+
+        self.handle_shallow_clone(options.deepen);
+
+        let current_commit_hash = self.git.current_commit_hash();
+
+        let (slides, sections) = if options.more_refs.is_empty() {
+            (self.explicit_slides(&options), Vec::new())
+        } else {
+            self.chapter_slides(&options)
+        };
+
+        let store = Store {
+            branch: git::current_branch(),
+            head: commit_hash.clone(),
+            from,
+            all_parents: options.all_parents || self.options.all_parents,
+            pattern: options.pattern,
+            slides,
+            sections,
+            hidden: Vec::new(),
+            started: Some(time::now()),
+            publish: None,
+            worktree: None,
+            start_stash: None,
+            stopped: None,
+            position: None,
+            slide_count: None,
+            checksum: None,
+            transitions: Vec::new(),
+            stashes: Vec::new(),
+            cache: None,
+        };
+
+        let history = self.history_from_store(&store);
+        if history.is_empty() {
+            self.exit_no_slides(
+                options.tags.as_deref(),
+                options.manifest.as_deref(),
+                store.pattern.as_deref(),
+            );
+        }
+
+        let n = self.start_index(options.at, current_commit_hash.as_deref(), &history);
+        self.warn_about_branches_in_slides(&history);
+
+        let pre_start = hooks::run(
+            &self.git_dir,
+            "pre-start",
+            &[
+                ("GIT_SLIDES_HEAD", commit_hash.clone()),
+                ("GIT_SLIDES_TOTAL", history.len().to_string()),
+            ],
+        );
+        if pre_start == Some(false) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: The 'pre-start' hook failed. Aborting."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        let mut store = Self::with_checksum(Self::with_history_cache(store, &history), &history);
+        if options.worktree {
+            store.worktree = Some(self.create_worktree(&commit_hash));
+        }
+        if is_dirty {
+            store.start_stash = self.clean_before_start(options.stash, options.yes);
+        }
+        self.write_store_file(&store);
+
+        // Already computed, no need to read them back.
+        let _ = self.store.set(store);
+        let _ = self.history.set(history);
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Presentation started at {}.",
+            commit_hash
+        ));
+
+        exit_on_error(self.go(n));
+    }
+
+    /// The commit the presentation ends on: `ref_`, or HEAD.
+    fn resolve_head_commit(&self, ref_: Option<&String>) -> String {
+        if let Some(ref_) = ref_ {
+            self.git.ref_to_commit_hash(ref_).unwrap_or_else(|| {
+                eprintln!(
+                    "{}",
+                    tr!(self.options.language, "error: Bad ref input: '{}'.", ref_)
+                );
+                std::process::exit(1);
+            })
+        } else {
+            self.git.current_commit_hash().unwrap_or_else(|| {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: No HEAD commit. Please provide a valid ref."
+                    )
+                );
+                std::process::exit(1);
+            })
+        }
+    }
+
+    /// Explain why `start` found nothing to present, and exit.
+    fn exit_no_slides(
+        &self,
+        tags: Option<&str>,
+        manifest: Option<&str>,
+        pattern: Option<&str>,
+    ) -> ! {
+        if let Some(tags) = tags {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: No tag matches '{}'.", tags)
+            );
+        } else if let Some(manifest) = manifest {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Manifest '{}' lists no slides.",
+                    manifest
+                )
+            );
+        } else if let Some(pattern) = pattern {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: No commit matches '{}'.",
+                    pattern
+                )
+            );
+        } else {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: No commits to present.")
+            );
+        }
+        std::process::exit(1);
+    }
+
+    /// Whether there are uncommitted changes `start` must deal with,
+    /// exiting if it was not told how to.
+    fn check_working_directory(&self, options: &StartOptions) -> bool {
+        if options.stash && options.force {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: '--stash' cannot be combined with '--force'."
+                )
+            );
+            std::process::exit(1);
+        }
+        // A worktree leaves the working directory alone.
+        let is_dirty = !options.worktree && !self.git.is_working_directory_clean();
+        if is_dirty && !options.stash && !options.force {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Working directory contains uncommitted changes."
+                )
+            );
+            std::process::exit(1);
+        }
+        is_dirty
+    }
+
+    /// Stash (`--stash`) or discard (`--force`) uncommitted changes
+    /// before starting, returning the stash commit, if any.
+    fn clean_before_start(&self, stash: bool, yes: bool) -> Option<String> {
+        if stash {
+            return Some(self.stash_before_start());
+        }
+
+        if !yes {
+            eprint!("{}", self.git.uncommitted_changes());
+            let question = tr!(self.options.language, "Discard these changes?");
+            if !console::confirm(&question) {
+                eprintln!("{}", tr!(self.options.language, "Aborting."));
+                std::process::exit(1);
+            }
+        }
+        if let Err(stderr) = self.git.discard_changes() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Could not discard uncommitted changes."
+                )
+            );
+            relay_git_error(&stderr);
+            std::process::exit(1);
+        }
+        self.print_info(&tr!(
+            self.options.language,
+            "Discarded uncommitted changes."
+        ));
+        None
+    }
+
+    /// Stash uncommitted changes for `start --stash`, returning the
+    /// stash commit.
+    fn stash_before_start(&self) -> String {
+        match self.git.stash("git-slides: before presentation") {
+            Ok(hash) => {
+                self.print_info(&tr!(self.options.language, "Stashed uncommitted changes."));
+                hash
+            }
+            Err(stderr) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not stash uncommitted changes."
+                    )
+                );
+                relay_git_error(&stderr);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Bring back the changes stashed by `start --stash`, once back
+    /// where the presentation started.
+    fn restore_start_stash(&self) {
+        let Some(hash) = &self.get_store().start_stash else {
+            return;
+        };
+        // Dropped by the user in the meantime, forget about it.
+        let Some(index) = self.git.stash_index(hash) else {
+            return;
+        };
+        match self.git.stash_pop(index) {
+            Ok(()) => self.print_info(&tr!(
+                self.options.language,
+                "Restored changes from before the presentation."
+            )),
+            Err(stderr) => {
+                eprintln!("{}", tr!(self.options.language, "warning: Could not restore changes from before the presentation, they are still stashed."));
+                relay_git_error(&stderr);
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "hint: Use 'git stash apply {}' to restore them by hand.",
+                        hash
+                    )
+                );
+            }
+        }
+    }
+
+    /// Go back to the slide the presentation is on, or to the last one
+    /// shown if HEAD has moved away since.
+    fn resume(&mut self) {
+        if !self.is_presentation_started() {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: No presentation to resume.")
+            );
+            std::process::exit(1);
+        }
+
+        let total = self.get_history().len();
+        let n = self
+            .get_index_of_current_commit_checked()
+            .map(|n| n + 1)
+            .or_else(|| {
+                let last = self.get_store().transitions.last()?;
+                Some(last.slide.min(total))
+            })
+            .unwrap_or(1);
+
+        self.print_info(&tr!(self.options.language, "Resuming presentation."));
+
+        exit_on_error(self.go(n));
+    }
+
+    /// Create the presentation's worktree, and run Git commands in it
+    /// from now on.
+    fn create_worktree(&self, head: &str) -> String {
+        let path = self.store_sibling("-worktree");
+        if !git::add_worktree(&path, head) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Could not create a worktree in '{}'.",
+                    self.display_path(&path)
+                )
+            );
+            std::process::exit(1);
+        }
+        let path = path.display().to_string();
+        self.use_worktree(&path);
+        self.print_info(&tr!(
+            self.options.language,
+            "Presenting in worktree '{}'.",
+            path
+        ));
+        path
+    }
+
+    /// Run Git commands in the presentation's worktree, if it has one.
+    ///
+    /// The current directory is kept, so paths given by the user still
+    /// mean the same thing.
+    pub fn enter_worktree(&self) {
+        let Ok(content) = fs::read_to_string(self.store_file()) else {
+            return;
+        };
+        // Commands reading the store will complain, if need be.
+        let Ok((
+            Store {
+                worktree: Some(worktree),
+                ..
+            },
+            _,
+        )) = Store::parse(&content)
+        else {
+            return;
+        };
+        self.use_worktree(&worktree);
+    }
+
+    fn use_worktree(&self, path: &str) {
+        let path = Path::new(path);
+        let Some(git_dir) = git::worktree_git_dir(path) else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Cannot find worktree '{}'.",
+                    path.display()
+                )
+            );
+            return;
+        };
+        // Git sub-processes inherit the environment.
+        std::env::set_var("GIT_DIR", git_dir);
+        std::env::set_var("GIT_WORK_TREE", path);
+    }
+
+    /// Slides listed explicitly (tags, manifest), instead of history.
+    fn explicit_slides(&self, options: &StartOptions) -> Option<Vec<Commit>> {
+        let slides = match (&options.tags, &options.manifest) {
+            (Some(_), Some(_)) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: '--tags' cannot be combined with '--manifest'."
+                    )
+                );
+                std::process::exit(1);
+            }
+            (Some(tags), None) => Some(self.tagged_slides(tags, options.tag_order)),
+            (None, Some(manifest)) => Some(self.manifest_slides(manifest)),
+            (None, None) => None,
+        };
+
+        if slides.is_some() && (options.from.is_some() || options.pattern.is_some()) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Explicit slides cannot be combined with '--from' or '--match'."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        slides
+    }
+
+    /// One section per ref, with the commits of each ref that earlier
+    /// refs don't have (e.g., one branch per chapter).
+    fn chapter_slides(&self, options: &StartOptions) -> (Option<Vec<Commit>>, Vec<Section>) {
+        if options.tags.is_some() || options.manifest.is_some() || options.from.is_some() {
+            eprintln!("{}", tr!(self.options.language, "error: Several refs cannot be combined with '--tags', '--manifest' or '--from'."));
+            std::process::exit(1);
+        }
+
+        let mut slides = Vec::new();
+        let mut sections = Vec::new();
+        let mut presented = Vec::new();
+        for ref_ in options.ref_.iter().chain(&options.more_refs) {
+            let Some(hash) = self.git.ref_to_commit_hash(ref_) else {
+                eprintln!(
+                    "{}",
+                    tr!(self.options.language, "error: Bad ref input: '{}'.", ref_)
+                );
+                std::process::exit(1);
+            };
+            let commits = self.git.history_up_to_commit(
+                &hash,
+                &HistoryOptions {
+                    all_parents: options.all_parents || self.options.all_parents,
+                    pattern: options.pattern.as_deref(),
+                    exclude: &presented,
+                    ..HistoryOptions::default()
+                },
+            );
+            if commits.is_empty() {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: '{}' has no commits that the refs before it don't have.",
+                        ref_
+                    )
+                );
+                std::process::exit(1);
+            }
+            sections.push(Section {
+                title: ref_.clone(),
+                first: slides.len() + 1,
+            });
+            slides.extend(commits);
+            presented.push(hash);
+        }
+
+        (Some(slides), sections)
+    }
+
+    /// Branches pointing to a slide before the last one are easy to
+    /// mistake for where to commit, once there.
+    fn warn_about_branches_in_slides(&self, history: &[Commit]) {
+        let Some((_, slides)) = history.split_last() else {
+            return;
+        };
+        let mut warned = false;
+        for (branch, hash) in git::branches() {
+            if let Some(n) = slides.iter().position(|slide| slide.hash == hash) {
+                eprintln!("{}", tr!(self.options.language, "warning: Branch '{}' points to slide {}/{}, in the middle of the presentation.", branch, n + 1, history.len()));
+                warned = true;
+            }
+        }
+        if warned {
+            eprintln!("{}", tr!(self.options.language, "hint: Commits made on a slide are not on any branch, use 'git switch -c <branch>' to keep them."));
+        }
+    }
+
+    /// In a shallow clone, history stops short of the first slides.
+    fn handle_shallow_clone(&self, deepen: bool) {
+        if !git::is_shallow() {
+            return;
+        }
+        if deepen {
+            self.print_info(&tr!(self.options.language, "Fetching the full history..."));
+            if !git::unshallow() {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not fetch the full history."
+                    )
+                );
+                std::process::exit(1);
+            }
+        } else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: This is a shallow clone, the first slides may be missing."
+                )
+            );
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "hint: Use 'git slides start --deepen' to fetch the full history."
+                )
+            );
+        }
+    }
+
+    /// Commit hash of `start --from`, which must lead to `head`.
+    fn resolve_from(&self, from: &str, head: &str) -> String {
+        let Some(from_hash) = self.git.ref_to_commit_hash(from) else {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Bad ref input: '{}'.", from)
+            );
+            std::process::exit(1);
+        };
+        if !self.git.is_ancestor(&from_hash, head) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: '{}' is not an ancestor of the last slide.",
+                    from
+                )
+            );
+            std::process::exit(1);
+        }
+        from_hash
+    }
+
+    /// Number of the slide `start` begins at.
+    fn start_index(&self, at: StartAt, current: Option<&str>, history: &[Commit]) -> usize {
+        let n = match at {
+            StartAt::First => 1,
+            StartAt::Index(n) => n,
+            StartAt::Current => current
+                .and_then(|hash| history.iter().position(|x| x.hash == hash))
+                .map_or_else(
+                    || {
+                        eprintln!(
+                            "{}",
+                            tr!(
+                                self.options.language,
+                                "error: Current HEAD not part of presentation."
+                            )
+                        );
+                        std::process::exit(1);
+                    },
+                    |n| n + 1,
+                ),
+        };
+        if n < 1 || n > history.len() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Bad slide index. Slide {} does not exist.",
+                    n
+                )
+            );
+            std::process::exit(1);
+        }
+
+        n
+    }
+
+    pub fn stop(&mut self, at: &StopAt) {
+        self.ensure_presentation_is_started();
+
+        let env = self
+            .get_index_of_current_commit_checked()
+            .map(|n| self.hook_env(n))
+            .unwrap_or_default();
+        if hooks::run(&self.git_dir, "pre-stop", &env) == Some(false) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: The 'pre-stop' hook failed. Aborting."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        if let StopAt::Ref(ref_) = at {
+            if self.get_store().worktree.is_some() {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: '--go' cannot be used with a presentation in a worktree."
+                    )
+                );
+                std::process::exit(1);
+            }
+            if self.git.ref_to_commit_hash(ref_).is_none() {
+                eprintln!(
+                    "{}",
+                    tr!(self.options.language, "error: Bad ref input: '{}'.", ref_)
+                );
+                std::process::exit(1);
+            }
+        }
+
+        let keep = matches!(at, StopAt::Current);
+        // Changes are kept too, to carry on working.
+        if !keep {
+            exit_on_error(self.ensure_no_operation_in_progress());
+            exit_on_error(self.stash_uncommitted_changes());
+        }
+
+        self.print_info(&tr!(self.options.language, "Presentation stopped."));
+
+        // Nothing to show anymore.
+        self.write_info_file("");
+        self.leave_presentation(at);
+
+        if !keep && self.get_store().worktree.is_none() {
+            self.post_checkout();
+        }
+        if matches!(at, StopAt::Initial) {
+            self.restore_start_stash();
+        } else if self.get_store().start_stash.is_some() {
+            self.print_info(&tr!(
+                self.options.language,
+                "Changes from before the presentation are still stashed."
+            ));
+        }
+
+        // Keep a copy, for `timeline` and `stats` after the talk.
+        let mut store = self.take_store();
+        let stopped = time::now();
+        store.stopped = Some(stopped);
+        store.cache = None;
+        let run_file = self
+            .runs_dir()
+            .join(store.started.unwrap_or(stopped).to_string());
+        if fs::create_dir_all(self.runs_dir())
+            .and_then(|()| fs::write(run_file, store.to_string()))
+            .is_err()
+        {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Cannot write to '{}'.",
+                    self.display_path(&self.runs_dir())
+                )
+            );
+        }
+
+        let store_file = self.store_file();
+        #[cfg(not(tarpaulin_include))]
+        {
+            if fs::remove_file(&store_file).is_err() {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Cannot remove '{}'. Aborting.",
+                        self.display_path(&store_file)
+                    )
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Leave HEAD where `at` says, once the presentation is stopped.
+    fn leave_presentation(&self, at: &StopAt) {
+        if let StopAt::Current = at {
+            let current = self.git.current_commit_hash().unwrap_or_default();
+            self.print_info(&tr!(
+                self.options.language,
+                "Staying on commit {}.",
+                current
+            ));
+        } else if let StopAt::Ref(ref_) = at {
+            self.print_info(&tr!(self.options.language, "Going to '{}'.", ref_));
+            if let Err(stderr) = self.git.checkout(ref_) {
+                eprintln!(
+                    "{}",
+                    tr!(self.options.language, "error: Could not checkout {}.", ref_)
+                );
+                relay_git_error(&stderr);
+            }
+        } else if let Some(worktree) = &self.get_store().worktree {
+            // The main working tree was never touched.
+            if git::remove_worktree(&self.git_dir, Path::new(worktree)) {
+                self.print_info(&tr!(
+                    self.options.language,
+                    "Removed worktree '{}'.",
+                    worktree
+                ));
+            } else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: Could not remove worktree '{}'.",
+                        worktree
+                    )
+                );
+            }
+        } else if let Some(initial_branch) = &self.get_store().branch {
+            self.print_info(&tr!(
+                self.options.language,
+                "Going back to branch '{}'.",
+                initial_branch
+            ));
+            if let Err(stderr) = self.git.checkout(initial_branch) {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not checkout {}.",
+                        initial_branch
+                    )
+                );
+                relay_git_error(&stderr);
+            }
+        } else {
+            // The user was likely in detached mode when the presentation started.
+            let head_commit = &self.get_store().head;
+            self.print_info(&tr!(
+                self.options.language,
+                "Going back to commit {}.",
+                head_commit
+            ));
+            if let Err(stderr) = self.git.checkout(head_commit) {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not checkout {}.",
+                        head_commit
+                    )
+                );
+                relay_git_error(&stderr);
+            }
+        }
+    }
+
+    /// Go back to the slide visited before the current one, like
+    /// alt-tab, to compare two slides back and forth.
+    pub fn swap(&mut self) -> Result<(), Exit> {
+        self.check_presentation_is_started()?;
+
+        let n = self.try_get_index_of_current_commit()?;
+        let history = self.get_history();
+        let current = &history[n].hash;
+        let previous = self
+            .get_store()
+            .transitions
+            .iter()
+            .rev()
+            .filter(|transition| transition.hash != *current)
+            .find_map(|transition| history.iter().position(|x| x.hash == transition.hash));
+
+        let Some(previous) = previous else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: No previous slide to go back to."
+                )
+            );
+            return Err(Exit(1));
+        };
+        self.go(previous + 1)?;
+
+        Ok(())
+    }
+
+    /// Go back to the first slide, and start the clock over (for
+    /// pacing and `timeline`), without leaving the presentation.
+    pub fn restart(&mut self) -> Result<(), Exit> {
+        self.check_presentation_is_started()?;
+
+        if !self.options.dry_run {
+            let mut store = self.take_store();
+            store.started = Some(time::now());
+            store.transitions.clear();
+            self.write_store_file(&store);
+            self.store = OnceCell::from(store);
+
+            self.print_info(&tr!(self.options.language, "Presentation restarted."));
+        }
+
+        self.go(1)?;
+
+        Ok(())
+    }
+
+    /// Go forward `offset` slides (default: `slides.step`, or 1).
+    pub fn next(&mut self, offset: Option<usize>) -> Result<(), Exit> {
+        self.check_presentation_is_started()?;
+
+        let offset = offset.unwrap_or(self.options.step);
+        let total = self.get_history().len();
+        let n = self.try_get_index_of_current_commit()?;
+
+        if self.options.loop_ {
+            return self.go((n + offset) % total + 1);
+        }
+
+        let n = n + 1 + offset;
+
+        if n >= total {
+            self.print_info(&tr!(
+                self.options.language,
+                "You've reached the end of the presentation."
+            ));
+        }
+
+        self.go(cmp::min(n, total))?;
+
+        if n > total {
+            self.exit_if_strict()?;
+        }
+
+        Ok(())
+    }
+
+    /// Go back `offset` slides (default: `slides.step`, or 1).
+    pub fn previous(&mut self, offset: Option<usize>) -> Result<(), Exit> {
+        self.check_presentation_is_started()?;
+
+        let offset = offset.unwrap_or(self.options.step);
+        let n = self.try_get_index_of_current_commit()?;
+
+        if self.options.loop_ {
+            let total = self.get_history().len();
+            return self.go((n + total - offset % total) % total + 1);
+        }
+
+        let past_start = offset > n;
+        let n = (n + 1).saturating_sub(offset);
+
+        if n <= 1 {
+            self.print_info(&tr!(
+                self.options.language,
+                "You're at the start of the presentation."
+            ));
+        }
+
+        self.go(cmp::max(n, 1))?;
+
+        if past_start {
+            self.exit_if_strict()?;
+        }
+
+        Ok(())
+    }
+
+    /// Going past a boundary is not an error, except in `--strict` mode,
+    /// so that scripts can tell.
+    fn exit_if_strict(&self) -> Result<(), Exit> {
+        if self.options.strict {
+            return Err(Exit(EXIT_BOUNDARY));
+        }
+
+        Ok(())
+    }
+
+    pub fn go_to(&mut self, target: GoTarget) -> Result<(), Exit> {
+        match target {
+            GoTarget::Index(n) => self.go(n),
+            GoTarget::Percentage(percentage) => {
+                self.check_presentation_is_started()?;
+                let last = self.get_commits_hashes().len().saturating_sub(1);
+                // Nearest slide, rounded half up.
+                self.go((percentage * last + 50) / 100 + 1)
+            }
+            GoTarget::Ref(ref_) => {
+                self.check_presentation_is_started()?;
+                let Some(hash) = self.git.ref_to_commit_hash(&ref_) else {
+                    eprintln!(
+                        "{}",
+                        tr!(self.options.language, "error: Bad ref input: '{}'.", ref_)
+                    );
+                    return Err(Exit(1));
+                };
+                let Some(n) = self.get_index_of_slide_containing(&hash) else {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "error: '{}' is not part of the presentation.",
+                            ref_
+                        )
+                    );
+                    return Err(Exit(1));
+                };
+                self.go(n + 1)
+            }
+        }
+    }
+
+    pub fn go(&mut self, n: usize) -> Result<(), Exit> {
+        self.check_presentation_is_started()?;
+
+        let commits = self.get_commits_hashes();
+
+        if n < 1 || n > commits.len() {
+            let language = self.options.language;
+            eprintln!(
+                "{}",
+                tr!(
+                    language,
+                    "error: Bad slide index. Slide {} does not exist.",
+                    n
+                )
+            );
+            eprintln!(
+                "{}",
+                tr!(
+                    language,
+                    "Possible values range from 1 to {}.",
+                    commits.len()
+                )
+            );
+            return Err(Exit(1));
+        }
+
+        let go_to = (*commits.get(n - 1).expect("bounds checked")).clone();
+
+        if self.options.dry_run {
+            self.print_dry_run(n);
+            return Ok(());
+        }
+
+        self.ensure_no_operation_in_progress()?;
+
+        self.handle_uncommitted_changes()?;
+
+        if let Err(stderr) = self.git.checkout(&go_to) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Could not checkout {}.",
+                    go_to
+                )
+            );
+            relay_git_error(&stderr);
+            return Err(Exit(1));
+        }
+
+        if self.options.scratch {
+            self.restore_scratch(n);
+        }
+
+        let checkpoint = self.crossed_checkpoint();
+
+        // For pacing (see `Slide-Duration`) and `timeline`.
+        let title = self.get_history()[n - 1].title.clone();
+        let mut store = self.take_store();
+        store.transitions.push(Transition {
+            at: time::now(),
+            slide: n,
+            hash: go_to.clone(),
+            title,
+        });
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+
+        self.post_checkout();
+        self.publish_current_slide(&go_to);
+        self.update_tmux(n - 1);
+
+        if self.options.terminal_title {
+            let history = self.get_history();
+            console::set_terminal_title(&format!(
+                "{n}/{} – {}",
+                history.len(),
+                self.display_title(&history[n - 1].title),
+            ));
+        }
+
+        if !self.options.quiet {
+            self.status_at(n - 1);
+            if let Some(note) = self.git.note(NOTES_REF, &go_to) {
+                println!("\n{note}");
+            }
+        }
+
+        if let Some(checkpoint) = checkpoint {
+            self.alert_checkpoint(checkpoint);
+        }
+
+        self.write_info_file(&self.expand_placeholders(
+            &self.options.info_format,
+            n - 1,
+            str::to_string,
+        ));
+        self.run_viewer(n - 1);
+        self.hand_off_slide_file(n - 1);
+        self.run_slide_exec(&go_to);
+        self.run_on_slide(n - 1);
+
+        if hooks::run(&self.git_dir, "post-slide", &self.hook_env(n - 1)) == Some(false) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: The 'post-slide' hook failed."
+                )
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Tell what going to slide `n` (1-indexed) would do.
+    fn print_dry_run(&self, n: usize) {
+        let language = self.options.language;
+
+        if !self.git.is_working_directory_clean() {
+            let message = match self.options.dirty_action {
+                DirtyAction::Stash => tr!(language, "Would stash uncommitted changes."),
+                DirtyAction::Block => tr!(
+                    language,
+                    "Would refuse to change slides, there are uncommitted changes."
+                ),
+                DirtyAction::Discard => tr!(language, "Would discard uncommitted changes."),
+            };
+            println!("{message}");
+        }
+
+        let history = self.get_history();
+        let Commit { hash, title } = &history[n - 1];
+        println!(
+            "{}",
+            tr!(
+                language,
+                "Would check out slide {}/{}: {} {}",
+                n,
+                history.len(),
+                self.short_hash(hash),
+                self.display_title(title)
+            )
+        );
+
+        if self.options.scratch
+            && self
+                .get_store()
+                .stashes
+                .iter()
+                .any(|stash| stash.slide == Some(n))
+        {
+            println!("{}", tr!(language, "Would restore changes of slide {}.", n));
+        }
+    }
+
+    pub fn reorder(&mut self) {
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+
+        let mut todo = String::with_capacity(history.len() * 72);
+        for Commit { hash, title } in history {
+            let _ = writeln!(todo, "{hash} {title}");
+        }
+        let _ = write!(
+            todo,
+            "
+# Reorder slides by moving lines, drop slides by removing lines.
+# Titles can be edited, and any ref can be added as '<ref> [<title>]'.
+# Lines starting with '#' are ignored. An empty list aborts.
+"
+        );
+
+        let todo_file = self.store_sibling("-reorder");
+        if fs::write(&todo_file, todo).is_err() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot write '{}'. Aborting.",
+                    todo_file.display()
+                )
+            );
+            std::process::exit(1);
+        }
+
+        let edited = git::edit(&todo_file).then(|| fs::read_to_string(&todo_file));
+        let _ = fs::remove_file(&todo_file);
+        let Some(Ok(edited)) = edited else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: There was a problem with the editor. Aborting."
+                )
+            );
+            std::process::exit(1);
+        };
+
+        let slides = self.resolve_manifest(&edited);
+        if slides.is_empty() {
+            self.print_info(&tr!(
+                self.options.language,
+                "Nothing to present, keeping the current order."
+            ));
+            return;
+        }
+
+        let current = self.git.current_commit_hash();
+        let current_is_kept = slides.iter().any(|x| Some(&x.hash) == current.as_ref());
+
+        let store = self.reordered_store(&slides);
+        self.set_store(store);
+
+        self.print_info(&tr!(self.options.language, "Slides reordered."));
+
+        if current_is_kept {
+            self.echo_status();
+        } else {
+            // The current slide was dropped.
+            exit_on_error(self.go(1));
+        }
+    }
+
+    /// The store with `slides` (as reordered) as slides.
+    ///
+    /// Hidden slides are not part of the reordering, they stay after
+    /// the slide they followed. Sections start at the same slides as
+    /// before, wherever those are now. Everything else (worktree,
+    /// stashes, transitions, etc.) stays as it is.
+    fn reordered_store(&self, slides: &[Commit]) -> Store {
+        let store = self.get_store();
+        let is_hidden = |slide: &Commit| store.hidden.iter().any(|x| x.hash == slide.hash);
+        let old_history = self.history_from_store(store);
+
+        // Hidden slides, by the visible slide they follow (`None` if
+        // they come first, or if it was dropped).
+        let mut followers: HashMap<Option<&str>, Vec<Commit>> = HashMap::new();
+        let mut previous = None;
+        for slide in &old_history {
+            if is_hidden(slide) {
+                let previous = previous.filter(|hash| slides.iter().any(|x| x.hash == *hash));
+                followers.entry(previous).or_default().push(slide.clone());
+            } else {
+                previous = Some(slide.hash.as_str());
+            }
+        }
+        let mut new_history = followers.remove(&None).unwrap_or_default();
+        for slide in slides {
+            new_history.push(slide.clone());
+            if let Some(hidden) = followers.remove(&Some(slide.hash.as_str())) {
+                new_history.extend(hidden);
+            }
+        }
+
+        let mut sections: Vec<Section> = store
+            .sections
+            .iter()
+            .filter_map(|Section { title, first }| {
+                let hash = &old_history.get(first.checked_sub(1)?)?.hash;
+                let first = new_history.iter().position(|x| x.hash == *hash)? + 1;
+                Some(Section {
+                    title: title.clone(),
+                    first,
+                })
+            })
+            .collect();
+        sections.sort_by_key(|section| section.first);
+        sections.dedup_by_key(|section| section.first);
+
+        Store {
+            slides: Some(new_history),
+            sections,
+            cache: None,
+            slide_count: None,
+            checksum: None,
+            ..store.clone()
+        }
+    }
+
+    pub fn insert(&mut self, message: Option<&str>) {
+        self.ensure_presentation_is_started();
+
+        let n = self.get_index_of_current_commit();
+
+        if !git::commit(message, false) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Could not create the new slide."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        self.rewrite_slide(n, SlideRewrite::Insert);
+    }
+
+    pub fn edit(&mut self, message: Option<&str>) {
+        self.ensure_presentation_is_started();
+
+        if message.is_none() && !git::has_staged_changes() {
+            self.print_info(&tr!(
+                self.options.language,
+                "No staged changes, the slide is unchanged."
+            ));
+            return;
+        }
+
+        self.amend_current_slide(message);
+    }
+
+    pub fn absorb(&mut self) {
+        self.ensure_presentation_is_started();
+
+        if !git::stage_tracked_changes() {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Could not stage changes.")
+            );
+            std::process::exit(1);
+        }
+
+        if !git::has_staged_changes() {
+            self.print_info(&tr!(
+                self.options.language,
+                "No changes, the slide is unchanged."
+            ));
+            return;
+        }
+
+        self.amend_current_slide(None);
+    }
+
+    /// Amend the current slide with what is staged, and rebase the
+    /// following slides onto it.
+    fn amend_current_slide(&mut self, message: Option<&str>) {
+        let n = self.get_index_of_current_commit();
+
+        if !git::commit(message, true) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Could not amend the current slide."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        self.rewrite_slide(n, SlideRewrite::Replace);
+    }
+
+    pub fn status(&self) -> Result<(), Exit> {
+        self.check_presentation_is_started()?;
+
+        self.status_at(self.try_get_index_of_current_commit()?);
+
+        Ok(())
+    }
+
+    /// Show the status, knowing that the current slide is `n`
+    /// (0-indexed), which spares asking Git.
+    fn status_at(&self, n: usize) {
+        const SHOW_N_PREVIOUS: usize = 2;
+        const SHOW_N_NEXT: usize = 3;
+
+        let history = self.get_history();
+
+        let display_from = n.saturating_sub(SHOW_N_PREVIOUS);
+        let display_to = std::cmp::min(n + SHOW_N_NEXT, history.len() - 1);
+
+        let (numbers, total) = self.slide_numbers();
+        let Colors {
+            reset,
+            dim,
+            hash: hash_color,
+            ..
+        } = &self.options.colors;
+
+        let columns = if self.options.columns.any() {
+            self.render_columns(&Self::commits_details(&history[display_from..=display_to]))
+        } else {
+            Vec::new()
+        };
+
+        // Acquire the lock once (instead of on every call to `print!`).
+        let mut stdout = io::stdout().lock();
+
+        let _ = writeln!(
+            stdout,
+            "{}{}\n",
+            self.progress_bar(n + 1, history.len()),
+            self.pacing(&history[n].hash),
+        );
+
+        if n.checked_sub(SHOW_N_PREVIOUS).is_none() {
+            let start = tr!(self.options.language, "(Start)");
+            let _ = writeln!(stdout, "  {dim}{start}{reset}");
+        }
+
+        for i in display_from..=display_to {
+            let Commit { hash, title } = history.get(i).expect("bounds have been checked");
+            let number = numbers.get(i).expect("bounds have been checked");
+            let title = self.display_title(title);
+            let columns = columns.get(i - display_from).map_or("", String::as_str);
+
+            if let Some(section) = self.section_starting_at(i) {
+                let _ = writeln!(stdout, "{dim}{section}{reset}");
+            }
+
+            if i == n {
+                let _ = write!(stdout, "* ");
+            } else {
+                let _ = write!(stdout, "  ");
+            }
+
+            if i < n {
+                let _ = writeln!(
+                    stdout,
+                    "{dim}{}/{total} {}{} {columns}{title}{reset}",
+                    number,
+                    self.short_hash(hash),
+                    self.decoration(hash),
+                );
+            } else {
+                let _ = writeln!(
+                    stdout,
+                    "{}/{total} {hash_color}{}{}{reset} {columns}{title}",
+                    number,
+                    self.short_hash(hash),
+                    self.decoration(hash),
+                );
+            }
+        }
+
+        if n + SHOW_N_NEXT > history.len() - 1 {
+            let end = tr!(self.options.language, "(End)");
+            let _ = writeln!(stdout, "  {dim}{end}{reset}");
+        }
+    }
+
+    /// Print the current slide on a single line, for status bars and
+    /// prompts (e.g., `12/40  a1b2c3d  Closures`).
+    pub fn status_oneline(&self) {
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+        let n = self.get_index_of_current_commit();
+        let Commit { hash, title } = &history[n];
+        let Colors {
+            reset,
+            hash: hash_color,
+            ..
+        } = &self.options.colors;
+
+        println!(
+            "{}/{}  {hash_color}{}{reset}  {}",
+            n + 1,
+            history.len(),
+            self.short_hash(hash),
+            self.display_title(title),
+        );
+    }
+
+    /// Print presentation metadata, from the store.
+    pub fn info(&self) {
+        self.ensure_presentation_is_started();
+
+        let store = self.get_store();
+
+        let started = store.started.map_or_else(
+            || String::from("unknown"),
+            |started| {
+                format!(
+                    "{} ({} ago)",
+                    time::format_timestamp(started),
+                    time::format_duration(time::now().saturating_sub(started)),
+                )
+            },
+        );
+
+        println!("Head:          {}", store.head);
+        println!(
+            "Branch:        {}",
+            store.branch.as_deref().unwrap_or("(detached)")
+        );
+        println!("Started:       {started}");
+        println!("Slides:        {}", self.get_history().len());
+        if !store.sections.is_empty() {
+            println!("Sections:      {}", store.sections.len());
+        }
+        if !store.hidden.is_empty() {
+            println!("Hidden:        {}", store.hidden.len());
+        }
+        println!("Stashes:       {}", store.stashes.len());
+        println!("Store file:    {}", self.store_file().display());
+        println!("Store format:  {}", Store::VERSION);
+    }
+
+    /// Print the current slide number and the total (e.g., `12 40`).
+    pub fn status_index(&self) {
+        self.ensure_presentation_is_started();
+
+        let n = self.get_index_of_current_commit();
+
+        println!("{} {}", n + 1, self.get_history().len());
+    }
+
+    /// Expand `%s` (title), `%h`/`%H` (hash), `%i` (number) and `%t`
+    /// (total) for slide `n` (0-indexed). The title goes through
+    /// `quote` first.
+    fn expand_placeholders(&self, format: &str, n: usize, quote: fn(&str) -> String) -> String {
+        let history = self.get_history();
+        let Commit { hash, title } = &history[n];
+
+        let mut out = String::with_capacity(format.len() + title.len());
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('s') => out.push_str(&quote(self.display_title(title))),
+                Some('h') => out.push_str(self.short_hash(hash)),
+                Some('H') => out.push_str(hash),
+                Some('i') => out.push_str(&(n + 1).to_string()),
+                Some('t') => out.push_str(&history.len().to_string()),
+                // `%%`, or a trailing `%`.
+                Some('%') | None => out.push('%'),
+                // Unknown placeholders are kept as is, like Git does.
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+            }
+        }
+        out
+    }
+
+    /// Print the current slide as shell variables, for `eval`.
+    pub fn env(&self) {
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+        let n = self.get_index_of_current_commit();
+        let Commit { hash, title } = &history[n];
+
+        println!("SLIDE_INDEX={}", n + 1);
+        println!("SLIDE_TOTAL={}", history.len());
+        println!("SLIDE_HASH={hash}");
+        println!("SLIDE_TITLE={}", shell_quote(title));
+    }
+
+    /// Print the current slide, formatted with `format`.
+    ///
+    /// Placeholders are `%s` (title), `%h` (short hash), `%H` (hash),
+    /// `%i` (slide number), `%t` (number of slides), and `%%`.
+    pub fn current(&self, format: &str) {
+        self.ensure_presentation_is_started();
+
+        let n = self.get_index_of_current_commit();
+        let out = self.expand_placeholders(format, n, str::to_string);
+
+        println!("{out}");
+    }
+
+    /// Save the presentation state to `file`, to continue it elsewhere
+    /// with `import-state` (e.g., on another clone).
+    pub fn export_state(&self, file: &str) {
+        self.ensure_presentation_is_started();
+
+        let mut store = Store {
+            position: self.get_index_of_current_commit_checked().map(|n| n + 1),
+            ..self.get_store().clone()
+        };
+        // Only meaningful on this machine.
+        store.worktree = None;
+        store.stashes.clear();
+
+        if fs::write(file, store.to_string()).is_err() {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Cannot write '{}'.", file)
+            );
+            std::process::exit(1);
+        }
+        self.print_info(&tr!(
+            self.options.language,
+            "Presentation state exported to '{}'.",
+            file
+        ));
+    }
+
+    /// Continue a presentation exported with `export-state`, where it
+    /// was left.
+    pub fn import_state(&mut self, file: &str) {
+        if self.is_presentation_started() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: A presentation is already in progress."
+                )
+            );
+            std::process::exit(1);
+        }
+        if !self.git.is_working_directory_clean() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Working directory contains uncommitted changes."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        let Ok(content) = fs::read_to_string(file) else {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Cannot read '{}'.", file)
+            );
+            std::process::exit(1);
+        };
+        let Ok((mut store, _)) = Store::parse(&content) else {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Cannot parse '{}'.", file)
+            );
+            std::process::exit(1);
+        };
+        if self.git.ref_to_commit_hash(&store.head).is_none() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Commit {} is not in this repository, fetch it first.",
+                    store.head
+                )
+            );
+            std::process::exit(1);
+        }
+
+        let n = store.position.take().unwrap_or(1);
+        // Not here, the current branch is what to go back to.
+        store.branch = git::current_branch();
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Presentation state imported from '{}'.",
+            file
+        ));
+
+        exit_on_error(self.go(n));
+    }
+
+    /// Publish the current slide to `remote` on every navigation, for
+    /// the audience to `follow`, or stop publishing (`None`).
+    pub fn publish(&mut self, remote: Option<&str>) {
+        self.ensure_presentation_is_started();
+
+        let mut store = self.take_store();
+        store.publish = remote.map(String::from);
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+
+        let Some(remote) = remote else {
+            self.print_info(&tr!(self.options.language, "Stopped publishing."));
+            return;
+        };
+
+        let n = self.get_index_of_current_commit();
+        let hash = self.get_history()[n].hash.clone();
+        if !git::force_push(remote, &hash, PUBLISHED_REF) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Could not publish the current slide to '{}'.",
+                    remote
+                )
+            );
+            std::process::exit(1);
+        }
+        self.print_info(&tr!(
+            self.options.language,
+            "Publishing slides to '{}'.",
+            remote
+        ));
+    }
+
+    /// Serve the current slide on `http://127.0.0.1:<port>/`, until
+    /// interrupted.
+    ///
+    /// The page polls `/hash`, and reloads when the slide changes.
+    pub fn web(&self, port: u16) {
+        const REFRESH: &str = r#"<script>
+const hash = document.currentScript.dataset.hash;
+setInterval(async () => {
+  try {
+    const current = await (await fetch("/hash")).text();
+    if (current !== hash) location.reload();
+  } catch (e) {}
+}, 1000);
+</script>"#;
+
+        self.ensure_presentation_is_started();
+
+        // Local only, this is not meant to be exposed.
+        let Ok(listener) = TcpListener::bind(("127.0.0.1", port)) else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot listen on port {}.",
+                    port
+                )
+            );
+            std::process::exit(1);
+        };
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Serving the current slide on http://127.0.0.1:{}/. Press Ctrl-C to stop.",
+            port
+        ));
+
+        web::serve(&listener, |path| match path {
+            "/" => {
+                let hash = self.git.current_commit_hash().unwrap_or_default();
+                let refresh = REFRESH.replace(
+                    "<script>",
+                    &format!(r#"<script data-hash="{}">"#, html::escape(&hash)),
+                );
+                Response::ok(
+                    "text/html; charset=utf-8",
+                    html::page(&self.html_slide(&hash), &refresh),
+                )
+            }
+            "/hash" => Response::ok(
+                "text/plain; charset=utf-8",
+                self.git.current_commit_hash().unwrap_or_default(),
+            ),
+            _ => Response::not_found(),
+        });
+    }
+
+    /// Show the current slide full-screen, and navigate from the
+    /// keyboard: Enter or `n` for next, `p` for previous, a number to
+    /// go to that slide, `q` to quit.
+    ///
+    /// Input is line-based, so it works in any terminal.
+    pub fn present(&mut self) {
+        self.ensure_presentation_is_started();
+
+        // Anything printed would be cleared right away by the slide.
+        self.options.quiet = true;
+        self.options.strict = false;
+        let bold = !self.options.colors.reset.is_empty();
+
+        let mut input = String::new();
+        loop {
+            self.render_screen(bold);
+
+            input.clear();
+            if matches!(io::stdin().read_line(&mut input), Ok(0) | Err(_)) {
+                break;
+            }
+
+            let _lock = self.lock();
+            // Others may navigate too (e.g., from a hotkey).
+            self.store = OnceCell::new();
+
+            match input.trim() {
+                "" | "n" => exit_on_error(self.next(None)),
+                "p" => exit_on_error(self.previous(None)),
+                "q" => break,
+                n => match n.parse::<usize>() {
+                    Ok(n) if (1..=self.get_history().len()).contains(&n) => {
+                        exit_on_error(self.go(n));
+                    }
+                    _ => (),
+                },
+            }
+        }
+
+        print!("\x1b[2J\x1b[H");
+        let _ = io::stdout().flush();
+    }
+
+    fn render_screen(&self, bold: bool) {
+        let history = self.get_history();
+        let n = self.get_index_of_current_commit();
+        let Commit { hash, title } = &history[n];
+        let body = git::commits_details(slice::from_ref(hash))
+            .pop()
+            .map(|x| x.body)
+            .unwrap_or_default();
+
+        let slide = screen::Slide {
+            number: n + 1,
+            total: history.len(),
+            title: self.display_title(title),
+            body: &body,
+        };
+        let (columns, lines) = screen::size();
+        print!("{}", screen::render(&slide, columns, lines, bold));
+        let _ = io::stdout().flush();
+    }
+
+    /// Everything needed to render slide `hash` as HTML.
+    fn html_slide(&self, hash: &str) -> html::Slide {
+        let history = self.get_history();
+        let hash = hash.to_string();
+        let title = self
+            .git
+            .commits(slice::from_ref(&hash))
+            .pop()
+            .map(|commit| self.display_title(&commit.title).to_string())
+            .unwrap_or_default();
+        let body = git::commits_details(slice::from_ref(&hash))
+            .pop()
+            .map(|details| details.body)
+            .unwrap_or_default();
+        html::Slide {
+            number: history
+                .iter()
+                .position(|commit| commit.hash == hash)
+                .map(|n| n + 1),
+            total: history.len(),
+            diff: git::diff(&hash),
+            short_hash: self.short_hash(&hash).to_string(),
+            title,
+            body,
+            notes: None,
+            annotations: None,
+            links: None,
+        }
+    }
+
+    /// Export the presentation as a static site in `dir`: an index, and
+    /// a page per slide, with notes and diff.
+    pub fn export_html(&self, dir: &Path) {
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+        let hashes: Vec<String> = history.iter().map(|commit| commit.hash.clone()).collect();
+        let details = git::commits_details(&hashes);
+
+        let padding = history.len().to_string().len();
+        let page_name = |n: usize| format!("slide-{n:0padding$}.html");
+
+        if fs::create_dir_all(dir).is_err() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot create '{}'.",
+                    dir.display()
+                )
+            );
+            std::process::exit(1);
+        }
+        let write = |name: &str, content: String| {
+            if fs::write(dir.join(name), content).is_err() {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Cannot write '{}'.",
+                        dir.join(name).display()
+                    )
+                );
+                std::process::exit(1);
+            }
+        };
+
+        let mut index = Vec::with_capacity(history.len());
+        for (i, Commit { hash, title }) in history.iter().enumerate() {
+            let n = i + 1;
+            let title = self.display_title(title).to_string();
+            let slide = html::Slide {
+                number: Some(n),
+                total: history.len(),
+                short_hash: self.short_hash(hash).to_string(),
+                title: title.clone(),
+                body: details
+                    .get(i)
+                    .map(|details| details.body.clone())
+                    .unwrap_or_default(),
+                diff: git::diff(hash),
+                notes: self.git.note(NOTES_REF, hash),
+                annotations: self.git.note(ANNOTATIONS_REF, hash),
+                links: Some(html::Links {
+                    previous: (n > 1).then(|| page_name(n - 1)),
+                    next: (n < history.len()).then(|| page_name(n + 1)),
+                    index: String::from("index.html"),
+                }),
+            };
+            write(&page_name(n), html::page(&slide, ""));
+            index.push((page_name(n), title));
+        }
+
+        let deck = index.first().map_or("", |(_, title)| title.as_str());
+        write("index.html", html::index(deck, &index));
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Exported {} slides to '{}'.",
+            history.len(),
+            dir.display()
+        ));
+    }
+
+    /// Check out the slide published on `remote`, whenever it changes,
+    /// until interrupted.
+    ///
+    /// This doesn't need a presentation in progress, it is meant for
+    /// the audience.
+    pub fn follow(&self, remote: &str, interval: Duration) {
+        self.print_info(&tr!(
+            self.options.language,
+            "Following slides from '{}'. Press Ctrl-C to stop.",
+            remote
+        ));
+
+        let mut current = None;
+        loop {
+            let published = git::remote_ref_hash(remote, PUBLISHED_REF);
+            if published.is_some() && published != current {
+                let hash = published.as_deref().expect("checked is some");
+                let _lock = self.lock();
+                if git::fetch_ref(remote, PUBLISHED_REF) && self.git.checkout(hash).is_ok() {
+                    self.post_checkout();
+                    if let Some(Commit { title, .. }) = self.git.commits(&[hash.to_string()]).pop()
+                    {
+                        println!("{} {}", self.short_hash(hash), self.display_title(&title));
+                    }
+                    current = published;
+                } else {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "warning: Could not check out {}.",
+                            hash
+                        )
+                    );
+                }
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    /// Print a JSON line for every slide change, as it happens, until
+    /// interrupted. The current slide comes first, if any.
+    ///
+    /// The store is watched for changes, so consumers don't have to
+    /// poll `status` themselves.
+    pub fn events(&self) {
+        const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+        let store_file = self.store_file();
+        // Start of the run, and number of transitions already emitted.
+        let mut seen: Option<(Option<u64>, usize)> = None;
+        let mut first = true;
+        loop {
+            let store = fs::read_to_string(&store_file)
+                .ok()
+                .and_then(|content| Store::parse(&content).ok());
+
+            match store {
+                Some((store, _)) => {
+                    let from = match seen {
+                        Some((started, emitted)) if started == store.started => emitted,
+                        // Joining mid-presentation, only the current slide.
+                        _ if first => store.transitions.len().saturating_sub(1),
+                        // New presentation, or restarted.
+                        _ => 0,
+                    };
+                    if let Some(new) = store.transitions.get(from..).filter(|x| !x.is_empty()) {
+                        let total = self.history_from_store(&store).len();
+                        for Transition {
+                            at,
+                            slide,
+                            hash,
+                            title,
+                        } in new
+                        {
+                            // TOML basic strings are valid JSON strings.
+                            println!(
+                                r#"{{"event": "slide", "slide": {slide}, "total": {total}, "hash": {}, "title": {}, "at": "{}"}}"#,
+                                toml::quote(hash),
+                                toml::quote(title),
+                                time::format_rfc3339(*at),
+                            );
+                        }
+                    }
+                    seen = Some((store.started, store.transitions.len()));
+                }
+                None if seen.is_some() && !store_file.exists() => {
+                    println!(
+                        r#"{{"event": "stop", "at": "{}"}}"#,
+                        time::format_rfc3339(time::now())
+                    );
+                    seen = None;
+                }
+                // Not started yet, or unreadable for now.
+                None => (),
+            }
+
+            first = false;
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Print every slide change of the presentation in progress (or of
+    /// the last one), with when it happened and how long it lasted.
+    pub fn timeline(&self, format: TimelineFormat) {
+        let last;
+        let store = if self.is_presentation_started() {
+            self.get_store()
+        } else {
+            let Some(store) = self.recorded_runs().pop() else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: No presentation to show the timeline of."
+                    )
+                );
+                std::process::exit(1);
+            };
+            last = store;
+            &last
+        };
+
+        let timeline = store.timeline();
+
+        let mut out = String::new();
+        match format {
+            TimelineFormat::Csv => {
+                out.push_str("slide,hash,title,entered_at,offset,duration\n");
+                for &TimelineEntry {
+                    transition,
+                    offset,
+                    duration,
+                } in &timeline
+                {
+                    let _ = writeln!(
+                        out,
+                        "{},{},{},{},{offset},{duration}",
+                        transition.slide,
+                        transition.hash,
+                        csv_field(&transition.title),
+                        time::format_rfc3339(transition.at),
+                    );
+                }
+            }
+            TimelineFormat::Json => {
+                // TOML basic strings are valid JSON strings.
+                let entries: Vec<String> = timeline
+                    .iter()
+                    .map(|&TimelineEntry {
+                         transition,
+                         offset,
+                         duration,
+                     }| {
+                        format!(
+                            r#"  {{"slide": {}, "hash": {}, "title": {}, "entered_at": "{}", "offset": {offset}, "duration": {duration}}}"#,
+                            transition.slide,
+                            toml::quote(&transition.hash),
+                            toml::quote(&transition.title),
+                            time::format_rfc3339(transition.at),
+                        )
+                    })
+                    .collect();
+                if entries.is_empty() {
+                    out.push_str("[]\n");
+                } else {
+                    let _ = writeln!(out, "[\n{}\n]", entries.join(",\n"));
+                }
+            }
+        }
+        print!("{out}");
+    }
+
+    /// Print time spent per slide across recorded runs (including the
+    /// one in progress): minimum, average and maximum, against the
+    /// `Slide-Duration` budget.
+    ///
+    /// Slides are matched by title, so that rewritten slides still add
+    /// up. Slides over budget in every run are highlighted.
+    pub fn stats(&self) {
+        let recorded = self.recorded_runs();
+        let mut runs: Vec<&Store> = recorded.iter().collect();
+        if self.is_presentation_started() {
+            runs.push(self.get_store());
+        }
+        runs.retain(|run| !run.transitions.is_empty());
+        if runs.is_empty() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: No recorded presentation yet."
+                )
+            );
+            std::process::exit(1);
+        }
+
+        let (slides, totals) = Self::time_per_slide(&runs);
+
+        let hashes: Vec<String> = slides.iter().map(|(slide, _)| slide.hash.clone()).collect();
+        // Empty if some slides no longer exist, no budgets then.
+        let budgets: Vec<Option<u64>> = git::commits_details(&hashes)
+            .into_iter()
+            .map(|details| {
+                details
+                    .trailers
+                    .iter()
+                    .rev()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("Slide-Duration"))
+                    .and_then(|(_, value)| time::parse_duration(value))
+            })
+            .collect();
+
+        let Colors { reset, warning, .. } = &self.options.colors;
+        let duration =
+            |seconds: Option<u64>| seconds.map_or_else(|| String::from("-"), time::format_duration);
+        let stats = |durations: &[u64]| {
+            let min = durations.iter().min().copied();
+            let max = durations.iter().max().copied();
+            let avg = (!durations.is_empty())
+                .then(|| durations.iter().sum::<u64>() / durations.len() as u64);
+            (min, avg, max)
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}",
+            tr!(self.options.language, "Runs: {}", runs.len())
+        );
+        let _ = writeln!(
+            out,
+            "\n{:>3}  {:>10}  {:>10}  {:>10}  {:>10}  Title",
+            "#", "Min", "Avg", "Max", "Budget"
+        );
+        for (i, (slide, durations)) in slides.iter().enumerate() {
+            let (min, avg, max) = stats(durations);
+            let budget = budgets.get(i).copied().flatten();
+            let (color, reset) = match (min, budget) {
+                (Some(min), Some(budget)) if min > budget => (warning.as_str(), reset.as_str()),
+                _ => ("", ""),
+            };
+            let _ = writeln!(
+                out,
+                "{color}{:>3}  {:>10}  {:>10}  {:>10}  {:>10}  {}{reset}",
+                slide.slide,
+                duration(min),
+                duration(avg),
+                duration(max),
+                duration(budget),
+                self.display_title(&slide.title),
+            );
+        }
+        let (min, avg, max) = stats(&totals);
+        let _ = writeln!(
+            out,
+            "{:>3}  {:>10}  {:>10}  {:>10}  {:>10}  {}",
+            "",
+            duration(min),
+            duration(avg),
+            duration(max),
+            "",
+            tr!(self.options.language, "Total"),
+        );
+        print!("{out}");
+    }
+
+    /// Time spent on each slide (by title) in every run it appears in,
+    /// ordered by slide number, and total time of every run.
+    fn time_per_slide<'a>(runs: &[&'a Store]) -> (Vec<(&'a Transition, Vec<u64>)>, Vec<u64>) {
+        let mut slides: Vec<(&Transition, Vec<u64>)> = Vec::new();
+        let mut totals = Vec::with_capacity(runs.len());
+        for run in runs {
+            let mut spent: Vec<(&Transition, u64)> = Vec::new();
+            for entry in run.timeline() {
+                match spent
+                    .iter_mut()
+                    .find(|(slide, _)| slide.title == entry.transition.title)
+                {
+                    Some((_, duration)) => *duration += entry.duration,
+                    None => spent.push((entry.transition, entry.duration)),
+                }
+            }
+            totals.push(spent.iter().map(|(_, duration)| duration).sum::<u64>());
+            for (transition, duration) in spent {
+                match slides
+                    .iter_mut()
+                    .find(|(slide, _)| slide.title == transition.title)
+                {
+                    Some((slide, durations)) => {
+                        // Latest version, for the budget.
+                        *slide = transition;
+                        durations.push(duration);
+                    }
+                    None => slides.push((transition, vec![duration])),
+                }
+            }
+        }
+        slides.sort_by_key(|(slide, _)| slide.slide);
+        (slides, totals)
+    }
+
+    /// Print the web URL of the current slide, on the forge hosting
+    /// `remote`.
+    pub fn permalink(&self, remote: &str) {
+        self.ensure_presentation_is_started();
+
+        let n = self.get_index_of_current_commit();
+        let hash = &self.get_history()[n].hash;
+
+        let template = self.options.permalink.as_deref();
+        let web_url = if template.is_some_and(|template| !template.contains("%u")) {
+            // Not needed, don't require a remote.
+            String::new()
+        } else {
+            let Some(remote_url) = git::remote_url(remote) else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: No such remote: '{}'.",
+                        remote
+                    )
+                );
+                std::process::exit(1);
+            };
+            let Some(web_url) = forge::web_url(&remote_url) else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Cannot derive a web URL from '{}'.",
+                        remote_url
+                    )
+                );
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "hint: Set 'slides.permalink' to a URL template, e.g.,"
+                    )
+                );
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "hint: 'https://example.com/repo/commit/%H'."
+                    )
+                );
+                std::process::exit(1);
+            };
+            web_url
+        };
+
+        let template = template.unwrap_or_else(|| forge::commit_url_template(&web_url));
+        println!("{}", forge::expand(template, &web_url, hash));
+    }
+
+    /// Print `[slides <n>/<total>]` for shell prompts, or nothing if no
+    /// presentation is in progress.
+    ///
+    /// This runs on every prompt render, so it only reads files (store
+    /// and HEAD), without running Git, and never fails loudly.
+    pub fn prompt(&self) {
+        let Ok(content) = fs::read_to_string(self.store_file()) else {
+            return;
+        };
+        let Ok((store, _)) = Store::parse(&content) else {
+            return;
+        };
+        // A linked worktree has its own HEAD, in its own Git directory.
+        let git_dir = match &store.worktree {
+            Some(worktree) => git::worktree_git_dir(Path::new(worktree)),
+            None => Some(self.git_dir.clone()),
+        };
+        let head = git_dir
+            .and_then(|git_dir| fs::read_to_string(git_dir.join("HEAD")).ok())
+            .unwrap_or_default();
+        let head = head.trim();
+
+        let position = store
+            .slides
+            .as_ref()
+            .or(store.cached_slides())
+            .and_then(|slides| {
+                let slides = Self::without_hidden(&store, slides.clone());
+                let n = slides.iter().position(|slide| slide.hash == head)?;
+                Some((n + 1, slides.len()))
+            });
+
+        match position {
+            Some((n, total)) => println!("[slides {n}/{total}]"),
+            None => println!("[slides]"),
+        }
+    }
+
+    /// List slides as `<n>\t<title>`, for shell completion.
+    ///
+    /// Silent if there is no presentation. Slides are read from the
+    /// store when cached, so completing is usually free of Git calls.
+    pub fn complete(&self) {
+        let Ok(content) = fs::read_to_string(self.store_file()) else {
+            return;
+        };
+        let Ok((store, _)) = Store::parse(&content) else {
+            return;
+        };
+        let history = Self::without_hidden(&store, self.history_from_store(&store));
+        let mut out = String::new();
+        for (i, Commit { title, .. }) in history.iter().enumerate() {
+            let _ = writeln!(out, "{}\t{title}", i + 1);
+        }
+        print!("{out}");
+    }
+
+    /// Print the speaker notes of the current slide, or edit them.
+    ///
+    /// Notes live in `refs/notes/slides`, so they can be changed
+    /// without rewriting the slides.
+    pub fn notes(&self, edit: bool) {
+        self.ensure_presentation_is_started();
+
+        let n = self.get_index_of_current_commit();
+        let hash = &self.get_history()[n].hash;
+
+        if edit {
+            if !git::edit_note(NOTES_REF, hash) {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not edit notes of {}.",
+                        hash
+                    )
+                );
+                std::process::exit(1);
+            }
+            return;
+        }
+
+        let note = self.git.note(NOTES_REF, hash);
+        let annotations = self.git.note(ANNOTATIONS_REF, hash);
+        if let Some(note) = &note {
+            println!("{note}");
+        }
+        if let Some(annotations) = &annotations {
+            if note.is_some() {
+                println!();
+            }
+            println!("{}", tr!(self.options.language, "Annotations:"));
+            println!("{annotations}");
+        }
+        if note.is_none() && annotations.is_none() {
+            self.print_info(&tr!(
+                self.options.language,
+                "No notes for the current slide."
+            ));
+        }
+    }
+
+    /// Append a timestamped note to the current slide, e.g., to keep
+    /// track of questions asked during the talk.
+    ///
+    /// Annotations are kept apart from speaker notes, in
+    /// `refs/notes/slides-annotations`.
+    pub fn annotate(&self, message: &str) {
+        self.ensure_presentation_is_started();
+
+        let n = self.get_index_of_current_commit();
+        let hash = &self.get_history()[n].hash;
+
+        let annotation = format!("[{}] {message}", time::format_timestamp(time::now()));
+        if !git::append_note(ANNOTATIONS_REF, hash, &annotation) {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Could not annotate {}.", hash)
+            );
+            std::process::exit(1);
+        }
+
+        self.print_info(&tr!(self.options.language, "Annotated slide {}.", n + 1));
+    }
+
+    /// Leave slides `numbers` (as shown by `status` and `list`, see
+    /// [`Self::slide_index()`]) out of the presentation, or list hidden
+    /// slides if there are no `numbers`.
+    pub fn hide(&mut self, numbers: &[String]) {
+        self.ensure_presentation_is_started();
+
+        if numbers.is_empty() {
+            for (i, Commit { hash, title }) in self.hidden_slides().iter().enumerate() {
+                println!(
+                    "{} {} {}",
+                    i + 1,
+                    self.short_hash(hash),
+                    self.display_title(title)
+                );
+            }
+            return;
+        }
+
+        let history = self.get_history();
+        let current = self.get_index_of_current_commit();
+        let mut slides = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            let Some(i) = self.slide_index(number) else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Bad slide index. Slide {} does not exist.",
+                        number
+                    )
+                );
+                std::process::exit(1);
+            };
+            if i == current {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Cannot hide the current slide."
+                    )
+                );
+                std::process::exit(1);
+            }
+            slides.push(history[i].clone());
+        }
+
+        let mut store = self.take_store();
+        for slide in slides {
+            if !store.hidden.iter().any(|x| x.hash == slide.hash) {
+                store.hidden.push(slide);
+            }
+        }
+        self.update_hidden(store);
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Hidden slides: {}.",
+            self.get_store().hidden.len()
+        ));
+        self.echo_status();
+    }
+
+    /// Put hidden slides `numbers` (1-indexed, as listed by `hide`) back
+    /// in the presentation, or all of them if there are no `numbers`.
+    pub fn unhide(&mut self, numbers: &[usize]) {
+        self.ensure_presentation_is_started();
+
+        let hidden = self.hidden_slides();
+        let mut slides = Vec::with_capacity(numbers.len());
+        for &n in numbers {
+            let Some(slide) = n.checked_sub(1).and_then(|n| hidden.get(n)) else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: There is no hidden slide {}.",
+                        n
+                    )
+                );
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "hint: Use 'git slides hide' to list hidden slides."
+                    )
+                );
+                std::process::exit(1);
+            };
+            slides.push(slide.hash.clone());
+        }
+
+        let mut store = self.take_store();
+        if numbers.is_empty() {
+            store.hidden.clear();
+        } else {
+            store.hidden.retain(|slide| !slides.contains(&slide.hash));
+        }
+        self.update_hidden(store);
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Hidden slides: {}.",
+            self.get_store().hidden.len()
+        ));
+        self.echo_status();
+    }
+
+    /// Index (0-indexed) of slide `number`, numbered as displayed (e.g.,
+    /// `2.3` with sections), or 1-indexed across the whole presentation.
+    fn slide_index(&self, number: &str) -> Option<usize> {
+        let (numbers, _) = self.slide_numbers();
+        numbers
+            .iter()
+            .position(|x| x.trim_start() == number)
+            .or_else(|| {
+                let n: usize = number.parse().ok()?;
+                n.checked_sub(1).filter(|&i| i < numbers.len())
+            })
+    }
+
+    /// Hidden slides, in presentation order.
+    fn hidden_slides(&self) -> Vec<Commit> {
+        let store = self.get_store();
+        self.history_from_store(store)
+            .into_iter()
+            .filter(|slide| store.hidden.iter().any(|x| x.hash == slide.hash))
+            .collect()
+    }
+
+    fn update_hidden(&mut self, store: Store) {
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+        self.history = OnceCell::new();
+        self.sections = OnceCell::new();
+    }
+
+    /// Print the number of slides, or of slides after the current one.
+    pub fn count(&self, remaining: bool) {
+        self.ensure_presentation_is_started();
+
+        let total = self.get_history().len();
+        if remaining {
+            let n = self.get_index_of_current_commit();
+            println!("{}", total - n - 1);
+        } else {
+            println!("{total}");
+        }
+    }
+
+    /// List the slides whose title contains `pattern`, ignoring case.
+    pub fn find(&self, pattern: &str) {
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+        let n = self.get_index_of_current_commit_checked();
+        let needle = pattern.to_lowercase();
+
+        let (numbers, total) = self.slide_numbers();
+        let Colors {
+            reset,
+            hash: hash_color,
+            ..
+        } = &self.options.colors;
+
+        let mut out = String::new();
+        for (i, Commit { hash, title }) in history.iter().enumerate() {
+            if !title.to_lowercase().contains(&needle) {
+                continue;
+            }
+            let _ = writeln!(
+                out,
+                "{}{}/{total} {hash_color}{}{reset} {}",
+                if Some(i) == n { "* " } else { "  " },
+                numbers[i],
+                self.short_hash(hash),
+                self.display_title(title),
+            );
+        }
+
+        if out.is_empty() {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "No slide matches '{}'.", pattern)
+            );
+            std::process::exit(1);
+        }
+        print!("{out}");
+    }
+
+    /// List the slides whose files contain `pattern` (a regex), with the
+    /// matching files, without checking any of them out.
+    pub fn grep(&self, pattern: &str, ignore_case: bool) {
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+        let n = self.get_index_of_current_commit_checked();
+        let hashes: Vec<&str> = history.iter().map(|commit| commit.hash.as_str()).collect();
+
+        let matches = match git::grep(pattern, &hashes, ignore_case) {
+            Ok(matches) => matches,
+            Err(stderr) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not search slides for '{}'.",
+                        pattern
+                    )
+                );
+                relay_git_error(&stderr);
+                std::process::exit(1);
+            }
+        };
+
+        let (numbers, total) = self.slide_numbers();
+        let Colors {
+            reset,
+            hash: hash_color,
+            ..
+        } = &self.options.colors;
+        // Aligned with the hash.
+        let indent = " ".repeat(2 + numbers.first().map_or(0, String::len) + 1 + total.len() + 1);
+
+        let mut out = String::new();
+        for (i, Commit { hash, title }) in history.iter().enumerate() {
+            let mut files = matches
+                .iter()
+                .filter(|(slide, _)| slide == hash)
+                .map(|(_, path)| path)
+                .peekable();
+            if files.peek().is_none() {
+                continue;
+            }
+            let _ = writeln!(
+                out,
+                "{}{}/{total} {hash_color}{}{reset} {}",
+                if Some(i) == n { "* " } else { "  " },
+                numbers[i],
+                self.short_hash(hash),
+                self.display_title(title),
+            );
+            for path in files {
+                let _ = writeln!(out, "{indent}{path}");
+            }
+        }
+
+        if out.is_empty() {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "No slide contains '{}'.", pattern)
+            );
+            std::process::exit(1);
+        }
+        print!("{out}");
+    }
+
+    /// Time spent on the current slide against its `Slide-Duration`,
+    /// like `  (1m 10s / 2m 00s)`, or nothing if it has no budget.
+    fn pacing(&self, hash: &str) -> String {
+        let Some(budget) = self
+            .git
+            .trailer_values(hash, "Slide-Duration")
+            .last()
+            .and_then(|duration| time::parse_duration(duration))
+        else {
+            return String::new();
+        };
+        let Some(slide_started) = self
+            .get_store()
+            .transitions
+            .last()
+            .filter(|transition| transition.hash == hash)
+            .map(|transition| transition.at)
+        else {
+            return String::new();
+        };
+
+        let spent = time::now().saturating_sub(slide_started);
+        let pacing = format!(
+            "({} / {})",
+            time::format_duration(spent),
+            time::format_duration(budget),
+        );
+
+        if spent > budget {
+            let Colors { reset, warning, .. } = &self.options.colors;
+            let over = tr!(self.options.language, "over time");
+            format!("  {warning}{pacing} {over}{reset}")
+        } else {
+            format!("  {pacing}")
+        }
+    }
+
+    /// The last checkpoint crossed since the previous navigation, if
+    /// any. Only the last one, several at once would be noise.
+    fn crossed_checkpoint(&self) -> Option<u64> {
+        let store = self.get_store();
+        let started = store.started?;
+        // After a restart, transitions predate the start.
+        let since = store
+            .transitions
+            .last()
+            .map_or(started, |transition| transition.at.max(started));
+
+        let before = since - started;
+        let elapsed = time::now().saturating_sub(started);
+        self.options
+            .checkpoints
+            .iter()
+            .filter(|&&checkpoint| before < checkpoint && checkpoint <= elapsed)
+            .max()
+            .copied()
+    }
+
+    fn alert_checkpoint(&self, checkpoint: u64) {
+        let message = tr!(
+            self.options.language,
+            "Checkpoint: {} elapsed.",
+            time::format_duration(checkpoint)
+        );
+        let Colors { reset, warning, .. } = &self.options.colors;
+        println!("\n{warning}{message}{reset}");
+
+        match self.options.checkpoint_alert {
+            CheckpointAlert::Notify if console::notify("git-slides", &message) => (),
+            _ => console::ring_bell(),
+        }
+    }
+
+    /// Render a progress bar, like `[#####-----] 5/10  50%`.
+    fn progress_bar(&self, slide: usize, total: usize) -> String {
+        const WIDTH: usize = 20;
+
+        let Colors { reset, dim, .. } = &self.options.colors;
+
+        let done = slide * WIDTH / total;
+        let percent = slide * 100 / total;
+
+        format!(
+            "[{}{dim}{}{reset}] {slide}/{total}  {percent}%",
+            "#".repeat(done),
+            "-".repeat(WIDTH - done),
+        )
+    }
+
+    pub fn list(&self, options: &ListOptions) {
+        const BODY_EXCERPT_LINES: usize = 3;
+
+        let verbose = options.verbose;
+        self.ensure_presentation_is_started();
+
+        let history = self.get_history();
+        let n = self.get_index_of_current_commit();
+
+        let (numbers, total) = self.slide_numbers();
+        let Colors {
+            reset,
+            dim,
+            hash: hash_color,
+            ..
+        } = &self.options.colors;
+
+        let details = if verbose || self.options.columns.any() {
+            Self::commits_details(history)
+        } else {
+            Vec::new()
+        };
+        let mut columns = self.render_columns(&details);
+        if options.stat {
+            columns = Self::render_diff_stats(history)
+                .into_iter()
+                .zip(columns.into_iter().chain(std::iter::repeat(String::new())))
+                .map(|(stat, columns)| stat + &columns)
+                .collect();
+        }
+        // Aligned with the hash.
+        let indent = " ".repeat(2 + numbers.first().map_or(0, String::len) + 1 + total.len() + 1);
+
+        // Pre-allocate a "best-guess" number of characters. Each line
+        // includes padding, slide number, commit hash, title and color.
+        let mut out = String::with_capacity(history.len() * 72);
+
+        for i in 0..history.len() {
+            let Commit { hash, title } = history.get(i).expect("bounds have been checked");
+            let number = numbers.get(i).expect("bounds have been checked");
+            let title = self.display_title(title);
+            let columns = columns.get(i).map_or("", String::as_str);
+
+            if let Some(section) = self.section_starting_at(i) {
+                if i > 0 {
+                    out.push('\n');
+                }
+                let _ = writeln!(out, "{dim}{section}{reset}");
+            }
+
+            if i == n {
+                let _ = write!(out, "* ");
+            } else {
+                let _ = write!(out, "  ");
+            }
+
+            let _ = writeln!(
+                out,
+                "{}/{total} {hash_color}{}{}{reset} {columns}{title}",
+                number,
+                self.short_hash(hash),
+                self.decoration(hash),
+            );
+
+            if !verbose {
+                continue;
+            }
+            let Some(git::CommitDetails { body, trailers, .. }) = details.get(i) else {
+                continue;
+            };
+
+            let mut body_lines = body.lines();
+            for line in body_lines.by_ref().take(BODY_EXCERPT_LINES) {
+                let _ = writeln!(out, "{indent}{line}");
+            }
+            if body_lines.next().is_some() {
+                let _ = writeln!(out, "{indent}[...]");
+            }
+
+            // Only show trailers meant for git-slides (e.g., `Slide-Duration`).
+            for (key, value) in trailers {
+                if key.to_lowercase().starts_with("slide-") {
+                    let _ = writeln!(out, "{indent}{dim}{key}: {value}{reset}");
+                }
+            }
+        }
+
+        if options.pager {
+            pager::page_or_print(&out);
+        } else {
+            print!("{out}");
+        }
+    }
+
+    /// Number of each slide, and the total, right-aligned so they line
+    /// up. With sections, numbers are `<section>.<slide>`, and the total
+    /// is the number of the last slide (e.g., `2.3` of `5.4`).
+    fn slide_numbers(&self) -> (Vec<String>, String) {
+        let len = self.get_history().len();
+        let sections = self.get_sections();
+
+        let numbers: Vec<String> = if sections.is_empty() {
+            (1..=len).map(|n| n.to_string()).collect()
+        } else {
+            (1..=len)
+                .map(|n| {
+                    // Slides before the first section are in section 0.
+                    let section = sections.iter().filter(|x| x.first <= n).count();
+                    let first = sections
+                        .get(section.wrapping_sub(1))
+                        .map_or(1, |section| section.first);
+                    format!("{section}.{}", n - first + 1)
+                })
+                .collect()
+        };
+
+        let total = numbers.last().cloned().unwrap_or_default();
+        let width = numbers.iter().map(String::len).max().unwrap_or(0);
+        let numbers = numbers
+            .into_iter()
+            .map(|n| format!("{n:>width$}"))
+            .collect();
+        (numbers, total)
+    }
+
+    /// Title of the section slide `i` (0-indexed) is the first of, if
+    /// any.
+    fn section_starting_at(&self, i: usize) -> Option<&str> {
+        self.get_sections()
+            .iter()
+            .find(|section| section.first == i + 1)
+            .map(|section| section.title.as_str())
+    }
+
+    /// Show extra columns in `status` and `list`, on top of config.
+    pub fn show_columns(&mut self, columns: Columns) {
+        self.options.columns.author |= columns.author;
+        self.options.columns.date |= columns.date;
+    }
+
+    /// Render the optional columns of each commit, aligned, and followed
+    /// by a separator. Empty if no column is enabled.
+    fn render_columns(&self, details: &[git::CommitDetails]) -> Vec<String> {
+        let columns = self.options.columns;
+        if !columns.any() {
+            return Vec::new();
+        }
+
+        let width = |field: fn(&git::CommitDetails) -> &str| {
+            details
+                .iter()
+                .map(|x| field(x).chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let author_width = width(|x| &x.author);
+        let date_width = width(|x| &x.date);
+
+        details
+            .iter()
+            .map(|x| {
+                let mut rendered = String::new();
+                if columns.author {
+                    let _ = write!(rendered, "{:<author_width$}  ", x.author);
+                }
+                if columns.date {
+                    let _ = write!(rendered, "{:<date_width$}  ", x.date);
+                }
+                rendered
+            })
+            .collect()
+    }
+
+    /// Render the size of each slide's diff from the previous slide,
+    /// like `3 files  +120   -4  `, aligned.
+    fn render_diff_stats(history: &[Commit]) -> Vec<String> {
+        let pairs: Vec<(Option<&str>, &str)> = history
+            .iter()
+            .enumerate()
+            .map(|(i, commit)| {
+                let previous = i.checked_sub(1).map(|i| history[i].hash.as_str());
+                (previous, commit.hash.as_str())
+            })
+            .collect();
+        let stats = git::diff_stats(&pairs);
+
+        let width = |field: fn(&git::DiffStat) -> usize| {
+            stats
+                .iter()
+                .map(|x| field(x).to_string().len())
+                .max()
+                .unwrap_or(0)
+        };
+        let files_width = width(|x| x.files);
+        let insertions_width = width(|x| x.insertions) + 1;
+        let deletions_width = width(|x| x.deletions) + 1;
+
+        stats
+            .iter()
+            .map(|x| {
+                let files = if x.files == 1 { "file " } else { "files" };
+                format!(
+                    "{:>files_width$} {files}  {:>insertions_width$} {:>deletions_width$}  ",
+                    x.files,
+                    format!("+{}", x.insertions),
+                    format!("-{}", x.deletions),
+                )
+            })
+            .collect()
+    }
+
+    fn commits_details(commits: &[Commit]) -> Vec<git::CommitDetails> {
+        let hashes: Vec<String> = commits.iter().map(|x| x.hash.clone()).collect();
+        git::commits_details(&hashes)
+    }
+
+    /// Strip the first matching `slides.titlePrefix` from `title`.
+    fn display_title<'a>(&self, title: &'a str) -> &'a str {
+        self.options
+            .title_prefixes
+            .iter()
+            .find_map(|pattern| glob::strip_prefix(pattern, title))
+            .map(str::trim_start)
+            .filter(|stripped| !stripped.is_empty())
+            .unwrap_or(title)
+    }
+
+    fn ensure_presentation_is_started(&self) {
+        exit_on_error(self.check_presentation_is_started());
+    }
+
+    fn check_presentation_is_started(&self) -> Result<(), Exit> {
+        if !self.is_presentation_started() {
+            let command = format!("{} start", env!("CARGO_BIN_NAME").replacen('-', " ", 1));
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "You need to start by '{}'.", command)
+            );
+            return Err(Exit(1));
+        }
+        Ok(())
+    }
+
+    fn ensure_presentation_is_not_started(&self) {
+        if self.is_presentation_started() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: A presentation is already in progress."
+                )
+            );
+            eprintln!("{}", tr!(self.options.language, "hint: Use 'git slides start --resume' to continue it, or 'git slides stop' to end it."));
+            std::process::exit(1);
+        }
+    }
+
+    /// Refuse to check anything out in the middle of a rebase, merge,
+    /// etc., which would leave it in a confusing state.
+    fn ensure_no_operation_in_progress(&self) -> Result<(), Exit> {
+        let Some(operation) = self.git.operation_in_progress() else {
+            return Ok(());
+        };
+        let language = self.options.language;
+        let command = operation.command();
+        eprintln!("{}", tr!(language, "error: A {} is in progress.", command));
+        if operation == git::Operation::Bisect {
+            eprintln!(
+                "{}",
+                tr!(language, "hint: Use 'git bisect reset' to end it first.")
+            );
+        } else {
+            eprintln!(
+                "{}",
+                tr!(
+                    language,
+                    "hint: Use 'git {} --continue' to finish it, or 'git {} --abort' to cancel it, first.",
+                    command,
+                    command
+                )
+            );
+        }
+        Err(Exit(1))
+    }
+
+    /// Print an informational message, unless `--quiet`.
+    fn print_info(&self, message: &str) {
+        if !self.options.quiet {
+            println!("{message}");
+        }
+    }
+
+    /// Show the status after a change, unless `--quiet`.
+    fn echo_status(&self) {
+        if !self.options.quiet {
+            exit_on_error(self.status());
+        }
+    }
+
+    /// Remap the presentation onto rewritten history (e.g., after
+    /// a rebase), matching slides by title.
+    ///
+    /// The rewritten history is `ref_`, or the initial branch if it has
+    /// moved, or HEAD.
+    pub fn repair(&mut self, ref_: Option<&str>) {
+        self.ensure_presentation_is_started();
+
+        let store = self.get_store();
+        let target = match ref_ {
+            Some(ref_) => ref_.to_string(),
+            None => store
+                .branch
+                .clone()
+                .filter(|branch| self.git.ref_to_commit_hash(branch).as_ref() != Some(&store.head))
+                .unwrap_or_else(|| String::from("HEAD")),
+        };
+        let Some(new_head) = self.git.ref_to_commit_hash(&target) else {
+            eprintln!(
+                "{}",
+                tr!(self.options.language, "error: Bad ref input: '{}'.", target)
+            );
+            std::process::exit(1);
+        };
+
+        // Commits of the rewritten history, by title, oldest first.
+        let candidates = self.git.history_up_to_commit(
+            &new_head,
+            &HistoryOptions {
+                all_parents: true,
+                ..HistoryOptions::default()
+            },
+        );
+        let mut by_title: HashMap<&str, VecDeque<&str>> = HashMap::new();
+        for Commit { hash, title } in &candidates {
+            by_title.entry(title).or_default().push_back(hash);
+        }
+        let mut remap = |title: &str| by_title.get_mut(title).and_then(VecDeque::pop_front);
+
+        let old_history = self.get_history().clone();
+        let mut store = self.take_store();
+        let mut found = 0;
+
+        if let Some(slides) = &mut store.slides {
+            // Slides that are not found are kept, their commits still exist.
+            for slide in slides {
+                if let Some(hash) = remap(&slide.title) {
+                    slide.hash = hash.to_string();
+                    found += 1;
+                }
+            }
+        } else {
+            if let Some(from) = &store.from {
+                let title = self
+                    .git
+                    .commits(slice::from_ref(from))
+                    .pop()
+                    .map(|commit| commit.title)
+                    .unwrap_or_default();
+                store.from = remap(&title).map(String::from);
+            }
+            let new_history = self.git.history_up_to_commit(
+                &new_head,
+                &HistoryOptions {
+                    from: store.from.as_deref(),
+                    all_parents: store.all_parents,
+                    pattern: store.pattern.as_deref(),
+                    exclude: &[],
+                },
+            );
+            found = old_history
+                .iter()
+                .filter(|old| new_history.iter().any(|new| new.title == old.title))
+                .count();
+            store.head.clone_from(&new_head);
+        }
+
+        if found == 0 {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: No slide found in '{}'. The presentation is unchanged.",
+                    target
+                )
+            );
+            std::process::exit(1);
+        }
+
+        self.set_store(store);
+
+        self.print_info(&tr!(
+            self.options.language,
+            "Remapped presentation onto {}, found {} of {} slides.",
+            self.short_hash(&new_head),
+            found,
+            old_history.len(),
+        ));
+
+        if self.get_index_of_current_commit_checked().is_some() {
+            self.echo_status();
+            return;
+        }
+
+        // Go to the rewritten version of the current commit, if any.
+        let current_title = self
+            .git
+            .current_commit_hash()
+            .and_then(|head| self.git.commits(&[head]).pop())
+            .map(|commit| commit.title);
+        let n = self
+            .get_history()
+            .iter()
+            .position(|commit| Some(&commit.title) == current_title.as_ref())
+            .unwrap_or(0);
+        exit_on_error(self.go(n + 1));
+    }
+
+    /// Check environment and presentation state, and suggest fixes.
+    ///
+    /// Exits with 1 if any problem is found.
+    pub fn doctor(&self) {
+        // `git rev-list --no-commit-header`.
+        const MIN_GIT_VERSION: (u32, u32) = (2, 33);
+
+        let mut problems = 0;
+        let mut problem = |message: &str, fix: &str| {
+            problems += 1;
+            println!("problem: {message}");
+            println!("    fix: {fix}");
+        };
+
+        match git::version() {
+            Some(version) if version >= MIN_GIT_VERSION => {
+                println!("     ok: Git version {}.{}.", version.0, version.1);
+            }
+            Some(version) => problem(
+                &format!("Git version {}.{} is too old.", version.0, version.1),
+                &format!(
+                    "Upgrade Git to {}.{} or later.",
+                    MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+                ),
+            ),
+            None => problem(
+                "Cannot determine Git version.",
+                "Check that 'git --version' works.",
+            ),
+        }
+
+        let stashes = git::stash_count();
+        if stashes > 0 {
+            println!("   note: {stashes} stash entries, some may come from navigating slides.");
+            println!(
+                "         Review them with 'git stash list' (those are labeled 'git-slides')."
+            );
+        }
+
+        if !self.is_presentation_started() {
+            println!("     ok: No presentation in progress.");
+            std::process::exit(i32::from(problems > 0));
+        }
+
+        let store = match fs::read_to_string(self.store_file()).map(|x| Store::parse(&x)) {
+            Ok(Ok((store, _))) => {
+                println!("     ok: Store file is valid.");
+                store
+            }
+            Ok(Err(store::Error::UnsupportedVersion(version))) => {
+                problem(
+                    &format!("Store file uses unsupported format version {version}."),
+                    &format!(
+                        "Upgrade git-slides, or remove '{}' to discard the presentation.",
+                        self.display_path(&self.store_file())
+                    ),
+                );
+                std::process::exit(1);
+            }
+            Ok(Err(store::Error::Malformed)) | Err(_) => {
+                problem(
+                    "Store file is unreadable or corrupt.",
+                    &format!(
+                        "Remove '{}' to discard the presentation.",
+                        self.display_path(&self.store_file())
+                    ),
+                );
+                std::process::exit(1);
+            }
+        };
+
+        if self.git.ref_to_commit_hash(&store.head).is_some() {
+            println!("     ok: Presentation head exists.");
+        } else {
+            problem(
+                &format!("Presentation head {} does not exist.", store.head),
+                "Use 'git slides repair <ref>' to remap the presentation.",
+            );
+        }
+
+        if let Some(branch) = &store.branch {
+            if self.git.ref_to_commit_hash(branch).is_none() {
+                problem(
+                    &format!("Initial branch '{branch}' does not exist anymore."),
+                    &format!("'stop' will fail to go back to it, recreate it with 'git branch {branch} {}'.", store.head),
+                );
+            }
+        }
+
+        if let Some(branch) = git::current_branch() {
+            problem(
+                &format!("HEAD is on branch '{branch}', instead of detached on a slide."),
+                "Use 'git slides go <n>' to go back to a slide.",
+            );
+        } else if self.get_index_of_current_commit_checked().is_some() {
+            println!("     ok: HEAD is on a slide.");
+        } else if self.looks_rewritten() {
+            problem(
+                "HEAD is not a slide, history seems to have been rewritten.",
+                "Use 'git slides repair' to remap the presentation.",
+            );
+        } else {
+            problem(
+                "HEAD is not a slide.",
+                "Use 'git slides go <n>' to go back to a slide.",
+            );
+        }
+
+        std::process::exit(i32::from(problems > 0));
+    }
+
+    /// Check that every slide of the store still exists, and that the
+    /// list of slides is the one recorded when it was written.
+    pub fn verify(&self) {
+        self.ensure_presentation_is_started();
+
+        let mut problems = 0;
+        let mut problem = |message: &str, fix: &str| {
+            problems += 1;
+            println!("problem: {message}");
+            println!("    fix: {fix}");
+        };
+
+        let store = self.get_store();
+        // The slides as recorded, even if the head has moved since.
+        let slides = store
+            .slides
+            .as_ref()
+            .or(store.cache.as_ref().map(|cache| &cache.slides))
+            .unwrap_or_else(|| self.get_history());
+        let total = slides.len();
+
+        match (store.slide_count, &store.checksum) {
+            (Some(count), Some(checksum))
+                if count == total && *checksum == Store::checksum(slides) =>
+            {
+                println!("     ok: Slides match the checksum ({total} slides).");
+            }
+            (Some(count), Some(_)) => problem(
+                &format!(
+                    "Slides do not match the checksum ({count} slides recorded, {total} now)."
+                ),
+                &format!(
+                    "Use 'git slides repair' to remap the presentation, or check '{}'.",
+                    self.display_path(&self.store_file())
+                ),
+            ),
+            _ => println!("   note: No checksum recorded (started with an older git-slides)."),
+        }
+
+        let hashes: Vec<&str> = slides.iter().map(|slide| slide.hash.as_str()).collect();
+        let missing = git::missing_commits(&hashes);
+        if missing.is_empty() {
+            println!("     ok: All {total} slides exist.");
+        } else {
+            for (n, Commit { hash, title }) in slides.iter().enumerate() {
+                if missing.contains(hash) {
+                    let hash = self.short_hash(hash);
+                    println!("missing: {}/{total} {hash} {title}", n + 1);
+                }
+            }
+            problem(
+                &format!("{} of {total} slides are missing.", missing.len()),
+                "Use 'git slides repair <ref>' to remap the presentation.",
+            );
+        }
+
+        std::process::exit(i32::from(problems > 0));
+    }
+
+    /// Take an exclusive lock on the presentation, so that concurrent
+    /// invocations (e.g., a clicker double-sending) run one after the
+    /// other. The lock is released when the returned file is dropped.
+    ///
+    /// Gives up after a while, in case another invocation is stuck
+    /// (e.g., in an editor).
+    pub fn lock(&self) -> fs::File {
+        const TIMEOUT: Duration = Duration::from_secs(2);
+        const RETRY_DELAY: Duration = Duration::from_millis(20);
+
+        let lock_file = self.store_sibling(".lock");
+        let Ok(file) = fs::File::create(&lock_file) else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot create '{}'. Aborting.",
+                    lock_file.display()
+                )
+            );
+            std::process::exit(1);
+        };
+
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => return file,
+                Err(fs::TryLockError::WouldBlock) if start.elapsed() < TIMEOUT => {
+                    thread::sleep(RETRY_DELAY);
+                }
+                Err(fs::TryLockError::WouldBlock) => {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "error: Another git-slides process is running. Aborting."
+                        )
+                    );
+                    std::process::exit(1);
+                }
+                // E.g., a network file system without locks. Better run
+                // unlocked than not at all.
+                Err(fs::TryLockError::Error(err)) if err.kind() == io::ErrorKind::Unsupported => {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "warning: Cannot lock '{}': {}.",
+                            self.display_path(&lock_file),
+                            err
+                        )
+                    );
+                    return file;
+                }
+                Err(fs::TryLockError::Error(err)) => {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "error: Cannot lock '{}': {}. Aborting.",
+                            self.display_path(&lock_file),
+                            err
+                        )
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    pub fn is_presentation_started(&self) -> bool {
+        let store_file = self.store_file();
+        store_file.is_file()
+    }
+
+    /// Stash, refuse or discard uncommitted changes before leaving the
+    /// current slide, depending on `slides.dirtyAction`.
+    #[cfg(not(tarpaulin_include))]
+    fn handle_uncommitted_changes(&mut self) -> Result<(), Exit> {
+        match self.options.dirty_action {
+            DirtyAction::Stash => self.stash_uncommitted_changes()?,
+            DirtyAction::Block => {
+                if self.git.is_working_directory_clean() {
+                    return Ok(());
+                }
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Working directory contains uncommitted changes."
+                    )
+                );
+                eprint!("{}", self.git.uncommitted_changes());
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "hint: Commit or stash them first ('slides.dirtyAction' is 'block')."
+                    )
+                );
+                return Err(Exit(1));
+            }
+            DirtyAction::Discard => {
+                if self.git.is_working_directory_clean() {
+                    return Ok(());
+                }
+                if let Err(stderr) = self.git.discard_changes() {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "error: Could not discard uncommitted changes."
+                        )
+                    );
+                    relay_git_error(&stderr);
+                    return Err(Exit(1));
+                }
+                self.print_info(&tr!(
+                    self.options.language,
+                    "Discarded uncommitted changes."
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stash uncommitted changes, labeled with the current slide, and
+    /// record the stash, so it can be told apart from the user's own.
+    fn stash_uncommitted_changes(&mut self) -> Result<(), Exit> {
+        if self.git.is_working_directory_clean() {
+            return Ok(());
+        }
+
+        let slide = self.get_index_of_current_commit_checked().map(|n| n + 1);
+        let message = slide.map_or_else(
+            || String::from("git-slides"),
+            |n| format!("git-slides: slide {n}"),
+        );
+        let hash = match self.git.stash(&message) {
+            Ok(hash) => hash,
+            Err(stderr) => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not stash uncommitted changes."
+                    )
+                );
+                relay_git_error(&stderr);
+                return Err(Exit(1));
+            }
+        };
+        self.print_info(&tr!(self.options.language, "Stashed uncommitted changes."));
+
+        let mut store = self.take_store();
+        store.stashes.push(Stash { hash, slide });
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+
+        Ok(())
+    }
+
+    /// Bring back the changes last stashed on slide `n` (1-indexed), if
+    /// any.
+    fn restore_scratch(&mut self, n: usize) {
+        let Some(i) = self
+            .get_store()
+            .stashes
+            .iter()
+            .rposition(|stash| stash.slide == Some(n))
+        else {
+            return;
+        };
+
+        let mut store = self.take_store();
+        let stash = store.stashes.remove(i);
+        match self.git.stash_index(&stash.hash) {
+            // Dropped by the user in the meantime, forget about it.
+            None => (),
+            Some(index) => match self.git.stash_pop(index) {
+                Ok(()) => self.print_info(&tr!(
+                    self.options.language,
+                    "Restored changes of slide {}.",
+                    n
+                )),
+                Err(stderr) => {
+                    eprintln!("{}", tr!(self.options.language, "warning: Could not restore changes of slide {}, they are still stashed.", n));
+                    relay_git_error(&stderr);
+                }
+            },
+        }
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+    }
+
+    #[cfg(not(tarpaulin_include))] // Does not ignore 'eprintln()'.
+    fn post_checkout(&self) {
+        if self.options.recurse_submodules && !git::update_submodules() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Could not update submodules."
+                )
+            );
+        }
+
+        match self.options.lfs {
+            Some(Lfs::Checkout) if !git::lfs_checkout() => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: Could not run 'git lfs checkout'."
+                    )
+                );
+            }
+            Some(Lfs::Pull) if !git::lfs_pull() => {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: Could not run 'git lfs pull'."
+                    )
+                );
+            }
+            _ => (),
+        }
+    }
+
+    fn publish_current_slide(&self, hash: &str) {
+        if let Some(remote) = &self.get_store().publish {
+            if !git::force_push(remote, hash, PUBLISHED_REF) {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: Could not publish the current slide to '{}'.",
+                        remote
+                    )
+                );
+            }
+        }
+    }
+
+    /// Name the tmux window after slide `n` (0-indexed), and refresh
+    /// the companion pane, if any.
+    fn update_tmux(&self, n: usize) {
+        let Some(Tmux {
+            refresh_pane,
+            refresh_keys,
+        }) = &self.options.tmux
+        else {
+            return;
+        };
+        if !tmux::is_inside() {
+            return;
+        }
+
+        let title = self.display_title(&self.get_history()[n].title);
+        if !tmux::rename_window(title) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Could not rename tmux window."
+                )
+            );
+        }
+
+        if let Some(pane) = refresh_pane {
+            if !tmux::send_keys(pane, refresh_keys) {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: Could not send keys to tmux pane '{}'.",
+                        pane
+                    )
+                );
+            }
+        }
+    }
+
+    /// Metadata of slide `n` (0-indexed), for hooks.
+    fn hook_env(&self, n: usize) -> Vec<(&'static str, String)> {
+        let history = self.get_history();
+        let Commit { hash, title } = &history[n];
+        vec![
+            ("GIT_SLIDES_INDEX", (n + 1).to_string()),
+            ("GIT_SLIDES_TOTAL", history.len().to_string()),
+            ("GIT_SLIDES_HASH", hash.clone()),
+            ("GIT_SLIDES_TITLE", title.clone()),
+        ]
+    }
+
+    /// Run the `Slide-Exec` trailers of `commit`, in order, through the
+    /// shell.
+    ///
+    /// This runs commands from commit messages, so it is opt-in, and
+    /// only hinted at otherwise.
+    fn run_slide_exec(&self, commit: &str) {
+        let commands = self.git.trailer_values(commit, "Slide-Exec");
+        if commands.is_empty() {
+            return;
+        }
+
+        if !self.options.exec {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "hint: This slide has 'Slide-Exec' commands, they were not run."
+                )
+            );
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "hint: Use 'git config slides.exec true' to run them."
+                )
+            );
+            return;
+        }
+
+        for command in commands {
+            match shell(&command).status() {
+                Ok(status) if status.success() => (),
+                _ => eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: Slide-Exec command failed: '{}'.",
+                        command
+                    )
+                ),
+            }
+        }
+    }
+
+    /// Run `slides.viewer` for slide `n` (0-indexed), from the root of
+    /// the working tree.
+    fn run_viewer(&self, n: usize) {
+        let Some(viewer) = &self.options.viewer else {
+            return;
+        };
+        let command = self.expand_placeholders(viewer, n, shell_quote);
+        let mut shell = shell(&command);
+        if let Some(top_level) = git::top_level() {
+            shell.current_dir(top_level);
+        }
+        match shell.status() {
+            Ok(status) if status.success() => (),
+            _ => eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Viewer command failed: '{}'.",
+                    command
+                )
+            ),
+        }
+    }
+
+    /// Replace the content of `slides.infoFile`, if set.
+    fn write_info_file(&self, content: &str) {
+        let Some(info_file) = &self.options.info_file else {
+            return;
+        };
+        if fs::write(info_file, content).is_err() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Cannot write info file '{}'.",
+                    self.display_path(info_file)
+                )
+            );
+        }
+    }
+
+    /// Hand the `slides.slideFile` of slide `n` (0-indexed) over to
+    /// a slide renderer (e.g., presenterm), as an absolute path.
+    ///
+    /// Slides without the file (e.g., code-only slides) are skipped.
+    fn hand_off_slide_file(&self, n: usize) {
+        const PIPE_TIMEOUT: Duration = Duration::from_secs(1);
+
+        let Some(slide_file) = &self.options.slide_file else {
+            return;
+        };
+        let Some(top_level) = git::top_level() else {
+            return;
+        };
+        let path = top_level.join(self.expand_placeholders(slide_file, n, str::to_string));
+        if !path.is_file() {
+            return;
+        }
+
+        let Some(pipe) = self.options.slide_file_pipe.clone() else {
+            println!("{}", path.display());
+            return;
+        };
+        // Opening a FIFO blocks until there is a reader, don't hang on
+        // it if the renderer is not running.
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let content = format!("{}\n", path.display());
+        let writer = pipe.clone();
+        thread::spawn(move || {
+            let _ = sender.send(fs::write(&writer, content).is_ok());
+        });
+        if receiver.recv_timeout(PIPE_TIMEOUT) != Ok(true) {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: Could not write the slide file to '{}'.",
+                    pipe.display()
+                )
+            );
+        }
+    }
+
+    /// Run `slides.onSlide` for slide `n` (0-indexed), from the root of
+    /// the working tree, with the slide in `SLIDE_*` variables.
+    fn run_on_slide(&self, n: usize) {
+        let Some(command) = &self.options.on_slide else {
+            return;
+        };
+        let history = self.get_history();
+        let Commit { hash, title } = &history[n];
+        let mut shell = shell(command);
+        shell
+            .env("SLIDE_INDEX", (n + 1).to_string())
+            .env("SLIDE_TOTAL", history.len().to_string())
+            .env("SLIDE_HASH", hash)
+            .env("SLIDE_TITLE", title);
+        if let Some(top_level) = git::top_level() {
+            shell.current_dir(top_level);
+        }
+        match shell.status() {
+            Ok(status) if status.success() => (),
+            _ => eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "warning: onSlide command failed: '{}'.",
+                    command
+                )
+            ),
+        }
+    }
+
+    /// Abbreviate `hash`, like Git would, but to the same length for
+    /// all slides, so they line up.
+    fn short_hash<'a>(&self, hash: &'a str) -> &'a str {
+        const DEFAULT_ABBREV: usize = 7;
+
+        let len = *self.abbrev.get_or_init(|| {
+            let hashes: Vec<&str> = if self.is_presentation_started() {
+                self.get_commits_hashes()
+                    .into_iter()
+                    .map(String::as_str)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            (!hashes.is_empty())
+                .then(|| self.git.abbrev_len(&hashes, self.options.abbrev))
+                .flatten()
+                .unwrap_or(self.options.abbrev.unwrap_or(DEFAULT_ABBREV))
+        });
+        &hash[..len.min(hash.len())]
+    }
+
+    /// Branches and tags pointing to `hash`, like `git log --decorate`
+    /// (e.g., ` (exercise-3, tag: v1.0)`), or nothing.
+    fn decoration(&self, hash: &str) -> String {
+        let decorations = self.decorations.get_or_init(|| {
+            let mut decorations: HashMap<String, Vec<String>> = HashMap::new();
+            for (branch, hash) in self.git.branches() {
+                decorations.entry(hash).or_default().push(branch);
+            }
+            for (tag, hash) in self.git.tags() {
+                decorations
+                    .entry(hash)
+                    .or_default()
+                    .push(format!("tag: {tag}"));
+            }
+            decorations
+        });
+        decorations
+            .get(hash)
+            .map(|refs| format!(" ({})", refs.join(", ")))
+            .unwrap_or_default()
+    }
+
+    fn get_commits_hashes(&self) -> Vec<&String> {
+        let history = self.get_history();
+        history.iter().map(|x| &x.hash).collect()
+    }
+
+    /// Slides of the presentation, without hidden ones.
+    fn get_history(&self) -> &Vec<Commit> {
+        // This function is expensive, and is called multiple times.
+        // Calling it multiple times simplifies the API a lot, so we
+        // cache the result instead of changing the API.
+        self.history.get_or_init(|| {
+            let store = self.get_store();
+            Self::without_hidden(store, self.history_from_store(store))
+        })
+    }
+
+    /// `history`, without the slides `store` says are hidden.
+    fn without_hidden(store: &Store, mut history: Vec<Commit>) -> Vec<Commit> {
+        if !store.hidden.is_empty() {
+            history.retain(|slide| !store.hidden.iter().any(|x| x.hash == slide.hash));
+        }
+        history
+    }
+
+    /// Sections, numbered like slides are, without hidden slides. Those
+    /// left with no slides are dropped.
+    fn get_sections(&self) -> &Vec<Section> {
+        self.sections.get_or_init(|| {
+            let store = self.get_store();
+            if store.hidden.is_empty() {
+                return store.sections.clone();
+            }
+            let is_hidden = |slide: &Commit| store.hidden.iter().any(|x| x.hash == slide.hash);
+            let full_history = self.history_from_store(store);
+            let visible = full_history.len() - full_history.iter().filter(|x| is_hidden(x)).count();
+
+            let mut sections: Vec<Section> = Vec::new();
+            for Section { title, first } in &store.sections {
+                let before = first.saturating_sub(1).min(full_history.len());
+                let first = full_history[..before]
+                    .iter()
+                    .filter(|x| !is_hidden(x))
+                    .count()
+                    + 1;
+                if first > visible {
+                    break;
+                }
+                // The previous section is all hidden.
+                if sections.last().is_some_and(|last| last.first == first) {
+                    sections.pop();
+                }
+                sections.push(Section {
+                    title: title.clone(),
+                    first,
+                });
+            }
+            sections
+        })
+    }
+
+    fn history_from_store(&self, store: &Store) -> Vec<Commit> {
+        if let Some(slides) = store.slides.as_ref().or(store.cached_slides()) {
+            return slides.clone();
+        }
+        self.git.history_up_to_commit(
+            &store.head,
+            &HistoryOptions {
+                from: store.from.as_deref(),
+                all_parents: store.all_parents,
+                pattern: store.pattern.as_deref(),
+                exclude: &[],
+            },
+        )
+    }
+
+    fn tagged_slides(&self, pattern: &str, order: TagOrder) -> Vec<Commit> {
+        let sort = match order {
+            TagOrder::Name => "version:refname",
+            TagOrder::Date => "creatordate",
+        };
+
+        let mut hashes = git::tags_matching(pattern, sort);
+
+        // Multiple tags may point to the same commit; keep the first.
+        let mut seen = HashSet::new();
+        hashes.retain(|hash| seen.insert(hash.clone()));
+
+        self.git.commits(&hashes)
+    }
+
+    fn manifest_slides(&self, path: &str) -> Vec<Commit> {
+        let Ok(content) = fs::read_to_string(path) else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot read manifest '{}'.",
+                    path
+                )
+            );
+            std::process::exit(1);
+        };
+        self.resolve_manifest(&content)
     }
 
-    pub fn start(&self, ref_: Option<String>) {
-        if !git::is_working_directory_clean() {
-            eprintln!("error: Working directory contains uncommitted changes.");
+    fn resolve_manifest(&self, content: &str) -> Vec<Commit> {
+        let entries = manifest::parse(content);
+
+        // Validate everything first, to report all errors at once.
+        let mut hashes = Vec::with_capacity(entries.len());
+        let mut seen = HashMap::new();
+        let mut has_errors = false;
+        for entry in &entries {
+            let Some(hash) = self.git.ref_to_commit_hash(&entry.ref_) else {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Bad ref in manifest, line {}: '{}'.",
+                        entry.line,
+                        entry.ref_
+                    )
+                );
+                has_errors = true;
+                continue;
+            };
+            // The current slide is found from HEAD, so it must be unique.
+            if let Some(first) = seen.insert(hash.clone(), entry.line) {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Duplicate slide in manifest, line {}: '{}' (same as line {}).",
+                        entry.line,
+                        entry.ref_,
+                        first
+                    )
+                );
+                has_errors = true;
+                continue;
+            }
+            hashes.push(hash);
+        }
+        if has_errors {
             std::process::exit(1);
         }
 
-        let commit_hash = if let Some(ref_) = ref_ {
-            git::ref_to_commit_hash(&ref_).unwrap_or_else(|| {
-                eprintln!("error: Bad ref input: '{ref_}'.");
-                std::process::exit(1);
-            })
-        } else {
-            git::current_commit_hash().unwrap_or_else(|| {
-                eprintln!("error: No HEAD commit. Please provide a valid ref.");
-                std::process::exit(1);
+        let titles: HashMap<String, String> = self
+            .git
+            .commits(&hashes)
+            .into_iter()
+            .map(|Commit { hash, title }| (hash, title))
+            .collect();
+
+        entries
+            .into_iter()
+            .zip(hashes)
+            .map(|(entry, hash)| {
+                let title = entry
+                    .title
+                    .or_else(|| titles.get(&hash).cloned())
+                    .unwrap_or_default();
+                Commit { hash, title }
             })
+            .collect()
+    }
+
+    fn get_store(&self) -> &Store {
+        self.store.get_or_init(|| {
+            let (store, version) = match Store::parse(&self.read_store_file()) {
+                Ok(parsed) => parsed,
+                Err(store::Error::Malformed) => {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "error: Cannot parse '{}'. Aborting.",
+                            self.display_path(&self.store_file())
+                        )
+                    );
+                    std::process::exit(1);
+                }
+                Err(store::Error::UnsupportedVersion(version)) => {
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "error: '{}' uses format version {}, which is not supported.",
+                            self.display_path(&self.store_file()),
+                            version
+                        )
+                    );
+                    eprintln!(
+                        "{}",
+                        tr!(
+                            self.options.language,
+                            "It was likely written by a newer version of git-slides. Aborting."
+                        )
+                    );
+                    std::process::exit(1);
+                }
+            };
+            if version < Store::VERSION {
+                // Migrate, so the file is always in the latest format.
+                self.write_store_file(&store);
+            }
+            store
+        })
+    }
+
+    /// Integrate a new commit (HEAD) into the presentation, in place of,
+    /// or after, slide `n` (0-indexed), depending on `rewrite`.
+    ///
+    /// If the slides come from history, the slides after `n` are rebased
+    /// on top of the new commit. The presentation head is updated, and so
+    /// is the initial branch if it pointed to the presentation head.
+    fn rewrite_slide(&mut self, n: usize, rewrite: SlideRewrite) {
+        let old = self.get_history()[n].hash.clone();
+        let Some(new) = self.git.current_commit_hash() else {
+            eprintln!("{}", tr!(self.options.language, "error: No HEAD commit."));
+            std::process::exit(1);
         };
 
-        let branch_name = git::current_branch().unwrap_or_default();
+        let mut store = self.take_store();
 
-        let store_file = self.store_file();
-        #[cfg(not(tarpaulin_include))]
-        {
-            if fs::write(store_file, format!("{branch_name}:{commit_hash}\n")).is_err() {
-                eprintln!("error: Cannot write '.git/{STORE_FILE}'. Aborting.");
+        if let Some(slides) = &mut store.slides {
+            // Hidden slides shift positions in the explicit list.
+            let n = slides
+                .iter()
+                .position(|slide| slide.hash == old)
+                .unwrap_or(n);
+            // Explicit slides are independent commits, nothing to rebase.
+            let commit = self
+                .git
+                .commits(slice::from_ref(&new))
+                .pop()
+                .unwrap_or(Commit {
+                    hash: new.clone(),
+                    title: String::new(),
+                });
+            match rewrite {
+                SlideRewrite::Insert => {
+                    slides.insert(n + 1, commit);
+                    // Later sections start one slide later.
+                    for section in &mut store.sections {
+                        if section.first > n + 1 {
+                            section.first += 1;
+                        }
+                    }
+                }
+                SlideRewrite::Replace => slides[n] = commit,
+            }
+        } else {
+            let new_head = if old == store.head {
+                new.clone()
+            } else if git::rebase_onto(&new, &old, &store.head) {
+                self.git.current_commit_hash().unwrap_or_default()
+            } else {
+                let _ = self.git.checkout(&old);
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "error: Could not rebase the following slides onto the new commit."
+                    )
+                );
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "The presentation is unchanged. The new commit is {}.",
+                        new
+                    )
+                );
                 std::process::exit(1);
+            };
+
+            if let Some(branch) = &store.branch {
+                if self.git.ref_to_commit_hash(branch).as_ref() == Some(&store.head)
+                    && git::update_branch(branch, &new_head, &store.head)
+                {
+                    self.print_info(&tr!(self.options.language, "Updated branch '{}'.", branch));
+                }
             }
+
+            if matches!(rewrite, SlideRewrite::Replace) && store.from.as_ref() == Some(&old) {
+                store.from = Some(new.clone());
+            }
+            store.head = new_head;
         }
 
-        println!("Presentation started at {commit_hash}.");
+        self.set_store(store);
+
+        // The new commit may be filtered out (e.g., by `--match`).
+        let target = self
+            .get_commits_hashes()
+            .iter()
+            .position(|x| **x == new)
+            .unwrap_or_else(|| {
+                eprintln!(
+                    "{}",
+                    tr!(
+                        self.options.language,
+                        "warning: The new commit is not part of the presentation."
+                    )
+                );
+                cmp::min(n + 1, self.get_history().len() - 1)
+            });
 
-        self.go(1);
+        exit_on_error(self.go(target + 1));
     }
 
-    pub fn stop(&self) {
-        self.ensure_presentation_is_started();
+    /// Take the store out, e.g., to modify it before [`Self::set_store()`].
+    fn take_store(&mut self) -> Store {
+        self.get_store();
+        self.store.take().expect("initialized just above")
+    }
 
-        Self::stash_uncommitted_changes();
+    /// Replace the store, on disk and in memory.
+    fn set_store(&mut self, mut store: Store) {
+        // Resolve the history again, the head may have changed.
+        store.cache = None;
+        let history = self.history_from_store(&store);
+        let store = Self::with_checksum(Self::with_history_cache(store, &history), &history);
 
-        println!("Presentation stopped.");
+        self.write_store_file(&store);
+        self.store = OnceCell::from(store);
+        // Hidden slides are left out, and sections renumbered.
+        self.history = OnceCell::new();
+        self.sections = OnceCell::new();
+    }
 
-        if let Some(initial_branch) = self.get_initial_branch() {
-            println!("Going back to branch '{initial_branch}'.");
-            let _ = git::checkout(&initial_branch);
-        } else {
-            // The user was likely in detached mode when the presentation started.
-            let head_commit = self.get_presentation_head_commit_hash();
-            println!("Going back to commit {head_commit}.");
-            let _ = git::checkout(&head_commit);
+    /// Cache slides resolved from history, so that later invocations
+    /// don't need to resolve them again.
+    fn with_history_cache(mut store: Store, history: &[Commit]) -> Store {
+        if store.slides.is_none() {
+            store.cache = Some(HistoryCache {
+                head: store.head.clone(),
+                slides: history.to_vec(),
+            });
         }
+        store
+    }
+
+    /// Record the number of slides and their checksum, for `verify`.
+    fn with_checksum(mut store: Store, history: &[Commit]) -> Store {
+        store.slide_count = Some(history.len());
+        store.checksum = Some(Store::checksum(history));
+        store
+    }
 
+    #[cfg(not(tarpaulin_include))]
+    fn write_store_file(&self, store: &Store) {
         let store_file = self.store_file();
-        #[cfg(not(tarpaulin_include))]
+        // Written aside, then renamed over, so `events` (which does
+        // not lock) never reads a half-written store.
+        let new_file = self.store_sibling(".new");
+        if fs::write(&new_file, store.to_string())
+            .and_then(|()| fs::rename(&new_file, &store_file))
+            .is_err()
         {
-            if fs::remove_file(store_file).is_err() {
-                eprintln!("error: Cannot remove '.git/{STORE_FILE}'. Aborting.");
-                std::process::exit(1);
-            }
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot write '{}'. Aborting.",
+                    self.display_path(&store_file)
+                )
+            );
+            std::process::exit(1);
         }
     }
 
-    pub fn next(&self, offset: usize) {
-        self.ensure_presentation_is_started();
+    #[cfg(not(tarpaulin_include))]
+    fn read_store_file(&self) -> String {
+        let store_file = self.store_file();
+        let Ok(store) = fs::read_to_string(&store_file) else {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "error: Cannot read '{}'. Aborting.",
+                    self.display_path(&store_file)
+                )
+            );
+            std::process::exit(1);
+        };
+        store
+    }
 
-        let commits = self.get_history();
-        let n = self.get_index_of_current_commit();
+    fn store_file(&self) -> PathBuf {
+        self.options
+            .store_file
+            .clone()
+            .unwrap_or_else(|| self.git_dir.join(STORE_FILE))
+    }
 
-        let n = n + 1 + offset;
+    /// Path next to the store file, named after it (e.g., the lock).
+    fn store_sibling(&self, suffix: &str) -> PathBuf {
+        let mut path = self.store_file().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
 
-        if n >= commits.len() {
-            println!("You've reached the end of the presentation.");
-        }
+    /// Stores of past presentations, kept after they stopped, named
+    /// after when they started.
+    fn runs_dir(&self) -> PathBuf {
+        self.store_sibling("-runs")
+    }
 
-        self.go(cmp::min(n, commits.len()));
+    /// Path for messages, relative to `.git/` if it is in there.
+    fn display_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.git_dir).map_or_else(
+            |_| path.display().to_string(),
+            |path| format!(".git/{}", path.display()),
+        )
     }
 
-    pub fn previous(&self, offset: usize) {
-        self.ensure_presentation_is_started();
+    /// Stores of past presentations, oldest first.
+    fn recorded_runs(&self) -> Vec<Store> {
+        let Ok(entries) = fs::read_dir(self.runs_dir()) else {
+            return Vec::new();
+        };
+        let mut runs: Vec<(u64, Store)> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let started = path.file_name()?.to_str()?.parse().ok()?;
+                let (store, _) = Store::parse(&fs::read_to_string(&path).ok()?).ok()?;
+                Some((started, store))
+            })
+            .collect();
+        runs.sort_by_key(|(started, _)| *started);
+        runs.into_iter().map(|(_, store)| store).collect()
+    }
 
-        let n = self.get_index_of_current_commit();
+    fn get_index_of_current_commit(&self) -> usize {
+        exit_on_error(self.try_get_index_of_current_commit())
+    }
 
-        let n = (n + 1).saturating_sub(offset);
+    fn try_get_index_of_current_commit(&self) -> Result<usize, Exit> {
+        if let Some(commit) = self.get_index_of_current_commit_checked() {
+            return Ok(commit);
+        }
+        if self.options.recover {
+            if let Some(commit) = self.recover()? {
+                return Ok(commit);
+            }
+        }
 
-        if n <= 1 {
-            println!("You're at the start of the presentation.");
+        eprintln!(
+            "{}",
+            tr!(
+                self.options.language,
+                "error: Current HEAD not part of presentation."
+            )
+        );
+        if self.looks_rewritten() {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "hint: History seems to have been rewritten (e.g., rebased)."
+                )
+            );
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "hint: Use 'git slides repair' to remap the presentation."
+                )
+            );
+        } else if !self.options.recover {
+            eprintln!(
+                "{}",
+                tr!(
+                    self.options.language,
+                    "hint: Use 'git slides --recover <command>' to go back to the nearest slide."
+                )
+            );
+        }
+        Err(Exit(1))
+    }
+
+    /// Check out the last slide HEAD descends from (e.g., after checking
+    /// out a branch started from a slide), and return its index.
+    fn recover(&self) -> Result<Option<usize>, Exit> {
+        let Some(head) = self.git.current_commit_hash() else {
+            return Ok(None);
+        };
+        let Some(n) = self
+            .get_commits_hashes()
+            .iter()
+            .rposition(|slide| self.git.is_ancestor(slide, &head))
+        else {
+            return Ok(None);
+        };
+        let hash = &self.get_history()[n].hash;
+
+        eprintln!(
+            "{}",
+            tr!(
+                self.options.language,
+                "warning: Current HEAD not part of presentation, going back to slide {}.",
+                n + 1
+            )
+        );
+        if !self.options.dry_run {
+            if let Err(stderr) = self.git.checkout(hash) {
+                eprintln!(
+                    "{}",
+                    tr!(self.options.language, "error: Could not checkout {}.", hash)
+                );
+                relay_git_error(&stderr);
+                return Err(Exit(1));
+            }
+            self.post_checkout();
         }
 
-        self.go(cmp::max(n, 1));
+        Ok(Some(n))
     }
 
-    pub fn go(&self, n: usize) {
-        self.ensure_presentation_is_started();
+    /// Whether HEAD looks like a rewritten slide (same title), or the
+    /// initial branch has moved away from the presentation.
+    fn looks_rewritten(&self) -> bool {
+        let store = self.get_store();
+        if let Some(branch) = store.branch.as_deref() {
+            if self
+                .git
+                .ref_to_commit_hash(branch)
+                .is_some_and(|tip| !self.git.is_ancestor(&store.head, &tip))
+            {
+                return true;
+            }
+        }
+        let Some(head) = self.git.current_commit_hash() else {
+            return false;
+        };
+        let Some(Commit { title, .. }) = self.git.commits(&[head]).pop() else {
+            return false;
+        };
+        self.get_history()
+            .iter()
+            .any(|commit| commit.title == title)
+    }
 
-        let commits = self.get_commits_hashes();
+    /// Index of the slide `hash` is, or of the first slide it leads to
+    /// (e.g., a commit skipped by `--match`).
+    fn get_index_of_slide_containing(&self, hash: &str) -> Option<usize> {
+        let hashes = self.get_commits_hashes();
+        if let Some(n) = hashes.iter().position(|x| *x == hash) {
+            return Some(n);
+        }
 
-        if n < 1 || n > commits.len() {
-            eprintln!("error: Bad slide index. Slide {n} does not exist.");
-            eprintln!("Possible values range from 1 to {}.", commits.len());
-            std::process::exit(1);
+        // Commits preceding `--from` are not part of the presentation,
+        // even though they lead to the first slide.
+        let store = self.get_store();
+        if let Some(from) = &store.from {
+            if self.git.is_ancestor(hash, from) {
+                return None;
+            }
         }
 
-        let go_to = commits.get(n - 1).expect("bounds checked");
+        hashes.iter().position(|x| self.git.is_ancestor(hash, x))
+    }
 
-        Self::stash_uncommitted_changes();
+    // May return `None` if user checked out to non-presentation commit,
+    // or deleted commits.
+    fn get_index_of_current_commit_checked(&self) -> Option<usize> {
+        let hash = self.git.current_commit_hash()?;
 
-        if !git::checkout(go_to) {
-            eprintln!("error: Could not checkout {go_to}.");
-            std::process::exit(1);
-        }
+        let hashes = self.get_commits_hashes();
+        hashes.into_iter().position(|x| *x == hash)
+    }
+}
 
-        self.status();
+/// Quote a CSV field if needed (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
     }
+}
 
-    pub fn status(&self) {
-        const SHOW_N_PREVIOUS: usize = 2;
-        const SHOW_N_NEXT: usize = 3;
+/// Print what Git said about a failure, after our own error message.
+fn relay_git_error(stderr: &str) {
+    if !stderr.is_empty() {
+        eprintln!("{stderr}");
+    }
+}
 
-        self.ensure_presentation_is_started();
+/// Run `command` through the shell.
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
 
-        let history = self.get_history();
-        let n = self.get_index_of_current_commit();
+/// Quote a value for POSIX shells.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
 
-        let display_from = n.saturating_sub(SHOW_N_PREVIOUS);
-        let display_to = std::cmp::min(n + SHOW_N_NEXT, history.len() - 1);
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::path::PathBuf;
+    use std::{env, fs, process};
 
-        let slide_number_padding = history.len().to_string().len();
+    use git_slides::git::{Commit, GitBackend, HistoryOptions, Operation};
 
-        // Acquire the lock once (instead of on every call to `print!`).
-        let mut stdout = io::stdout().lock();
+    use super::{Cmd, DirtyAction, Exit, Options, EXIT_BOUNDARY, STORE_FILE};
+    use crate::store::Store;
 
-        if n.checked_sub(SHOW_N_PREVIOUS).is_none() {
-            let _ = writeln!(stdout, "  {COLOR_FAINT}(Start){COLOR_RESET}");
+    /// Linear history, in memory. Checking out and stashing can be made
+    /// to fail, like they would with conflicting changes or a broken
+    /// index.
+    #[derive(Default)]
+    struct Fake {
+        history: Vec<Commit>,
+        head: RefCell<String>,
+        dirty: Cell<bool>,
+        stashes: RefCell<Vec<String>>,
+        checkout_fails: bool,
+        stash_fails: bool,
+    }
+
+    impl Fake {
+        fn new(slides: usize) -> Self {
+            let history: Vec<Commit> = (1..=slides)
+                .map(|n| Commit {
+                    hash: format!("{n:040}"),
+                    title: format!("Slide {n}"),
+                })
+                .collect();
+            let head = RefCell::new(history[0].hash.clone());
+            Self {
+                history,
+                head,
+                ..Self::default()
+            }
         }
 
-        for i in display_from..=display_to {
-            let Commit { hash, title } = history.get(i).expect("bounds have been checked");
+        fn position(&self, hash: &str) -> Option<usize> {
+            self.history.iter().position(|x| x.hash == hash)
+        }
+    }
 
-            if i == n {
-                let _ = write!(stdout, "* ");
-            } else {
-                let _ = write!(stdout, "  ");
+    impl GitBackend for Fake {
+        fn current_commit_hash(&self) -> Option<String> {
+            Some(self.head.borrow().clone())
+        }
+        fn ref_to_commit_hash(&self, ref_: &str) -> Option<String> {
+            let commit = self.history.iter().find(|x| x.hash.starts_with(ref_))?;
+            Some(commit.hash.clone())
+        }
+        fn commits(&self, hashes: &[String]) -> Vec<Commit> {
+            let find = |hash: &String| self.history.iter().find(|x| &x.hash == hash);
+            hashes.iter().filter_map(find).cloned().collect()
+        }
+        fn history_up_to_commit(&self, commit: &str, _: &HistoryOptions) -> Vec<Commit> {
+            let n = self.position(commit);
+            n.map_or_else(Vec::new, |n| self.history[..=n].to_vec())
+        }
+        fn is_working_directory_clean(&self) -> bool {
+            !self.dirty.get()
+        }
+        fn checkout(&self, commit: &str) -> Result<(), String> {
+            if self.checkout_fails {
+                return Err(String::from(
+                    "error: Your local changes would be overwritten by checkout.",
+                ));
             }
-
-            if i < n {
-                let _ = writeln!(
-                    stdout,
-                    "{COLOR_FAINT}{:>slide_number_padding$}/{} {} {title}{COLOR_RESET}",
-                    i + 1,
-                    history.len(),
-                    &hash[..7],
-                );
+            *self.head.borrow_mut() = self.ref_to_commit_hash(commit).ok_or("bad ref")?;
+            Ok(())
+        }
+        fn stash(&self, message: &str) -> Result<String, String> {
+            if self.stash_fails {
+                return Err(String::from("error: could not write index"));
+            }
+            let hash = format!("stash {}: {message}", self.stashes.borrow().len());
+            self.stashes.borrow_mut().insert(0, hash.clone());
+            self.dirty.set(false);
+            Ok(hash)
+        }
+        fn stash_pop(&self, n: usize) -> Result<(), String> {
+            self.stashes.borrow_mut().remove(n);
+            self.dirty.set(true);
+            Ok(())
+        }
+        fn abbrev_len(&self, _: &[&str], min: Option<usize>) -> Option<usize> {
+            min
+        }
+        fn tags(&self) -> Vec<(String, String)> {
+            Vec::new()
+        }
+        fn branches(&self) -> Vec<(String, String)> {
+            Vec::new()
+        }
+        fn is_ancestor(&self, ancestor: &str, commit: &str) -> bool {
+            matches!(
+                (self.position(ancestor), self.position(commit)),
+                (Some(ancestor), Some(commit)) if ancestor <= commit
+            )
+        }
+        fn trailer_values(&self, _: &str, _: &str) -> Vec<String> {
+            Vec::new()
+        }
+        fn note(&self, _: &str, _: &str) -> Option<String> {
+            None
+        }
+        fn operation_in_progress(&self) -> Option<Operation> {
+            None
+        }
+        fn uncommitted_changes(&self) -> String {
+            if self.dirty.get() {
+                String::from(" M README.md\n")
             } else {
-                let _ = writeln!(
-                    stdout,
-                    "{:>slide_number_padding$}/{} {COLOR_YELLOW}{}{COLOR_RESET} {title}",
-                    i + 1,
-                    history.len(),
-                    &hash[..7],
-                );
+                String::new()
             }
         }
-
-        if n + SHOW_N_NEXT > history.len() - 1 {
-            let _ = writeln!(stdout, "  {COLOR_FAINT}(End){COLOR_RESET}");
+        fn discard_changes(&self) -> Result<(), String> {
+            self.dirty.set(false);
+            Ok(())
+        }
+        fn stash_index(&self, hash: &str) -> Option<usize> {
+            self.stashes.borrow().iter().position(|x| x == hash)
         }
     }
 
-    pub fn list(&self) {
-        self.ensure_presentation_is_started();
+    /// Empty directory standing in for `.git/`, to hold the store.
+    fn git_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("git-slides-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
 
-        let history = self.get_history();
-        let n = self.get_index_of_current_commit();
+    /// Presentation of all of `git`'s history, started on its HEAD.
+    fn presentation(name: &str, git: Fake, options: Options) -> Cmd {
+        let git_dir = git_dir(name);
+        let store = Store {
+            head: git.history.last().unwrap().hash.clone(),
+            started: Some(0),
+            ..Store::default()
+        };
+        fs::write(git_dir.join(STORE_FILE), store.to_string()).unwrap();
+        Cmd::new(Box::new(git), git_dir, options)
+    }
 
-        let slide_number_padding = history.len().to_string().len();
+    fn options() -> Options {
+        Options {
+            step: 1,
+            quiet: true,
+            ..Options::default()
+        }
+    }
 
-        // Pre-allocate a "best-guess" number of characters. Each line
-        // includes padding, slide number, commit hash, title and color.
-        let mut out = String::with_capacity(history.len() * 72);
+    fn head(cmd: &Cmd) -> String {
+        cmd.git.current_commit_hash().unwrap()
+    }
 
-        for i in 0..history.len() {
-            let Commit { hash, title } = history.get(i).expect("bounds have been checked");
+    fn slide(n: usize) -> String {
+        format!("{n:040}")
+    }
 
-            if i == n {
-                let _ = write!(out, "* ");
-            } else {
-                let _ = write!(out, "  ");
-            }
+    #[test]
+    fn next_and_previous() {
+        let mut cmd = presentation("next_and_previous", Fake::new(3), options());
 
-            let _ = writeln!(
-                out,
-                "{:>slide_number_padding$}/{} {COLOR_YELLOW}{}{COLOR_RESET} {title}",
-                i + 1,
-                history.len(),
-                &hash[..7],
-            );
-        }
+        assert_eq!(cmd.next(None), Ok(()));
+        assert_eq!(head(&cmd), slide(2));
+        assert_eq!(cmd.next(Some(5)), Ok(()));
+        assert_eq!(head(&cmd), slide(3));
+        assert_eq!(cmd.previous(Some(2)), Ok(()));
+        assert_eq!(head(&cmd), slide(1));
 
-        Pager::page_or_print(&out);
+        let slides: Vec<usize> = cmd
+            .get_store()
+            .transitions
+            .iter()
+            .map(|x| x.slide)
+            .collect();
+        assert_eq!(slides, [2, 3, 1]);
     }
 
-    fn ensure_presentation_is_started(&self) {
-        if !self.is_presentation_started() {
-            eprintln!(
-                "You need to start by '{} start'.",
-                env!("CARGO_BIN_NAME").replacen('-', " ", 1)
-            );
-            std::process::exit(1);
-        }
+    #[test]
+    fn next_past_the_end() {
+        let mut cmd = presentation("next_past_the_end", Fake::new(2), options());
+
+        assert_eq!(cmd.next(Some(2)), Ok(()));
+        assert_eq!(head(&cmd), slide(2));
+
+        cmd.options.strict = true;
+        assert_eq!(cmd.next(None), Err(Exit(EXIT_BOUNDARY)));
+        assert_eq!(cmd.previous(Some(2)), Err(Exit(EXIT_BOUNDARY)));
+        assert_eq!(head(&cmd), slide(1));
     }
 
-    pub fn is_presentation_started(&self) -> bool {
-        let store_file = self.store_file();
-        store_file.is_file()
+    #[test]
+    fn next_loops() {
+        let mut options = options();
+        options.loop_ = true;
+        let mut cmd = presentation("next_loops", Fake::new(3), options);
+
+        assert_eq!(cmd.previous(None), Ok(()));
+        assert_eq!(head(&cmd), slide(3));
+        assert_eq!(cmd.next(None), Ok(()));
+        assert_eq!(head(&cmd), slide(1));
     }
 
-    #[cfg(not(tarpaulin_include))] // Does not ignore 'else eprintln()'.
-    fn stash_uncommitted_changes() {
-        if !git::is_working_directory_clean() {
-            if git::stash() {
-                println!("Stashed uncommitted changes.");
-            } else {
-                eprintln!("error: Could not stash uncommitted changes.");
-            }
-        }
+    #[test]
+    fn go_out_of_bounds() {
+        let mut cmd = presentation("go_out_of_bounds", Fake::new(3), options());
+
+        assert_eq!(cmd.go(0), Err(Exit(1)));
+        assert_eq!(cmd.go(4), Err(Exit(1)));
+        assert_eq!(head(&cmd), slide(1));
+        assert!(cmd.get_store().transitions.is_empty());
+
+        assert_eq!(cmd.go(3), Ok(()));
+        assert_eq!(head(&cmd), slide(3));
     }
 
-    fn get_commits_hashes(&self) -> Vec<&String> {
-        let history = self.get_history();
-        history.iter().map(|x| &x.hash).collect()
+    #[test]
+    fn go_dry_run() {
+        let mut options = options();
+        options.dry_run = true;
+        let mut cmd = presentation("go_dry_run", Fake::new(3), options);
+
+        assert_eq!(cmd.go(2), Ok(()));
+        assert_eq!(head(&cmd), slide(1));
+        assert!(cmd.get_store().transitions.is_empty());
     }
 
-    fn get_history(&self) -> &Vec<Commit> {
-        // This function is expensive, and is called multiple times.
-        // Calling it multiple times simplifies the API a lot, so we
-        // cache the result instead of changing the API.
-        self.history.get_or_init(|| {
-            let hash = self.get_presentation_head_commit_hash();
-            git::history_up_to_commit(&hash)
-        })
+    #[test]
+    fn not_started() {
+        let git_dir = git_dir("not_started");
+        let mut cmd = Cmd::new(Box::new(Fake::new(3)), git_dir, options());
+
+        assert_eq!(cmd.next(None), Err(Exit(1)));
+        assert_eq!(cmd.previous(None), Err(Exit(1)));
+        assert_eq!(cmd.go(1), Err(Exit(1)));
+        assert_eq!(cmd.status(), Err(Exit(1)));
     }
 
-    fn get_presentation_head_commit_hash(&self) -> String {
-        // <branch name>:<commit hash>
-        self.read_store_file()
-            .trim()
-            .split_once(':')
-            .expect("':' is always inserted during 'start'")
-            .1
-            .to_string()
+    #[test]
+    fn status() {
+        let cmd = presentation("status", Fake::new(3), options());
+        assert_eq!(cmd.status(), Ok(()));
+
+        let git = Fake::new(3);
+        *git.head.borrow_mut() = String::from("deadbeef");
+        let cmd = presentation("status_outside", git, options());
+        assert_eq!(cmd.status(), Err(Exit(1)));
     }
 
-    fn get_initial_branch(&self) -> Option<String> {
-        // <branch name>:<commit hash>
-        let store = self.read_store_file();
-        let branch = store
-            .trim()
-            .split_once(':')
-            .expect("':' is always inserted during 'start'")
-            .0;
+    #[test]
+    fn head_outside_presentation() {
+        let git = Fake::new(3);
+        *git.head.borrow_mut() = String::from("deadbeef");
+        let mut cmd = presentation("head_outside_presentation", git, options());
 
-        if branch.is_empty() {
-            None
-        } else {
-            Some(branch.to_string())
-        }
+        assert_eq!(cmd.next(None), Err(Exit(1)));
+        assert_eq!(cmd.previous(None), Err(Exit(1)));
+        assert_eq!(head(&cmd), "deadbeef");
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn read_store_file(&self) -> String {
-        let store_file = self.store_file();
-        let Ok(store) = fs::read_to_string(store_file) else {
-            eprintln!("error: Cannot read '.git/{STORE_FILE}'. Aborting.");
-            std::process::exit(1);
+    #[test]
+    fn failed_checkout() {
+        let git = Fake {
+            checkout_fails: true,
+            ..Fake::new(3)
         };
-        store
+        let mut cmd = presentation("failed_checkout", git, options());
+
+        assert_eq!(cmd.next(None), Err(Exit(1)));
+        assert_eq!(cmd.go(3), Err(Exit(1)));
+        assert_eq!(head(&cmd), slide(1));
+        assert!(cmd.get_store().transitions.is_empty());
     }
 
-    fn store_file(&self) -> PathBuf {
-        self.git_dir.join(STORE_FILE)
+    #[test]
+    fn uncommitted_changes_are_stashed() {
+        let git = Fake::new(3);
+        git.dirty.set(true);
+        let mut cmd = presentation("uncommitted_changes_are_stashed", git, options());
+
+        assert_eq!(cmd.next(None), Ok(()));
+        assert_eq!(head(&cmd), slide(2));
+        assert!(cmd.git.is_working_directory_clean());
+        let stashes = &cmd.get_store().stashes;
+        assert_eq!(stashes.len(), 1);
+        assert_eq!(stashes[0].slide, Some(1));
     }
 
-    fn get_index_of_current_commit(&self) -> usize {
-        let Some(commit) = self.get_index_of_current_commit_checked() else {
-            eprintln!("error: Current HEAD not part of presentation.");
-            std::process::exit(1);
+    #[test]
+    fn failed_stash() {
+        let git = Fake {
+            stash_fails: true,
+            ..Fake::new(3)
         };
-        commit
+        git.dirty.set(true);
+        let mut cmd = presentation("failed_stash", git, options());
+
+        assert_eq!(cmd.next(None), Err(Exit(1)));
+        assert_eq!(head(&cmd), slide(1));
+        assert!(!cmd.git.is_working_directory_clean());
+        assert!(cmd.get_store().stashes.is_empty());
+        assert!(cmd.get_store().transitions.is_empty());
     }
 
-    // May return `None` if user checked out to non-presentation commit,
-    // or deleted commits.
-    fn get_index_of_current_commit_checked(&self) -> Option<usize> {
-        let hash = git::current_commit_hash()?;
+    #[test]
+    fn uncommitted_changes_block() {
+        let mut options = options();
+        options.dirty_action = DirtyAction::Block;
+        let git = Fake::new(3);
+        git.dirty.set(true);
+        let mut cmd = presentation("uncommitted_changes_block", git, options);
 
-        let hashes = self.get_commits_hashes();
-        hashes.into_iter().position(|x| *x == hash)
+        assert_eq!(cmd.go(2), Err(Exit(1)));
+        assert_eq!(head(&cmd), slide(1));
+        assert!(!cmd.git.is_working_directory_clean());
+    }
+
+    #[test]
+    fn stored_navigation() {
+        let mut cmd = presentation("stored_navigation", Fake::new(3), options());
+        assert_eq!(cmd.go(3), Ok(()));
+
+        let content = fs::read_to_string(cmd.store_file()).unwrap();
+        let (store, _) = Store::parse(&content).unwrap_or_else(|_| panic!("bad store"));
+        assert_eq!(store.transitions.len(), 1);
+        assert_eq!(store.transitions[0].hash, slide(3));
     }
 }
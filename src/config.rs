@@ -0,0 +1,91 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use crate::git;
+
+/// Git config section holding git-slides settings (e.g., `slides.loop`).
+pub const SECTION: &str = "slides";
+
+/// git-slides settings, read from Git config.
+///
+/// All `slides.*` entries are read at once, in a single call to Git,
+/// and looked up from memory afterwards.
+#[derive(Default)]
+pub struct Config {
+    values: HashMap<String, Vec<Option<String>>>,
+}
+
+impl Config {
+    #[must_use]
+    pub fn load() -> Self {
+        Self::parse(&git::config_entries(SECTION))
+    }
+
+    /// Parse `git config --null --get-regexp` output.
+    #[must_use]
+    pub fn parse(raw: &str) -> Self {
+        let mut values: HashMap<String, Vec<Option<String>>> = HashMap::new();
+
+        for record in raw.split('\0').filter(|record| !record.is_empty()) {
+            let (key, value) = match record.split_once('\n') {
+                Some((key, value)) => (key, Some(value.to_string())),
+                // `[slides] loop` (no `= value`) is an implicit `true`.
+                None => (record, None),
+            };
+            values.entry(key.to_lowercase()).or_default().push(value);
+        }
+
+        Self { values }
+    }
+
+    /// Get the value of `slides.<key>`.
+    ///
+    /// Keys are case-insensitive, like in Git (`recurseSubmodules` is
+    /// the same as `recursesubmodules`).
+    ///
+    /// If the key is set multiple times, the last value wins.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_all(key).pop()
+    }
+
+    /// Get all the values of a multi-valued `slides.<key>`.
+    #[must_use]
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        let key = format!("{SECTION}.{key}").to_lowercase();
+        let Some(values) = self.values.get(&key) else {
+            return Vec::new();
+        };
+        values
+            .iter()
+            .map(|value| value.as_deref().unwrap_or("true"))
+            .collect()
+    }
+
+    /// Get the value of `slides.<key>`, interpreted as a boolean.
+    ///
+    /// Returns `None` if the key is not set, or is not a valid boolean.
+    #[must_use]
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)?.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" | "" => Some(false),
+            _ => None,
+        }
+    }
+}
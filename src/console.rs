@@ -0,0 +1,148 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Terminal setup, for platforms that need it.
+
+/// Make the terminal interpret ANSI escape sequences (colors).
+///
+/// Always works on Unix. On Windows, legacy consoles print them
+/// literally unless virtual terminal processing is enabled, which this
+/// tries to do. Returns `false` if it is not supported.
+pub fn enable_ansi_sequences() -> bool {
+    #[cfg(windows)]
+    {
+        windows::enable_virtual_terminal_processing()
+    }
+    #[cfg(not(windows))]
+    {
+        true
+    }
+}
+
+/// Set the title of the terminal tab or window (OSC 0).
+///
+/// Control characters are dropped, they would end the sequence early.
+pub fn set_terminal_title(title: &str) {
+    use std::io::Write as _;
+
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    let mut stdout = std::io::stdout().lock();
+    let _ = write!(stdout, "\x1b]0;{title}\x07");
+    let _ = stdout.flush();
+}
+
+/// Ring the terminal bell (BEL).
+pub fn ring_bell() {
+    use std::io::Write as _;
+
+    let mut stdout = std::io::stdout().lock();
+    let _ = write!(stdout, "\x07");
+    let _ = stdout.flush();
+}
+
+/// Send a desktop notification, with `notify-send` on Linux and
+/// `osascript` on macOS.
+///
+/// Returns `false` if it could not be sent (e.g., no notification
+/// daemon, or unsupported platform).
+pub fn notify(title: &str, message: &str) -> bool {
+    use std::process::{Command, Stdio};
+
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {message:?} with title {title:?}"
+        ));
+        command
+    } else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(message);
+        command
+    } else {
+        return false;
+    };
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Ask a yes/no question on stderr, and read the answer from stdin.
+///
+/// Anything but `y` or `yes` is a no, including no answer at all
+/// (e.g., stdin is not a terminal and is empty).
+pub fn confirm(question: &str) -> bool {
+    use std::io::Write as _;
+
+    eprint!("{question} [y/N] ");
+    let _ = std::io::stderr().flush();
+
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Use `more` as pager on Windows, where `less` is rarely installed,
+/// unless `PAGER` says otherwise (`GIT_PAGER` and `core.pager` still
+/// take precedence, see [`crate::pager`]).
+pub fn set_default_pager() {
+    if cfg!(windows) && std::env::var_os("PAGER").is_none() {
+        std::env::set_var("PAGER", "more");
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::c_void;
+
+    type Handle = *mut c_void;
+
+    /// `(DWORD)-11`.
+    const STD_OUTPUT_HANDLE: u32 = 0xFFFF_FFF5;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> Handle;
+        fn GetConsoleMode(console_handle: Handle, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console_handle: Handle, mode: u32) -> i32;
+    }
+
+    pub fn enable_virtual_terminal_processing() -> bool {
+        // SAFETY: Plain Win32 calls on the process' own stdout handle,
+        // with a valid pointer to a local for the out-parameter.
+        unsafe {
+            let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+            // `INVALID_HANDLE_VALUE` is -1.
+            if handle.is_null() || handle as isize == -1 {
+                return false;
+            }
+            let mut mode = 0;
+            if GetConsoleMode(handle, &mut mode) == 0 {
+                return false;
+            }
+            if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+                return true;
+            }
+            SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        }
+    }
+}
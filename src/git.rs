@@ -14,41 +14,423 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::env;
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 
+use crate::log;
+
+/// Print Git commands to stderr, as they are run.
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Print every Git command run from now on to stderr, along with its
+/// exit status (e.g., `trace: git checkout <hash> (exit 0)`).
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Run commands like [`Command`] does, tracing them if verbose, and
+/// logging them if the log is open (see [`log`]).
+trait Traced {
+    fn traced_output(&mut self) -> io::Result<Output>;
+    fn traced_status(&mut self) -> io::Result<ExitStatus>;
+    /// Like `traced_output()`, writing `input` to stdin (which must be
+    /// piped).
+    fn traced_output_with_input(&mut self, input: &[u8]) -> io::Result<Output>;
+}
+
+impl Traced for Command {
+    fn traced_output(&mut self) -> io::Result<Output> {
+        let output = self.output();
+        trace(self, output.as_ref().ok());
+        output
+    }
+
+    fn traced_status(&mut self) -> io::Result<ExitStatus> {
+        let status = self.status();
+        let output = status.as_ref().ok().map(|status| Output {
+            status: *status,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        });
+        trace(self, output.as_ref());
+        status
+    }
+
+    fn traced_output_with_input(&mut self, input: &[u8]) -> io::Result<Output> {
+        let output = self.spawn().and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(input);
+            }
+            child.wait_with_output()
+        });
+        trace(self, output.as_ref().ok());
+        output
+    }
+}
+
+/// Trace and log a command, `output` being `None` if it could not run.
+fn trace(command: &Command, output: Option<&Output>) {
+    let verbose = VERBOSE.load(Ordering::Relaxed);
+    if !verbose && !log::is_open() {
+        return;
+    }
+    let mut line = command.get_program().to_string_lossy().into_owned();
+    for arg in command.get_args() {
+        let arg = arg.to_string_lossy();
+        line.push(' ');
+        if arg.is_empty() || arg.contains(char::is_whitespace) {
+            line.push('\'');
+            line.push_str(&arg);
+            line.push('\'');
+        } else {
+            line.push_str(&arg);
+        }
+    }
+    let status = match output {
+        Some(output) => match output.status.code() {
+            Some(code) => format!("exit {code}"),
+            None => String::from("killed"),
+        },
+        None => String::from("not run"),
+    };
+    let line = format!("{line} ({status})");
+
+    if verbose {
+        eprintln!("trace: {line}");
+    }
+    log::write("git", &line);
+    // Git explains failures on stderr, if it was captured.
+    if let Some(output) = output.filter(|output| !output.status.success()) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.trim().is_empty() {
+            log::write("git-stderr", &stderr);
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Commit {
     pub hash: String,
     pub title: String,
 }
 
+/// The Git operations navigating between slides relies on.
+///
+/// [`Subprocess`] runs Git, through the functions of this module.
+/// Other implementations can stand in for a repository (e.g., an
+/// in-memory fake, to test navigation without creating repositories,
+/// or to simulate failures).
+pub trait GitBackend {
+    /// See [`current_commit_hash()`].
+    fn current_commit_hash(&self) -> Option<String>;
+    /// See [`ref_to_commit_hash()`].
+    fn ref_to_commit_hash(&self, ref_: &str) -> Option<String>;
+    /// See [`commits()`].
+    fn commits(&self, hashes: &[String]) -> Vec<Commit>;
+    /// See [`history_up_to_commit()`].
+    fn history_up_to_commit(&self, commit: &str, options: &HistoryOptions) -> Vec<Commit>;
+    /// See [`is_working_directory_clean()`].
+    fn is_working_directory_clean(&self) -> bool;
+    /// See [`checkout()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with what Git printed, if it failed.
+    fn checkout(&self, commit: &str) -> Result<(), String>;
+    /// See [`stash()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with what Git printed, if it failed.
+    fn stash(&self, message: &str) -> Result<String, String>;
+    /// See [`stash_pop()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with what Git printed, if it failed.
+    fn stash_pop(&self, n: usize) -> Result<(), String>;
+    /// See [`abbrev_len()`].
+    fn abbrev_len(&self, hashes: &[&str], min: Option<usize>) -> Option<usize>;
+    /// See [`tags()`].
+    fn tags(&self) -> Vec<(String, String)>;
+    /// See [`branches()`].
+    fn branches(&self) -> Vec<(String, String)>;
+    /// See [`is_ancestor()`].
+    fn is_ancestor(&self, ancestor: &str, commit: &str) -> bool;
+    /// See [`trailer_values()`].
+    fn trailer_values(&self, commit: &str, key: &str) -> Vec<String>;
+    /// See [`note()`].
+    fn note(&self, notes_ref: &str, commit: &str) -> Option<String>;
+    /// See [`operation_in_progress()`].
+    fn operation_in_progress(&self) -> Option<Operation>;
+    /// See [`uncommitted_changes()`].
+    fn uncommitted_changes(&self) -> String;
+    /// See [`discard_changes()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with what Git printed, if it failed.
+    fn discard_changes(&self) -> Result<(), String>;
+    /// See [`stash_index()`].
+    fn stash_index(&self, hash: &str) -> Option<usize>;
+}
+
+/// [`GitBackend`] running Git in the current directory.
+pub struct Subprocess;
+
+impl GitBackend for Subprocess {
+    fn current_commit_hash(&self) -> Option<String> {
+        current_commit_hash()
+    }
+
+    fn ref_to_commit_hash(&self, ref_: &str) -> Option<String> {
+        ref_to_commit_hash(ref_)
+    }
+
+    fn commits(&self, hashes: &[String]) -> Vec<Commit> {
+        commits(hashes)
+    }
+
+    fn history_up_to_commit(&self, commit: &str, options: &HistoryOptions) -> Vec<Commit> {
+        history_up_to_commit(commit, options)
+    }
+
+    fn is_working_directory_clean(&self) -> bool {
+        is_working_directory_clean()
+    }
+
+    fn checkout(&self, commit: &str) -> Result<(), String> {
+        checkout(commit)
+    }
+
+    fn stash(&self, message: &str) -> Result<String, String> {
+        stash(message)
+    }
+
+    fn stash_pop(&self, n: usize) -> Result<(), String> {
+        stash_pop(n)
+    }
+
+    fn abbrev_len(&self, hashes: &[&str], min: Option<usize>) -> Option<usize> {
+        abbrev_len(hashes, min)
+    }
+
+    fn tags(&self) -> Vec<(String, String)> {
+        tags()
+    }
+
+    fn branches(&self) -> Vec<(String, String)> {
+        branches()
+    }
+
+    fn is_ancestor(&self, ancestor: &str, commit: &str) -> bool {
+        is_ancestor(ancestor, commit)
+    }
+
+    fn trailer_values(&self, commit: &str, key: &str) -> Vec<String> {
+        trailer_values(commit, key)
+    }
+
+    fn note(&self, notes_ref: &str, commit: &str) -> Option<String> {
+        note(notes_ref, commit)
+    }
+
+    fn operation_in_progress(&self) -> Option<Operation> {
+        operation_in_progress()
+    }
+
+    fn uncommitted_changes(&self) -> String {
+        uncommitted_changes()
+    }
+
+    fn discard_changes(&self) -> Result<(), String> {
+        discard_changes()
+    }
+
+    fn stash_index(&self, hash: &str) -> Option<usize> {
+        stash_index(hash)
+    }
+}
+
+/// Git version, as `(major, minor)`.
+#[must_use]
+pub fn version() -> Option<(u32, u32)> {
+    let output = Command::new("git").arg("--version").traced_output().ok()?;
+
+    // "git version 2.43.0" (possibly with a vendor suffix).
+    let output = String::from_utf8_lossy(&output.stdout);
+    let version = output.split_whitespace().nth(2)?;
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// The current repository, as seen by Git.
+pub struct Repository {
+    /// Absolute path to the Git directory.
+    pub git_dir: PathBuf,
+    /// Whether there is no working tree.
+    pub is_bare: bool,
+}
+
+/// Why the current repository could not be found.
+pub enum RepositoryError {
+    /// The `git` executable could not be run.
+    GitNotFound,
+    /// Not inside a Git repository.
+    NotARepository,
+}
+
+/// Find the current repository.
+///
+/// This asks Git instead of looking for `.git` ourselves, so that bare
+/// repositories, `GIT_DIR`/`GIT_WORK_TREE` and linked worktrees (where
+/// `.git` is a file) are all handled the same way Git handles them.
+///
+/// Everything is queried at once, to spare process spawns (they are
+/// expensive on Windows).
+///
+/// # Errors
+///
+/// Errors if Git is not in `PATH`, or if not inside a repository.
+pub fn repository() -> Result<Repository, RepositoryError> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--absolute-git-dir")
+        .arg("--is-bare-repository")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .map_err(|_| RepositoryError::GitNotFound)?;
+
+    if !output.status.success() {
+        return Err(RepositoryError::NotARepository);
+    }
+
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut lines = output.lines();
+    let git_dir = lines.next().ok_or(RepositoryError::NotARepository)?;
+    let is_bare = lines.next() == Some("true");
+
+    Ok(Repository {
+        git_dir: PathBuf::from(git_dir),
+        is_bare,
+    })
+}
+
+/// URL of `remote` (e.g., `origin`).
+#[must_use]
+pub fn remote_url(remote: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("remote")
+        .arg("get-url")
+        .arg("--")
+        .arg(remote)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Point `ref_` on `remote` at `commit`, whatever it pointed at.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
 #[must_use]
-pub fn is_git_in_path() -> bool {
-    Command::new("git")
-        .arg("--version")
+pub fn force_push(remote: &str, commit: &str, ref_: &str) -> bool {
+    let status = Command::new("git")
+        .arg("push")
+        .arg("--force")
+        .arg("--quiet")
+        .arg("--")
+        .arg(remote)
+        .arg(format!("{commit}:{ref_}"))
         .stdin(Stdio::null())
         .stdout(Stdio::null())
         .stderr(Stdio::null())
-        .status()
-        .is_ok()
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
 }
 
+/// Commit `ref_` points at on `remote`, without fetching it.
 #[must_use]
-pub fn find_git_directory() -> Option<PathBuf> {
-    let mut current_dir = env::current_dir().ok()?;
+pub fn remote_ref_hash(remote: &str, ref_: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("ls-remote")
+        .arg("--")
+        .arg(remote)
+        .arg(ref_)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
 
-    loop {
-        let git_dir = current_dir.join(".git");
-        if git_dir.is_dir() {
-            return Some(git_dir);
-        }
-        if !current_dir.pop() {
-            break;
-        }
+    if !output.status.success() {
+        return None;
     }
 
-    None
+    // `<hash>\t<ref>`.
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let (hash, name) = line.split_once('\t')?;
+            (name == ref_).then(|| hash.to_string())
+        })
+}
+
+/// Fetch `ref_` from `remote` into the same local ref.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn fetch_ref(remote: &str, ref_: &str) -> bool {
+    let status = Command::new("git")
+        .arg("fetch")
+        .arg("--quiet")
+        .arg("--")
+        .arg(remote)
+        .arg(format!("+{ref_}:{ref_}"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Root of the working tree.
+#[must_use]
+pub fn top_level() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--show-toplevel")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let top_level = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!top_level.is_empty()).then(|| PathBuf::from(top_level))
 }
 
 #[must_use]
@@ -58,7 +440,7 @@ pub fn current_commit_hash() -> Option<String> {
         .arg("--verify")
         .arg("--quiet")
         .arg("HEAD^{commit}")
-        .output();
+        .traced_output();
 
     if let Ok(output) = output {
         if output.status.success() {
@@ -77,7 +459,7 @@ pub fn current_branch() -> Option<String> {
         .arg("--short")
         .arg("--quiet")
         .arg("HEAD")
-        .output();
+        .traced_output();
 
     if let Ok(output) = output {
         if output.status.success() {
@@ -89,6 +471,34 @@ pub fn current_branch() -> Option<String> {
     None
 }
 
+/// Length of the shortest abbreviation that keeps each of `hashes`
+/// unambiguous, of at least `min` (default: `core.abbrev`).
+///
+/// Everything is checked in a single call to Git.
+#[must_use]
+pub fn abbrev_len(hashes: &[&str], min: Option<usize>) -> Option<usize> {
+    let mut command = Command::new("git");
+    command.arg("log").arg("--no-walk").arg("--format=%h");
+    if let Some(min) = min {
+        command.arg(format!("--abbrev={min}"));
+    }
+    let output = command
+        .args(hashes)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::len)
+        .max()
+}
+
 #[must_use]
 pub fn ref_to_commit_hash(ref_: &str) -> Option<String> {
     let output = Command::new("git")
@@ -97,7 +507,7 @@ pub fn ref_to_commit_hash(ref_: &str) -> Option<String> {
         .arg("--quiet")
         .arg("--end-of-options")
         .arg(format!("{ref_}^{{commit}}"))
-        .output();
+        .traced_output();
 
     if let Ok(output) = output {
         if output.status.success() {
@@ -109,30 +519,62 @@ pub fn ref_to_commit_hash(ref_: &str) -> Option<String> {
     None
 }
 
+/// Options to [`history_up_to_commit()`].
+#[derive(Default)]
+pub struct HistoryOptions<'a> {
+    /// Start at this commit (included) instead of at the root commit.
+    pub from: Option<&'a str>,
+    /// Follow all parents of merge commits, not only the first one.
+    pub all_parents: bool,
+    /// Only keep commits whose message matches this extended regex.
+    pub pattern: Option<&'a str>,
+    /// Leave out commits reachable from any of these.
+    pub exclude: &'a [String],
+}
+
+/// List commits reachable from `commit`, oldest first.
+///
+/// By default, merged branches are skipped, and only the merge commits
+/// themselves are listed. With `all_parents`, the full history is
+/// listed, in topological order.
 #[cfg(not(tarpaulin_include))] // Does not ignore '(return) Vec::new()'.
 #[must_use]
-pub fn history_up_to_commit(commit: &str) -> Vec<Commit> {
-    let output = Command::new("git")
+pub fn history_up_to_commit(commit: &str, options: &HistoryOptions) -> Vec<Commit> {
+    let mut command = Command::new("git");
+    command
         .arg("rev-list")
-        .arg("--first-parent")
+        .arg(if options.all_parents {
+            "--topo-order"
+        } else {
+            "--first-parent"
+        })
         .arg("--format=%H %s")
+        .arg("--encoding=UTF-8")
         .arg("--no-commit-header")
-        .arg("--reverse")
-        .arg(commit)
-        .output();
+        .arg("--reverse");
+
+    if let Some(pattern) = options.pattern {
+        command
+            .arg("--extended-regexp")
+            .arg(format!("--grep={pattern}"));
+    }
+
+    command.arg(commit);
+
+    if options.from.is_some() || !options.exclude.is_empty() {
+        command.arg("--not");
+    }
+    if let Some(from) = options.from {
+        // Exclude the parents of `from`, and thus everything before it.
+        command.arg(format!("{from}^@"));
+    }
+    command.args(options.exclude);
+
+    let output = command.traced_output();
 
     if let Ok(output) = output {
         if output.status.success() {
-            let commits: Vec<Commit> = String::from_utf8_lossy(&output.stdout)
-                .lines()
-                .filter_map(|line| {
-                    let pieces = line.split_once(' ')?;
-                    let hash = String::from(pieces.0);
-                    let title = String::from(pieces.1);
-                    Some(Commit { hash, title })
-                })
-                .collect();
-            return commits;
+            return parse_commits(&output.stdout);
         }
     }
 
@@ -140,49 +582,1135 @@ pub fn history_up_to_commit(commit: &str) -> Vec<Commit> {
     Vec::new()
 }
 
-#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+/// Parse `--format=%H %s` output.
+fn parse_commits(output: &[u8]) -> Vec<Commit> {
+    decode_messages(output)
+        .lines()
+        .filter_map(|line| {
+            let pieces = line.split_once(' ')?;
+            let hash = String::from(pieces.0);
+            let title = String::from(pieces.1);
+            Some(Commit { hash, title })
+        })
+        .collect()
+}
+
+/// Encoding to assume for commit messages that are not valid UTF-8.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LegacyEncoding {
+    Latin1,
+    Windows1252,
+}
+
+/// Characters for bytes `0x80..=0x9F` in Windows-1252 (the rest is
+/// the same as Latin-1). Undefined bytes map to themselves.
+const WINDOWS_1252: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž', '\u{8f}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}', 'ž', 'Ÿ',
+];
+
+/// Follow `i18n.commitEncoding` if it says Latin-1, else assume
+/// Windows-1252 (a superset of Latin-1, and the most likely culprit).
+fn legacy_encoding() -> LegacyEncoding {
+    static ENCODING: OnceLock<LegacyEncoding> = OnceLock::new();
+    *ENCODING.get_or_init(|| {
+        let output = Command::new("git")
+            .arg("config")
+            .arg("i18n.commitEncoding")
+            .stderr(Stdio::null())
+            .traced_output();
+        let encoding = match output {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .to_lowercase(),
+            Err(_) => String::new(),
+        };
+        match encoding.as_str() {
+            "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "latin-1" | "l1" => {
+                LegacyEncoding::Latin1
+            }
+            _ => LegacyEncoding::Windows1252,
+        }
+    })
+}
+
+/// Decode commit messages output by Git with `--encoding=UTF-8`.
+///
+/// Git converts messages that declare their encoding (the `encoding`
+/// header, written when `i18n.commitEncoding` is set). The others are
+/// output as is if they are valid UTF-8, else decoded as Latin-1. That
+/// turns Windows-1252 punctuation (e.g., curly quotes) into invisible
+/// control characters, which we map back unless the repository says
+/// Latin-1 is what was meant.
+fn decode_messages(output: &[u8]) -> String {
+    let output = String::from_utf8_lossy(output);
+    let is_c1 = |c: char| ('\u{80}'..='\u{9f}').contains(&c);
+    if !output.contains(is_c1) || legacy_encoding() == LegacyEncoding::Latin1 {
+        return output.into_owned();
+    }
+    output
+        .chars()
+        .map(|c| {
+            if is_c1(c) {
+                WINDOWS_1252[c as usize - 0x80]
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Get hash and title of each of `hashes`, in the given order.
+#[cfg(not(tarpaulin_include))] // Does not ignore '(return) Vec::new()'.
 #[must_use]
-pub fn checkout(commit: &str) -> bool {
-    let status = Command::new("git")
-        .arg("checkout")
-        .arg(commit)
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status();
+pub fn commits(hashes: &[String]) -> Vec<Commit> {
+    if hashes.is_empty() {
+        // Git would default to HEAD.
+        return Vec::new();
+    }
 
-    let Ok(status) = status else {
-        return false;
-    };
+    let output = Command::new("git")
+        .arg("log")
+        .arg("--no-walk=unsorted")
+        .arg("--format=%H %s")
+        .arg("--encoding=UTF-8")
+        .arg("--end-of-options")
+        .args(hashes)
+        .traced_output();
 
-    status.success()
+    if let Ok(output) = output {
+        if output.status.success() {
+            return parse_commits(&output.stdout);
+        }
+    }
+
+    Vec::new()
 }
 
-#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+/// Those of `hashes` that are not commits in the repository (e.g.,
+/// pruned after a rewrite), in the given order.
+///
+/// Everything is checked in a single call to Git. If Git cannot be
+/// run, nothing is reported missing.
+#[cfg(not(tarpaulin_include))] // Does not ignore '(return) Vec::new()'.
 #[must_use]
-pub fn is_working_directory_clean() -> bool {
+pub fn missing_commits(hashes: &[&str]) -> Vec<String> {
+    let mut input = hashes.join("\n");
+    input.push('\n');
+
     let output = Command::new("git")
-        .arg("status")
-        .arg("--untracked-files=no")
-        .arg("--porcelain")
-        .output();
+        .arg("cat-file")
+        .arg("--batch-check=%(objecttype)")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .traced_output_with_input(input.as_bytes());
 
     let Ok(output) = output else {
-        return false;
+        return Vec::new();
     };
+    if !output.status.success() {
+        return Vec::new();
+    }
 
-    String::from_utf8_lossy(&output.stdout).trim().is_empty()
+    // One line per input: the type, or `<input> missing`.
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .zip(hashes)
+        .filter(|(line, _)| *line != "commit")
+        .map(|(_, hash)| (*hash).to_string())
+        .collect()
 }
 
-#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+/// Files matching `pattern` (a regex) in the tree of each of `hashes`,
+/// as `(hash, path)` pairs.
+///
+/// All trees are searched in a single call to Git.
+///
+/// # Errors
+///
+/// Errors with Git's message if the search fails (e.g., bad regex).
+pub fn grep(
+    pattern: &str,
+    hashes: &[&str],
+    ignore_case: bool,
+) -> Result<Vec<(String, String)>, String> {
+    let mut command = Command::new("git");
+    command.arg("grep").arg("--files-with-matches");
+    if ignore_case {
+        command.arg("--ignore-case");
+    }
+    let output = command
+        .arg("-e")
+        .arg(pattern)
+        .args(hashes)
+        .arg("--")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .traced_output()
+        .map_err(|error| error.to_string())?;
+
+    // Exits with 1 if nothing matches, which is not a failure.
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        return Err(String::from_utf8_lossy(&output.stderr)
+            .trim_end()
+            .to_string());
+    }
+
+    // One line per file: `<hash>:<path>`.
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(hash, path)| (hash.to_string(), path.to_string()))
+        .collect())
+}
+
+/// Patch introduced by `commit`, without colors.
 #[must_use]
-pub fn stash() -> bool {
-    let status = Command::new("git")
-        .arg("stash")
+pub fn diff(commit: &str) -> String {
+    let output = Command::new("git")
+        .arg("show")
+        .arg("--format=")
+        .arg("--patch")
+        .arg("--no-color")
+        .arg("--no-ext-diff")
+        .arg("--end-of-options")
+        .arg(commit)
         .stdin(Stdio::null())
-        .stdout(Stdio::null())
         .stderr(Stdio::null())
-        .status();
+        .traced_output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Size of a diff, as in `git diff --shortstat`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiffStat {
+    pub files: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Size of the diff of each `(from, to)` pair, in the given order.
+///
+/// Without `from`, `to` is compared to its first parent (or to nothing,
+/// for a root commit).
+///
+/// Everything is diffed in a single call to Git.
+#[cfg(not(tarpaulin_include))] // Does not ignore '(return) Vec::new()'.
+#[must_use]
+pub fn diff_stats(pairs: &[(Option<&str>, &str)]) -> Vec<DiffStat> {
+    let input: String = pairs
+        .iter()
+        .map(|(from, to)| match from {
+            // `--stdin` reads `<commit> <parent>`.
+            Some(from) => format!("{to} {from}\n"),
+            None => format!("{to}\n"),
+        })
+        .collect();
+
+    let output = Command::new("git")
+        .arg("diff-tree")
+        .arg("--stdin")
+        .arg("-r")
+        .arg("--root")
+        .arg("--always")
+        .arg("--shortstat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .traced_output_with_input(input.as_bytes());
+
+    match output {
+        Ok(output) if output.status.success() => parse_diff_stats(&output.stdout),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse `diff-tree --always --shortstat` output: a header line per
+/// diff, optionally followed by ` 1 file changed, 2 insertions(+)`.
+fn parse_diff_stats(output: &[u8]) -> Vec<DiffStat> {
+    let output = String::from_utf8_lossy(output);
+    let mut stats: Vec<DiffStat> = Vec::new();
+
+    for line in output.lines() {
+        if line.is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            stats.push(DiffStat::default());
+            continue;
+        }
+        let Some(stat) = stats.last_mut() else {
+            continue;
+        };
+        for part in line.split(',') {
+            let mut words = part.split_whitespace();
+            let (Some(count), Some(kind)) = (words.next(), words.next()) else {
+                continue;
+            };
+            let count = count.parse().unwrap_or(0);
+            if kind.starts_with("file") {
+                stat.files = count;
+            } else if kind.starts_with("insertion") {
+                stat.insertions = count;
+            } else if kind.starts_with("deletion") {
+                stat.deletions = count;
+            }
+        }
+    }
+
+    stats
+}
+
+/// Authorship, body and trailers of a commit.
+#[derive(Debug, Default)]
+pub struct CommitDetails {
+    /// Author name.
+    pub author: String,
+    /// Relative commit date (e.g., `2 days ago`).
+    pub date: String,
+    /// Message without title and trailers.
+    pub body: String,
+    /// `(key, value)` pairs, in order.
+    pub trailers: Vec<(String, String)>,
+}
+
+/// Get details of each of `hashes`, in the given order.
+#[cfg(not(tarpaulin_include))] // Does not ignore '(return) Vec::new()'.
+#[must_use]
+pub fn commits_details(hashes: &[String]) -> Vec<CommitDetails> {
+    if hashes.is_empty() {
+        // Git would default to HEAD.
+        return Vec::new();
+    }
+
+    let output = Command::new("git")
+        .arg("log")
+        .arg("--no-walk=unsorted")
+        .arg("--format=%an%x00%cr%x00%b%x00%(trailers:only,unfold)%x00")
+        .arg("--encoding=UTF-8")
+        .arg("--end-of-options")
+        .args(hashes)
+        .traced_output();
+
+    if let Ok(output) = output {
+        if output.status.success() {
+            return parse_commits_details(&output.stdout);
+        }
+    }
+
+    Vec::new()
+}
+
+/// Parse `--format=%an%x00%cr%x00%b%x00%(trailers:only,unfold)%x00` output.
+fn parse_commits_details(output: &[u8]) -> Vec<CommitDetails> {
+    let output = decode_messages(output);
+    let mut fields = output.split('\0');
+    let mut details = Vec::new();
+
+    while let (Some(author), Some(date), Some(body), Some(trailers)) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    {
+        // Entries are separated by a newline.
+        let author = author.strip_prefix('\n').unwrap_or(author).to_string();
+        let date = date.to_string();
+
+        let trailers: Vec<(String, String)> = trailers
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+
+        // The body includes the trailers, remove them.
+        let body = body
+            .lines()
+            .filter(|line| {
+                !line.split_once(':').is_some_and(|(key, value)| {
+                    trailers
+                        .iter()
+                        .any(|(k, v)| k == key.trim() && v == value.trim())
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+
+        details.push(CommitDetails {
+            author,
+            date,
+            body,
+            trailers,
+        });
+    }
+
+    details
+}
+
+/// List commit hashes of tags matching `pattern`, sorted by `sort`.
+///
+/// `sort` is any `git tag --sort` key (e.g., `version:refname`).
+#[must_use]
+pub fn tags_matching(pattern: &str, sort: &str) -> Vec<String> {
+    let output = Command::new("git")
+        .arg("tag")
+        .arg("--list")
+        .arg(format!("--sort={sort}"))
+        // Annotated tags point to a tag object, peel it to the commit.
+        .arg("--format=%(if)%(*objectname)%(then)%(*objectname)%(else)%(objectname)%(end)")
+        .arg(pattern)
+        .traced_output();
+
+    if let Ok(output) = output {
+        if output.status.success() {
+            return String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect();
+        }
+    }
+
+    Vec::new()
+}
+
+/// Tags, as `(name, commit hash)`, sorted by name.
+#[must_use]
+pub fn tags() -> Vec<(String, String)> {
+    let output = Command::new("git")
+        .arg("for-each-ref")
+        // Annotated tags point to a tag object, peel it to the commit.
+        .arg("--format=%(if)%(*objectname)%(then)%(*objectname)%(else)%(objectname)%(end) %(refname:short)")
+        .arg("refs/tags/")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    if let Ok(output) = output {
+        if output.status.success() {
+            return String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .map(|(hash, name)| (name.to_string(), hash.to_string()))
+                .collect();
+        }
+    }
+
+    Vec::new()
+}
+
+/// Local branches, as `(name, commit hash)`, sorted by name.
+#[must_use]
+pub fn branches() -> Vec<(String, String)> {
+    let output = Command::new("git")
+        .arg("for-each-ref")
+        .arg("--format=%(objectname) %(refname:short)")
+        .arg("refs/heads/")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    if let Ok(output) = output {
+        if output.status.success() {
+            return String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .map(|(hash, name)| (name.to_string(), hash.to_string()))
+                .collect();
+        }
+    }
+
+    Vec::new()
+}
+
+#[must_use]
+pub fn is_ancestor(ancestor: &str, commit: &str) -> bool {
+    let status = Command::new("git")
+        .arg("merge-base")
+        .arg("--is-ancestor")
+        .arg(ancestor)
+        .arg(commit)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Check out `commit` (or branch).
+///
+/// # Errors
+///
+/// Errors with what Git printed, if it failed (e.g., local changes
+/// would be overwritten).
+pub fn checkout(commit: &str) -> Result<(), String> {
+    run_reporting_errors(Command::new("git").arg("checkout").arg(commit))
+}
+
+/// Values of the `key` trailer of `commit` (e.g., `Slide-Exec`).
+#[must_use]
+pub fn trailer_values(commit: &str, key: &str) -> Vec<String> {
+    let output = Command::new("git")
+        .arg("log")
+        .arg("-1")
+        .arg(format!("--format=%(trailers:key={key},valueonly,unfold)"))
+        .arg(commit)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Note attached to `commit`, in `refs/notes/<notes_ref>`.
+#[must_use]
+pub fn note(notes_ref: &str, commit: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("notes")
+        .arg(format!("--ref={notes_ref}"))
+        .arg("show")
+        .arg(commit)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let note = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string();
+    (!note.is_empty()).then_some(note)
+}
+
+/// Edit the note attached to `commit`, in `refs/notes/<notes_ref>`.
+///
+/// Git opens the editor, it needs the terminal.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn edit_note(notes_ref: &str, commit: &str) -> bool {
+    let status = Command::new("git")
+        .arg("notes")
+        .arg(format!("--ref={notes_ref}"))
+        .arg("edit")
+        .arg(commit)
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Append `message` to the note attached to `commit`, in
+/// `refs/notes/<notes_ref>` (creating it if needed).
+#[must_use]
+pub fn append_note(notes_ref: &str, commit: &str, message: &str) -> bool {
+    let status = Command::new("git")
+        .arg("notes")
+        .arg(format!("--ref={notes_ref}"))
+        .arg("append")
+        .arg("--message")
+        .arg(message)
+        .arg(commit)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    status.is_ok_and(|status| status.success())
+}
+
+/// Commit staged changes (or nothing) on top of HEAD.
+///
+/// Without `message`, the commit message is asked for in an editor
+/// (or kept as is, with `amend`).
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn commit(message: Option<&str>, amend: bool) -> bool {
+    let mut command = Command::new("git");
+    command.arg("commit").arg("--allow-empty").arg("--quiet");
+
+    if amend {
+        command.arg("--amend");
+    }
+
+    match message {
+        Some(message) => {
+            command.arg("--message").arg(message);
+        }
+        None if amend => {
+            command.arg("--no-edit");
+        }
+        // Let Git open the editor, it needs the terminal.
+        None => (),
+    }
+
+    let Ok(status) = command.traced_status() else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Replay commits of `upstream..branch` on top of `onto`.
+///
+/// Merge commits are preserved. If the rebase fails (e.g., conflicts),
+/// it is aborted and HEAD is left where it was.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn rebase_onto(onto: &str, upstream: &str, branch: &str) -> bool {
+    let status = Command::new("git")
+        .arg("rebase")
+        .arg("--rebase-merges")
+        .arg("--autostash")
+        .arg("--onto")
+        .arg(onto)
+        .arg(upstream)
+        .arg(branch)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    if matches!(status, Ok(status) if status.success()) {
+        return true;
+    }
+
+    let _ = Command::new("git")
+        .arg("rebase")
+        .arg("--abort")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    false
+}
+
+/// Point `branch` to `new`, only if it still points to `old`.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn update_branch(branch: &str, new: &str, old: &str) -> bool {
+    let status = Command::new("git")
+        .arg("update-ref")
+        .arg(format!("refs/heads/{branch}"))
+        .arg(new)
+        .arg(old)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn is_working_directory_clean() -> bool {
+    let output = Command::new("git")
+        .arg("status")
+        .arg("--untracked-files=no")
+        .arg("--porcelain")
+        .traced_output();
+
+    let Ok(output) = output else {
+        return false;
+    };
+
+    String::from_utf8_lossy(&output.stdout).trim().is_empty()
+}
+
+/// Operation that must be finished (or aborted) before checking out
+/// anything else.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Rebase,
+    Merge,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+impl Operation {
+    /// Git command the operation belongs to (e.g., `cherry-pick`).
+    #[must_use]
+    pub fn command(self) -> &'static str {
+        match self {
+            Self::Rebase => "rebase",
+            Self::Merge => "merge",
+            Self::CherryPick => "cherry-pick",
+            Self::Revert => "revert",
+            Self::Bisect => "bisect",
+        }
+    }
+}
+
+/// Operation in progress in the working tree, if any, from the files
+/// Git keeps in the meantime.
+///
+/// Paths are resolved by Git, so linked worktrees are handled.
+#[must_use]
+pub fn operation_in_progress() -> Option<Operation> {
+    const STATE_FILES: [(&str, Operation); 6] = [
+        ("rebase-merge", Operation::Rebase),
+        ("rebase-apply", Operation::Rebase),
+        ("MERGE_HEAD", Operation::Merge),
+        ("CHERRY_PICK_HEAD", Operation::CherryPick),
+        ("REVERT_HEAD", Operation::Revert),
+        ("BISECT_LOG", Operation::Bisect),
+    ];
+
+    let mut command = Command::new("git");
+    command.arg("rev-parse");
+    for (file, _) in STATE_FILES {
+        command.arg("--git-path").arg(file);
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .zip(STATE_FILES)
+        .find(|(path, _)| Path::new(path).exists())
+        .map(|(_, (_, operation))| operation)
+}
+
+/// Uncommitted changes to tracked files, as in `git status --short`.
+#[must_use]
+pub fn uncommitted_changes() -> String {
+    let output = Command::new("git")
+        .arg("-c")
+        .arg("color.status=false")
+        .arg("status")
+        .arg("--untracked-files=no")
+        .arg("--short")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Throw away uncommitted changes to tracked files (staged or not).
+///
+/// # Errors
+///
+/// Errors with what Git printed, if it failed.
+pub fn discard_changes() -> Result<(), String> {
+    run_reporting_errors(
+        Command::new("git")
+            .arg("reset")
+            .arg("--hard")
+            .arg("--quiet"),
+    )
+}
+
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn has_staged_changes() -> bool {
+    let status = Command::new("git")
+        .arg("diff")
+        .arg("--cached")
+        .arg("--quiet")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    // Exits with 1 if there are differences.
+    status.code() == Some(1)
+}
+
+/// Stage all changes to tracked files (`git add --update`).
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn stage_tracked_changes() -> bool {
+    let status = Command::new("git")
+        .arg("add")
+        .arg("--update")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Number of entries in the stash.
+#[must_use]
+pub fn stash_count() -> usize {
+    let output = Command::new("git")
+        .arg("stash")
+        .arg("list")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    let Ok(output) = output else {
+        return 0;
+    };
+
+    String::from_utf8_lossy(&output.stdout).lines().count()
+}
+
+/// Stash uncommitted changes with `message`, and return the stash
+/// commit.
+///
+/// # Errors
+///
+/// Errors with what Git printed, if it failed.
+pub fn stash(message: &str) -> Result<String, String> {
+    run_reporting_errors(
+        Command::new("git")
+            .arg("stash")
+            .arg("push")
+            .arg("--message")
+            .arg(message),
+    )?;
+
+    ref_to_commit_hash("refs/stash").ok_or_else(String::new)
+}
+
+/// Position of stash commit `hash` in the stash list (`stash@{n}`),
+/// if it is still there.
+#[must_use]
+pub fn stash_index(hash: &str) -> Option<usize> {
+    let output = Command::new("git")
+        .arg("stash")
+        .arg("list")
+        .arg("--format=%H")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .position(|x| x == hash)
+}
+
+/// Apply `stash@{n}`, and drop it from the stash list.
+///
+/// # Errors
+///
+/// Errors with what Git printed, if it failed (e.g., on conflicts).
+pub fn stash_pop(n: usize) -> Result<(), String> {
+    run_reporting_errors(
+        Command::new("git")
+            .arg("stash")
+            .arg("pop")
+            .arg("--index")
+            .arg("--quiet")
+            .arg(format!("stash@{{{n}}}")),
+    )
+}
+
+/// Run a Git command quietly, but keep its stderr, to tell why it
+/// failed.
+fn run_reporting_errors(command: &mut Command) -> Result<(), String> {
+    let output = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .traced_output();
+
+    match output {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(String::from_utf8_lossy(&output.stderr)
+            .trim_end()
+            .to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Convert a Git color value (e.g., `bold yellow`) to an ANSI sequence.
+///
+/// Returns `None` if Git does not understand the value.
+#[must_use]
+pub fn parse_color(value: &str) -> Option<String> {
+    // `--get-color` only reads from config, so pass the value as
+    // a one-shot config entry.
+    let output = Command::new("git")
+        .arg("-c")
+        .arg(format!("slides.parsecolor={value}"))
+        .arg("config")
+        .arg("--get-color")
+        .arg("slides.parsecolor")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    let Ok(output) = output else {
+        return None;
+    };
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Pager command, as Git would pick it (`GIT_PAGER`, `core.pager`,
+/// `PAGER`, then Git's default).
+#[must_use]
+pub fn pager() -> Option<String> {
+    let output = Command::new("git")
+        .arg("var")
+        .arg("GIT_PAGER")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    if let Ok(output) = output {
+        if output.status.success() {
+            return Some(String::from_utf8_lossy(&output.stdout).trim().to_owned());
+        }
+    }
+
+    None
+}
+
+/// Read all config entries whose key starts with `prefix`.
+///
+/// Output is raw `git config --null --get-regexp` output, that is,
+/// `key\nvalue\0` records (or `key\0` for keys without a value).
+#[must_use]
+pub fn config_entries(prefix: &str) -> String {
+    let output = Command::new("git")
+        .arg("config")
+        .arg("--null")
+        .arg("--get-regexp")
+        .arg(format!("^{}\\.", prefix.replace('.', "\\.")))
+        .stderr(Stdio::null())
+        .traced_output();
+
+    let Ok(output) = output else {
+        return String::new();
+    };
+
+    // Exits with 1 if nothing matches, which isn't an error for us.
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Create a linked worktree at `path`, detached at `commit`.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn add_worktree(path: &Path, commit: &str) -> bool {
+    let status = Command::new("git")
+        .arg("worktree")
+        .arg("add")
+        .arg("--detach")
+        .arg("--quiet")
+        .arg(path)
+        .arg(commit)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Remove the linked worktree at `path`, even if it has changes.
+///
+/// Run against the main repository, whatever `GIT_DIR` says.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn remove_worktree(git_dir: &Path, path: &Path) -> bool {
+    let status = Command::new("git")
+        .arg("--git-dir")
+        .arg(git_dir)
+        .arg("worktree")
+        .arg("remove")
+        .arg("--force")
+        .arg(path)
+        .env_remove("GIT_WORK_TREE")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Git directory of the linked worktree at `path`, read from its `.git`
+/// file (`gitdir: <path>`).
+#[must_use]
+pub fn worktree_git_dir(path: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(path.join(".git")).ok()?;
+    let git_dir = content.strip_prefix("gitdir:")?.trim();
+    // Relative to the worktree, with `worktree.useRelativePaths`.
+    Some(path.join(git_dir))
+}
+
+/// Whether the repository is a shallow clone (its history is cut).
+#[must_use]
+pub fn is_shallow() -> bool {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--is-shallow-repository")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_output();
+
+    let Ok(output) = output else {
+        return false;
+    };
+
+    output.status.success() && output.stdout.trim_ascii() == b"true"
+}
+
+/// Fetch the missing history of a shallow clone.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn unshallow() -> bool {
+    let status = Command::new("git")
+        .arg("fetch")
+        .arg("--unshallow")
+        .arg("--quiet")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn update_submodules() -> bool {
+    let status = Command::new("git")
+        .arg("submodule")
+        .arg("update")
+        .arg("--init")
+        .arg("--recursive")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn lfs_checkout() -> bool {
+    lfs("checkout")
+}
+
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn lfs_pull() -> bool {
+    lfs("pull")
+}
+
+fn lfs(command: &str) -> bool {
+    let status = Command::new("git")
+        .arg("lfs")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .traced_status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
+
+/// Open `file` in the user's Git editor, and wait for it to close.
+///
+/// The editor is resolved by Git (`GIT_EDITOR`, `core.editor`,
+/// `VISUAL`, `EDITOR`), and run through the shell, like Git does.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn edit(file: &Path) -> bool {
+    let Ok(output) = Command::new("git")
+        .arg("var")
+        .arg("GIT_EDITOR")
+        .stderr(Stdio::null())
+        .traced_output()
+    else {
+        return false;
+    };
+    let editor = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    if editor.is_empty() {
+        return false;
+    }
+
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$@\""))
+        .arg(&editor)
+        .arg(file)
+        .traced_status();
 
     let Ok(status) = status else {
         return false;
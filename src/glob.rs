@@ -0,0 +1,71 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal shell-like pattern matching.
+//!
+//! Supported wildcards are `*` (any sequence of characters, possibly
+//! empty) and `?` (exactly one character). Everything else matches
+//! literally.
+
+/// Remove the shortest prefix of `text` matching `pattern`.
+///
+/// Returns `None` if `text` doesn't start with `pattern`.
+///
+/// # Examples
+///
+/// ```
+/// use git_slides::glob;
+///
+/// let title = glob::strip_prefix("slides: * – ", "slides: 03 – Closures");
+/// assert_eq!(title, Some("Closures"));
+///
+/// assert_eq!(glob::strip_prefix("slides: ", "Closures"), None);
+/// ```
+#[must_use]
+pub fn strip_prefix<'a>(pattern: &str, text: &'a str) -> Option<&'a str> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let len = match_prefix(&pattern, text)?;
+    Some(&text[len..])
+}
+
+/// Match `pattern` against the start of `text`.
+///
+/// Returns the length (in bytes) of the shortest matching prefix.
+fn match_prefix(pattern: &[char], text: &str) -> Option<usize> {
+    match pattern.split_first() {
+        None => Some(0),
+        Some(('*', rest)) => {
+            // Try the shortest expansion first.
+            let boundaries = text.char_indices().map(|(i, _)| i).chain([text.len()]);
+            for i in boundaries {
+                if let Some(len) = match_prefix(rest, &text[i..]) {
+                    return Some(i + len);
+                }
+            }
+            None
+        }
+        Some(('?', rest)) => {
+            let c = text.chars().next()?;
+            let len = match_prefix(rest, &text[c.len_utf8()..])?;
+            Some(c.len_utf8() + len)
+        }
+        Some((c, rest)) => {
+            let text = text.strip_prefix(*c)?;
+            let len = match_prefix(rest, text)?;
+            Some(c.len_utf8() + len)
+        }
+    }
+}
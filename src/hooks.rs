@@ -0,0 +1,68 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! User hooks, in `.git/slides-hooks/`.
+//!
+//! Like Git hooks, they are executable files named after the event
+//! they react to:
+//!
+//! - `pre-start`: Before the presentation starts. Exiting non-zero
+//!   aborts the start.
+//! - `post-slide`: After each slide change.
+//! - `pre-stop`: Before the presentation stops. Exiting non-zero aborts
+//!   the stop.
+//!
+//! Slide metadata is passed through `GIT_SLIDES_*` environment
+//! variables, and hooks run from the root of the working tree.
+
+use std::path::Path;
+use std::process::Command;
+
+use git_slides::git;
+
+/// Directory holding the hooks, in the Git directory.
+pub const DIR: &str = "slides-hooks";
+
+/// Run hook `name`, if there is one.
+///
+/// Returns `None` if there is no such hook, or whether it succeeded.
+pub fn run(git_dir: &Path, name: &str, env: &[(&str, String)]) -> Option<bool> {
+    let hook = git_dir.join(DIR).join(name);
+    if !is_executable(&hook) {
+        return None;
+    }
+
+    let mut command = Command::new(&hook);
+    command.envs(env.iter().map(|(var, value)| (var, value)));
+    if let Some(top_level) = git::top_level() {
+        command.current_dir(top_level);
+    }
+
+    Some(matches!(command.status(), Ok(status) if status.success()))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,201 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HTML rendering of slides, for `web` and `export --html`.
+
+use std::fmt::Write as _;
+
+/// A slide, as shown in a browser.
+pub struct Slide {
+    /// Slide number, 1-indexed (`None` if not part of the presentation).
+    pub number: Option<usize>,
+    pub total: usize,
+    /// Abbreviated commit hash, as shown in the terminal.
+    pub short_hash: String,
+    pub title: String,
+    /// Message without title and trailers.
+    pub body: String,
+    /// Patch introduced by the slide.
+    pub diff: String,
+    /// Speaker notes.
+    pub notes: Option<String>,
+    /// Notes taken during the presentation (see `annotate`).
+    pub annotations: Option<String>,
+    /// Links to the previous and next slides, and to the index.
+    pub links: Option<Links>,
+}
+
+/// Hrefs of pages around a slide.
+pub struct Links {
+    pub previous: Option<String>,
+    pub next: Option<String>,
+    pub index: String,
+}
+
+const STYLE: &str = "\
+body { margin: 0 auto; max-width: 72rem; padding: 2rem; \
+background: #1e1e1e; color: #d4d4d4; font-family: system-ui, sans-serif; }
+header { color: #858585; }
+.hash { color: #d7ba7d; font-family: monospace; }
+h1 { font-size: 2.2rem; margin: 0.5rem 0 1.5rem; }
+.body { font-size: 1.2rem; line-height: 1.5; white-space: pre-wrap; }
+pre { background: #252526; border-radius: 0.5rem; font-size: 1rem; \
+overflow-x: auto; padding: 1rem; }
+.add { color: #6a9955; }
+.del { color: #f44747; }
+.hunk { color: #569cd6; }
+.meta { color: #858585; font-weight: bold; }
+.notes { border-left: 0.25rem solid #569cd6; padding-left: 1rem; white-space: pre-wrap; }
+.annotations { border-left: 0.25rem solid #d7ba7d; padding-left: 1rem; white-space: pre-wrap; }
+nav { display: flex; gap: 1rem; justify-content: space-between; margin: 2rem 0; }
+a { color: #569cd6; }
+li { margin: 0.3rem 0; }
+";
+
+/// Escape `text` for HTML content and attributes.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render a unified diff, colorized line by line.
+pub fn diff(diff: &str) -> String {
+    let mut html = String::with_capacity(diff.len() * 2);
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            "meta"
+        } else if line.starts_with('+') {
+            "add"
+        } else if line.starts_with('-') {
+            "del"
+        } else if line.starts_with("@@") {
+            "hunk"
+        } else if line.starts_with("diff ") || line.starts_with("index ") {
+            "meta"
+        } else {
+            ""
+        };
+        if class.is_empty() {
+            let _ = writeln!(html, "{}", escape(line));
+        } else {
+            let _ = writeln!(html, r#"<span class="{class}">{}</span>"#, escape(line));
+        }
+    }
+    html
+}
+
+/// Render `slide` as a standalone page.
+///
+/// `head` is added to the `<head>` as is (e.g., a refresh script).
+pub fn page(slide: &Slide, head: &str) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{number}/{total} – {title}</title>
+<style>
+{STYLE}</style>
+{head}</head>
+<body>
+<header>{number}/{total} <span class="hash">{short_hash}</span></header>
+<h1>{title}</h1>
+"#,
+        number = slide
+            .number
+            .map_or_else(|| String::from("?"), |number| number.to_string()),
+        total = slide.total,
+        title = escape(&slide.title),
+        short_hash = escape(&slide.short_hash),
+    );
+    if !slide.body.is_empty() {
+        let _ = writeln!(html, r#"<div class="body">{}</div>"#, escape(&slide.body));
+    }
+    if let Some(notes) = &slide.notes {
+        let _ = writeln!(html, r#"<div class="notes">{}</div>"#, escape(notes));
+    }
+    if let Some(annotations) = &slide.annotations {
+        let _ = writeln!(
+            html,
+            r#"<div class="annotations">{}</div>"#,
+            escape(annotations)
+        );
+    }
+    if !slide.diff.is_empty() {
+        let _ = writeln!(html, "<pre>{}</pre>", diff(&slide.diff));
+    }
+    if let Some(links) = &slide.links {
+        let link = |href: &Option<String>, text: &str| {
+            href.as_ref().map_or_else(String::new, |href| {
+                format!(r#"<a href="{}">{text}</a>"#, escape(href))
+            })
+        };
+        let _ = writeln!(
+            html,
+            r#"<nav><span>{}</span><a href="{}">Index</a><span>{}</span></nav>"#,
+            link(&links.previous, "← Previous"),
+            escape(&links.index),
+            link(&links.next, "Next →"),
+        );
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render an index of all slides, as `(href, title)` pairs.
+pub fn index(title: &str, slides: &[(String, String)]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+{STYLE}</style>
+</head>
+<body>
+<h1>{title}</h1>
+<ol>
+"#,
+        title = escape(title),
+    );
+    for (href, title) in slides {
+        let _ = writeln!(
+            html,
+            r#"<li><a href="{}">{}</a></li>"#,
+            escape(href),
+            escape(title)
+        );
+    }
+    html.push_str("</ol>\n</body>\n</html>\n");
+    html
+}
@@ -0,0 +1,277 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Translation of user-facing messages.
+//!
+//! Messages are looked up by their English text (like gettext), with
+//! `{}` placeholders filled in order. Messages without a translation
+//! are shown in English.
+
+use std::fmt;
+
+/// Language of user-facing messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+impl Language {
+    /// Parse a language code (`fr`), or a locale (`fr_FR.UTF-8`).
+    pub fn parse(value: &str) -> Option<Self> {
+        let code = value.split(['_', '.', '@', '-']).next()?;
+        match code.to_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Self::English),
+            "fr" => Some(Self::French),
+            _ => None,
+        }
+    }
+
+    /// Language of the environment, from `LC_ALL`, `LC_MESSAGES` or
+    /// `LANG`, in that order (the first one set wins, like for Git).
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// Translate `message` and fill in its `{}` placeholders with `args`.
+pub fn format(language: Language, message: &'static str, args: &[&dyn fmt::Display]) -> String {
+    let template = translate(language, message);
+
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut pieces = template.split("{}");
+    if let Some(first) = pieces.next() {
+        out.push_str(first);
+    }
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(piece);
+    }
+    out
+}
+
+/// Translate, then format a message (see [`format()`]).
+macro_rules! tr {
+    ($language:expr, $message:literal $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::format($language, $message, &[$(&$arg as &dyn std::fmt::Display),*])
+    };
+}
+pub(crate) use tr;
+
+fn translate(language: Language, message: &'static str) -> &'static str {
+    match language {
+        Language::English => message,
+        Language::French => french(message).unwrap_or(message),
+    }
+}
+
+#[rustfmt::skip]
+#[allow(clippy::too_many_lines)] // A catalog.
+fn french(message: &str) -> Option<&'static str> {
+    Some(match message {
+        "Presentation started at {}." => "Présentation commencée à {}.",
+        "Presentation stopped." => "Présentation terminée.",
+        "Presentation restarted." => "Présentation redémarrée.",
+        "Presentation state exported to '{}'." => "État de la présentation exporté dans '{}'.",
+        "Presentation state imported from '{}'." => "État de la présentation importé depuis '{}'.",
+        "error: A presentation is already in progress." => "erreur : Une présentation est déjà en cours.",
+        "error: No presentation to resume." => "erreur : Aucune présentation à reprendre.",
+        "Resuming presentation." => "Reprise de la présentation.",
+        "Presenting in worktree '{}'." => "Présentation dans le worktree '{}'.",
+        "Removed worktree '{}'." => "Worktree '{}' supprimé.",
+        "Fetching the full history..." => "Récupération de l'historique complet...",
+        "Going back to branch '{}'." => "Retour à la branche '{}'.",
+        "Going back to commit {}." => "Retour au commit {}.",
+        "Staying on commit {}." => "On reste sur le commit {}.",
+        "Going to '{}'." => "Passage à '{}'.",
+        "You've reached the end of the presentation." => "Vous êtes arrivé à la fin de la présentation.",
+        "You're at the start of the presentation." => "Vous êtes au début de la présentation.",
+        "You need to start by '{}'." => "Vous devez commencer par '{}'.",
+        "Restored changes of slide {}." => "Modifications de la diapositive {} restaurées.",
+        "Stashed uncommitted changes." => "Modifications non validées remisées.",
+        "Restored changes from before the presentation." => "Modifications d'avant la présentation restaurées.",
+        "Changes from before the presentation are still stashed." => "Les modifications d'avant la présentation sont toujours remisées.",
+        "Would stash uncommitted changes." => "Les modifications non validées seraient remisées.",
+        "Would refuse to change slides, there are uncommitted changes." => "Le changement de diapositive serait refusé, il y a des modifications non validées.",
+        "Would discard uncommitted changes." => "Les modifications non validées seraient abandonnées.",
+        "Discarded uncommitted changes." => "Modifications non validées abandonnées.",
+        "Discard these changes?" => "Abandonner ces modifications ?",
+        "error: A {} is in progress." => "erreur : Un {} est en cours.",
+        "hint: Use 'git bisect reset' to end it first." => "astuce : Utilisez d'abord 'git bisect reset' pour le terminer.",
+        "hint: Use 'git {} --continue' to finish it, or 'git {} --abort' to cancel it, first." => "astuce : Utilisez d'abord 'git {} --continue' pour le terminer, ou 'git {} --abort' pour l'annuler.",
+        "Would check out slide {}/{}: {} {}" => "La diapositive {}/{} serait extraite : {} {}",
+        "Would restore changes of slide {}." => "Les modifications de la diapositive {} seraient restaurées.",
+        "Slides reordered." => "Diapositives réordonnées.",
+        "Nothing to present, keeping the current order." => "Rien à présenter, l'ordre actuel est conservé.",
+        "No staged changes, the slide is unchanged." => "Aucune modification indexée, la diapositive est inchangée.",
+        "No changes, the slide is unchanged." => "Aucune modification, la diapositive est inchangée.",
+        "Updated branch '{}'." => "Branche '{}' mise à jour.",
+        "Remapped presentation onto {}, found {} of {} slides." => "Présentation remappée sur {}, {} diapositives sur {} retrouvées.",
+        "No notes for the current slide." => "Aucune note pour la diapositive actuelle.",
+        "Annotations:" => "Annotations :",
+        "Annotated slide {}." => "Diapositive {} annotée.",
+        "No slide matches '{}'." => "Aucune diapositive ne correspond à '{}'.",
+        "No slide contains '{}'." => "Aucune diapositive ne contient '{}'.",
+        "error: No previous slide to go back to." => "erreur : Aucune diapositive précédente où retourner.",
+        "Hidden slides: {}." => "Diapositives masquées : {}.",
+        "Checkpoint: {} elapsed." => "Point d'étape : {} de présentation.",
+        "over time" => "temps dépassé",
+        "Runs: {}" => "Répétitions : {}",
+        "Total" => "Total",
+        "Publishing slides to '{}'." => "Publication des diapositives sur '{}'.",
+        "Stopped publishing." => "Publication arrêtée.",
+        "Following slides from '{}'. Press Ctrl-C to stop." => "Suivi des diapositives de '{}'. Appuyez sur Ctrl-C pour arrêter.",
+        "Serving the current slide on http://127.0.0.1:{}/. Press Ctrl-C to stop." => "Diapositive actuelle servie sur http://127.0.0.1:{}/. Appuyez sur Ctrl-C pour arrêter.",
+        "Exported {} slides to '{}'." => "{} diapositives exportées dans '{}'.",
+        "(Start)" => "(Début)",
+        "(End)" => "(Fin)",
+        "error: Working directory contains uncommitted changes." => "erreur : Le répertoire de travail contient des modifications non validées.",
+        "error: Current HEAD not part of presentation." => "erreur : HEAD ne fait pas partie de la présentation.",
+        "warning: Current HEAD not part of presentation, going back to slide {}." => "avertissement : HEAD ne fait pas partie de la présentation, retour à la diapositive {}.",
+        "error: Bad slide index. Slide {} does not exist." => "erreur : Mauvais numéro de diapositive. La diapositive {} n'existe pas.",
+        "error: '{}' is not part of the presentation." => "erreur : '{}' ne fait pas partie de la présentation.",
+        "Possible values range from 1 to {}." => "Les valeurs possibles vont de 1 à {}.",
+        "error: Could not stash uncommitted changes." => "erreur : Impossible de remiser les modifications non validées.",
+        "error: Bad ref input: '{}'." => "erreur : Mauvaise référence : '{}'.",
+        "error: No HEAD commit. Please provide a valid ref." => "erreur : Aucun commit HEAD. Veuillez fournir une référence valide.",
+        "error: The 'pre-start' hook failed. Aborting." => "erreur : Le hook 'pre-start' a échoué. Abandon.",
+        "error: No tag matches '{}'." => "erreur : Aucune étiquette ne correspond à '{}'.",
+        "error: Manifest '{}' lists no slides." => "erreur : Le manifeste '{}' ne liste aucune diapositive.",
+        "error: No commit matches '{}'." => "erreur : Aucun commit ne correspond à '{}'.",
+        "error: No commits to present." => "erreur : Aucun commit à présenter.",
+        "error: '--stash' cannot be combined with '--force'." => "erreur : '--stash' ne peut pas être combiné avec '--force'.",
+        "Aborting." => "Abandon.",
+        "error: Could not discard uncommitted changes." => "erreur : Impossible d'abandonner les modifications non validées.",
+        "warning: Could not restore changes from before the presentation, they are still stashed." => "avertissement : Impossible de restaurer les modifications d'avant la présentation, elles sont toujours remisées.",
+        "hint: Use 'git stash apply {}' to restore them by hand." => "astuce : Utilisez 'git stash apply {}' pour les restaurer à la main.",
+        "error: Could not create a worktree in '{}'." => "erreur : Impossible de créer un worktree dans '{}'.",
+        "warning: Cannot find worktree '{}'." => "avertissement : Impossible de trouver le worktree '{}'.",
+        "error: '--tags' cannot be combined with '--manifest'." => "erreur : '--tags' ne peut pas être combiné avec '--manifest'.",
+        "error: Explicit slides cannot be combined with '--from' or '--match'." => "erreur : Des diapositives explicites ne peuvent pas être combinées avec '--from' ou '--match'.",
+        "error: Several refs cannot be combined with '--tags', '--manifest' or '--from'." => "erreur : Plusieurs références ne peuvent pas être combinées avec '--tags', '--manifest' ou '--from'.",
+        "error: '{}' has no commits that the refs before it don't have." => "erreur : '{}' n'a aucun commit que les références précédentes n'ont pas déjà.",
+        "warning: Branch '{}' points to slide {}/{}, in the middle of the presentation." => "avertissement : La branche '{}' pointe sur la diapositive {}/{}, au milieu de la présentation.",
+        "hint: Commits made on a slide are not on any branch, use 'git switch -c <branch>' to keep them." => "astuce : Les commits faits sur une diapositive ne sont sur aucune branche, utilisez 'git switch -c <branche>' pour les garder.",
+        "error: Could not fetch the full history." => "erreur : Impossible de récupérer l'historique complet.",
+        "warning: This is a shallow clone, the first slides may be missing." => "avertissement : Ceci est un clone superficiel, les premières diapositives peuvent manquer.",
+        "hint: Use 'git slides start --deepen' to fetch the full history." => "astuce : Utilisez 'git slides start --deepen' pour récupérer l'historique complet.",
+        "error: '{}' is not an ancestor of the last slide." => "erreur : '{}' n'est pas un ancêtre de la dernière diapositive.",
+        "error: The 'pre-stop' hook failed. Aborting." => "erreur : Le hook 'pre-stop' a échoué. Abandon.",
+        "error: '--go' cannot be used with a presentation in a worktree." => "erreur : '--go' ne peut pas être utilisé avec une présentation dans un worktree.",
+        "warning: Cannot write to '{}'." => "avertissement : Impossible d'écrire dans '{}'.",
+        "error: Cannot remove '{}'. Aborting." => "erreur : Impossible de supprimer '{}'. Abandon.",
+        "error: Could not checkout {}." => "erreur : Impossible d'extraire {}.",
+        "warning: Could not remove worktree '{}'." => "avertissement : Impossible de supprimer le worktree '{}'.",
+        "warning: The 'post-slide' hook failed." => "avertissement : Le hook 'post-slide' a échoué.",
+        "error: Cannot write '{}'. Aborting." => "erreur : Impossible d'écrire '{}'. Abandon.",
+        "error: There was a problem with the editor. Aborting." => "erreur : Il y a eu un problème avec l'éditeur. Abandon.",
+        "error: Could not create the new slide." => "erreur : Impossible de créer la nouvelle diapositive.",
+        "error: Could not stage changes." => "erreur : Impossible d'indexer les modifications.",
+        "error: Could not amend the current slide." => "erreur : Impossible de corriger la diapositive actuelle.",
+        "error: Cannot write '{}'." => "erreur : Impossible d'écrire '{}'.",
+        "error: Cannot read '{}'." => "erreur : Impossible de lire '{}'.",
+        "error: Cannot parse '{}'." => "erreur : Impossible d'analyser '{}'.",
+        "error: Commit {} is not in this repository, fetch it first." => "erreur : Le commit {} n'est pas dans ce dépôt, récupérez-le d'abord.",
+        "error: Could not publish the current slide to '{}'." => "erreur : Impossible de publier la diapositive actuelle sur '{}'.",
+        "error: Cannot listen on port {}." => "erreur : Impossible d'écouter sur le port {}.",
+        "error: Cannot create '{}'." => "erreur : Impossible de créer '{}'.",
+        "warning: Could not check out {}." => "avertissement : Impossible d'extraire {}.",
+        "error: No presentation to show the timeline of." => "erreur : Aucune présentation dont afficher la chronologie.",
+        "error: No recorded presentation yet." => "erreur : Aucune présentation enregistrée pour l'instant.",
+        "error: No such remote: '{}'." => "erreur : Dépôt distant inconnu : '{}'.",
+        "error: Cannot derive a web URL from '{}'." => "erreur : Impossible de déduire une URL web de '{}'.",
+        "hint: Set 'slides.permalink' to a URL template, e.g.," => "astuce : Donnez à 'slides.permalink' un modèle d'URL, par exemple,",
+        "hint: 'https://example.com/repo/commit/%H'." => "astuce : 'https://example.com/repo/commit/%H'.",
+        "error: Could not edit notes of {}." => "erreur : Impossible de modifier les notes de {}.",
+        "error: Could not annotate {}." => "erreur : Impossible d'annoter {}.",
+        "error: Cannot hide the current slide." => "erreur : Impossible de masquer la diapositive actuelle.",
+        "error: There is no hidden slide {}." => "erreur : Il n'y a pas de diapositive masquée {}.",
+        "hint: Use 'git slides hide' to list hidden slides." => "astuce : Utilisez 'git slides hide' pour lister les diapositives masquées.",
+        "error: Could not search slides for '{}'." => "erreur : Impossible de chercher '{}' dans les diapositives.",
+        "hint: Use 'git slides start --resume' to continue it, or 'git slides stop' to end it." => "astuce : Utilisez 'git slides start --resume' pour la reprendre, ou 'git slides stop' pour la terminer.",
+        "error: No slide found in '{}'. The presentation is unchanged." => "erreur : Aucune diapositive trouvée dans '{}'. La présentation est inchangée.",
+        "error: Cannot create '{}'. Aborting." => "erreur : Impossible de créer '{}'. Abandon.",
+        "error: Another git-slides process is running. Aborting." => "erreur : Un autre processus git-slides est en cours. Abandon.",
+        "warning: Cannot lock '{}': {}." => "avertissement : Impossible de verrouiller '{}' : {}.",
+        "error: Cannot lock '{}': {}. Aborting." => "erreur : Impossible de verrouiller '{}' : {}. Abandon.",
+        "hint: Commit or stash them first ('slides.dirtyAction' is 'block')." => "astuce : Validez-les ou remisez-les d'abord ('slides.dirtyAction' vaut 'block').",
+        "warning: Could not restore changes of slide {}, they are still stashed." => "avertissement : Impossible de restaurer les modifications de la diapositive {}, elles sont toujours remisées.",
+        "warning: Could not update submodules." => "avertissement : Impossible de mettre à jour les sous-modules.",
+        "warning: Could not run 'git lfs checkout'." => "avertissement : Impossible de lancer 'git lfs checkout'.",
+        "warning: Could not run 'git lfs pull'." => "avertissement : Impossible de lancer 'git lfs pull'.",
+        "warning: Could not publish the current slide to '{}'." => "avertissement : Impossible de publier la diapositive actuelle sur '{}'.",
+        "warning: Could not rename tmux window." => "avertissement : Impossible de renommer la fenêtre tmux.",
+        "warning: Could not send keys to tmux pane '{}'." => "avertissement : Impossible d'envoyer les touches au panneau tmux '{}'.",
+        "hint: This slide has 'Slide-Exec' commands, they were not run." => "astuce : Cette diapositive a des commandes 'Slide-Exec', elles n'ont pas été lancées.",
+        "hint: Use 'git config slides.exec true' to run them." => "astuce : Utilisez 'git config slides.exec true' pour les lancer.",
+        "warning: Slide-Exec command failed: '{}'." => "avertissement : La commande Slide-Exec a échoué : '{}'.",
+        "warning: Viewer command failed: '{}'." => "avertissement : La commande de visualisation a échoué : '{}'.",
+        "warning: Cannot write info file '{}'." => "avertissement : Impossible d'écrire le fichier d'informations '{}'.",
+        "warning: Could not write the slide file to '{}'." => "avertissement : Impossible d'écrire le fichier de diapositive dans '{}'.",
+        "warning: onSlide command failed: '{}'." => "avertissement : La commande onSlide a échoué : '{}'.",
+        "error: Cannot read manifest '{}'." => "erreur : Impossible de lire le manifeste '{}'.",
+        "error: Bad ref in manifest, line {}: '{}'." => "erreur : Mauvaise référence dans le manifeste, ligne {} : '{}'.",
+        "error: Duplicate slide in manifest, line {}: '{}' (same as line {})." => "erreur : Diapositive en double dans le manifeste, ligne {} : '{}' (comme ligne {}).",
+        "error: Cannot parse '{}'. Aborting." => "erreur : Impossible d'analyser '{}'. Abandon.",
+        "error: '{}' uses format version {}, which is not supported." => "erreur : '{}' utilise la version de format {}, qui n'est pas prise en charge.",
+        "It was likely written by a newer version of git-slides. Aborting." => "Il a probablement été écrit par une version plus récente de git-slides. Abandon.",
+        "error: No HEAD commit." => "erreur : Aucun commit HEAD.",
+        "error: Could not rebase the following slides onto the new commit." => "erreur : Impossible de rebaser les diapositives suivantes sur le nouveau commit.",
+        "The presentation is unchanged. The new commit is {}." => "La présentation est inchangée. Le nouveau commit est {}.",
+        "warning: The new commit is not part of the presentation." => "avertissement : Le nouveau commit ne fait pas partie de la présentation.",
+        "error: Cannot read '{}'. Aborting." => "erreur : Impossible de lire '{}'. Abandon.",
+        "hint: History seems to have been rewritten (e.g., rebased)." => "astuce : L'historique semble avoir été réécrit (par exemple, rebasé).",
+        "hint: Use 'git slides repair' to remap the presentation." => "astuce : Utilisez 'git slides repair' pour remapper la présentation.",
+        "hint: Use 'git slides --recover <command>' to go back to the nearest slide." => "astuce : Utilisez 'git slides --recover <commande>' pour revenir à la diapositive la plus proche.",
+        "fatal: Cannot find the current executable." => "fatal : Impossible de trouver l'exécutable actuel.",
+        "error: Could not find the latest release." => "erreur : Impossible de trouver la dernière version.",
+        "error: {}" => "erreur : {}",
+        "fatal: Need a directory." => "fatal : Un répertoire est nécessaire.",
+        "fatal: Cannot write '{}'." => "fatal : Impossible d'écrire '{}'.",
+        "fatal: Option '-C' requires a path." => "fatal : L'option '-C' nécessite un chemin.",
+        "fatal: Cannot change to '{}'." => "fatal : Impossible d'aller dans '{}'.",
+        "fatal: Option '--work-tree' requires a path." => "fatal : L'option '--work-tree' nécessite un chemin.",
+        "fatal: Need a slide number, a percentage or a ref." => "fatal : Un numéro de diapositive, un pourcentage ou une référence est nécessaire.",
+        "fatal: Need a message." => "fatal : Un message est nécessaire.",
+        "fatal: Bad slide number: '{}'." => "fatal : Mauvais numéro de diapositive : '{}'.",
+        "fatal: Need an export format, e.g., '--html <dir>'." => "fatal : Un format d'export est nécessaire, par exemple '--html <répertoire>'.",
+        "fatal: Bad port: '{}'." => "fatal : Mauvais port : '{}'.",
+        "fatal: Bad interval: '{}'." => "fatal : Mauvais intervalle : '{}'.",
+        "fatal: Need a file." => "fatal : Un fichier est nécessaire.",
+        "fatal: Need a pattern." => "fatal : Un motif est nécessaire.",
+        "fatal: Unknown timeline format: '{}'." => "fatal : Format de chronologie inconnu : '{}'.",
+        "fatal: Bad tag order: '{}'. Expected 'name' or 'date'." => "fatal : Mauvais ordre d'étiquettes : '{}'. 'name' ou 'date' attendu.",
+        "fatal: Option '{}' requires a value." => "fatal : L'option '{}' nécessite une valeur.",
+        "Unknown argument: '{}'.\n" => "Argument inconnu : '{}'.\n",
+        "warning: Cannot open log file '{}'." => "avertissement : Impossible d'ouvrir le fichier de journal '{}'.",
+        "fatal: Cannot use '{}' as a work tree." => "fatal : Impossible d'utiliser '{}' comme arbre de travail.",
+        "fatal: Did not find git executable." => "fatal : L'exécutable git est introuvable.",
+        "fatal: Not a git repository (or any of the parent directories): .git" => "fatal : Ni ceci ni aucun de ses répertoires parents n'est un dépôt git : .git",
+        "fatal: Cannot present from a bare repository." => "fatal : Impossible de présenter depuis un dépôt nu.",
+        "Use '--work-tree <path>' to provide a working tree." => "Utilisez '--work-tree <chemin>' pour fournir un arbre de travail.",
+        "fatal: Bad config value for 'slides.{}': '{}'." => "fatal : Mauvaise valeur de configuration pour 'slides.{}' : '{}'.",
+        _ => return None,
+    })
+}
@@ -14,4 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod config;
+pub mod forge;
 pub mod git;
+pub mod glob;
+pub mod log;
+pub mod time;
+pub mod toml;
@@ -0,0 +1,81 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Debug log, appended to a file (`GIT_SLIDES_LOG`, `slides.logFile`).
+//!
+//! Every line is an event:
+//!
+//! ```text
+//! 2024-10-16T10:50:45Z [4242] command: git-slides next
+//! 2024-10-16T10:50:45Z [4242] git: git checkout <hash> (exit 0)
+//! ```
+//!
+//! The process ID tells invocations apart, if several write at once.
+
+use std::fs::{self, File};
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use crate::time;
+
+static FILE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Log events to `path` from now on, appending to it.
+///
+/// Only the first call has an effect.
+///
+/// # Errors
+///
+/// Errors if the file cannot be opened for writing.
+pub fn open(path: &Path) -> std::io::Result<()> {
+    if FILE.get().is_some() {
+        return Ok(());
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let _ = FILE.set(Mutex::new(file));
+    Ok(())
+}
+
+/// Whether events are being logged.
+#[must_use]
+pub fn is_open() -> bool {
+    FILE.get().is_some()
+}
+
+/// Log an event of some `kind` (e.g., `git`), if the log is open.
+///
+/// Multi-line messages are indented, to keep one event per unindented
+/// line. Failing to write is not an error, the log is best-effort.
+pub fn write(kind: &str, message: &str) {
+    let Some(file) = FILE.get() else {
+        return;
+    };
+    let mut entry = format!(
+        "{} [{}] {kind}: ",
+        time::format_rfc3339(time::now()),
+        std::process::id()
+    );
+    entry.push_str(&message.trim_end().replace('\n', "\n    "));
+    entry.push('\n');
+    if let Ok(mut file) = file.lock() {
+        // A single write, so concurrent invocations don't interleave.
+        let _ = file.write_all(entry.as_bytes());
+    }
+}
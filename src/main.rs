@@ -15,130 +15,1171 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod cmd;
+mod console;
+mod hooks;
+mod html;
+mod i18n;
+mod man;
+mod manifest;
+mod pager;
+mod screen;
+mod store;
+mod tmux;
+mod update;
+mod web;
 
-use std::env;
-use std::path::PathBuf;
+use std::env::{self, Args};
+use std::fs;
+use std::io::{self, IsTerminal as _};
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
 
-use git_slides::git;
+use git_slides::config::Config;
+use git_slides::git::{self, RepositoryError};
+use git_slides::{log, time};
 
-use crate::cmd::Cmd;
+use crate::cmd::{
+    exit_on_error, CheckpointAlert, Cmd, Colors, Columns, DirtyAction, GoTarget, Lfs, ListOptions,
+    Options, StartAt, StartOptions, StatusFormat, StopAt, TagOrder, TimelineFormat, Tmux,
+};
+use crate::i18n::{tr, Language};
+
+/// Options given before the command (`[<options>] <command>`).
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)] // Independent switches.
+struct GlobalArgs {
+    work_tree: Option<String>,
+    recurse_submodules: Option<bool>,
+    quiet: bool,
+    no_color: bool,
+    strict: bool,
+    loop_: bool,
+    recover: bool,
+    dry_run: bool,
+    verbose: bool,
+    /// From `GIT_SLIDES_STORE`, absolute so it survives `cd`.
+    store_file: Option<PathBuf>,
+}
+
+/// Language of messages, once known from the config.
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Language of messages printed before there is a [`Cmd`] to ask (the
+/// environment's, until the config is read).
+fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or_else(Language::from_env)
+}
 
 fn main() {
     let mut args = env::args().peekable();
     args.next();
 
-    if let Some(arg) = args.peek() {
+    let global_args = parse_global_args(&mut args);
+    git::set_verbose(global_args.verbose);
+    if let Some(log_file) = env::var_os("GIT_SLIDES_LOG").filter(|x| !x.is_empty()) {
+        open_log(Path::new(&log_file));
+    }
+
+    if args.peek().is_some_and(|arg| arg == "prompt") {
+        return prompt(&global_args);
+    }
+    // Hidden, for shell completion scripts.
+    if args.peek().is_some_and(|arg| arg == "__complete") {
+        return complete(&global_args);
+    }
+    // These work outside of a repository.
+    if args.peek().is_some_and(|arg| arg == "self-update") {
+        args.next();
+        return self_update(&global_args, &mut args);
+    }
+    if args.peek().is_some_and(|arg| arg == "generate-man") {
+        args.next();
+        return generate_man(&mut args);
+    }
+
+    if let Some(work_tree) = &global_args.work_tree {
+        set_work_tree_or_exit(work_tree);
+    }
+
+    let git_dir = get_git_directory_or_exit();
+
+    console::set_default_pager();
+
+    let config = Config::load();
+    if let Some(log_file) = config.get("logFile") {
+        open_log(Path::new(log_file));
+    }
+    let options = build_options(&global_args, &config);
+    let _ = LANGUAGE.set(options.language);
+
+    let mut cmd = Cmd::new(Box::new(git::Subprocess), git_dir, options);
+    cmd.enter_worktree();
+
+    if let Some(arg) = args.next() {
+        // Read-only commands don't need it (and `list` may sit in
+        // a pager for a long time). Neither do `notes`, they are not
+        // part of the store (and may sit in an editor). `present` and
+        // `follow` run until interrupted, they lock on each navigation
+        // instead.
+        let _lock = (!matches!(
+            arg.as_str(),
+            "status"
+                | "list"
+                | "count"
+                | "current"
+                | "env"
+                | "permalink"
+                | "export"
+                | "export-state"
+                | "info"
+                | "doctor"
+                | "verify"
+                | "find"
+                | "grep"
+                | "notes"
+                | "timeline"
+                | "events"
+                | "stats"
+                | "follow"
+                | "web"
+                | "present"
+        ))
+        .then(|| cmd.lock());
+        return run_command(&mut cmd, &arg, &mut args);
+    }
+
+    // No arguments.
+
+    if cmd.is_presentation_started() {
+        return exit_on_error(cmd.status());
+    }
+
+    help();
+}
+
+/// Fast path for shell prompts, that must not fail loudly (e.g., when
+/// outside of a repository) nor do more work than necessary.
+fn prompt(global_args: &GlobalArgs) {
+    if let Some(cmd) = quiet_cmd(global_args) {
+        cmd.prompt();
+    }
+}
+
+/// Fast path for shell completion, same constraints as prompts.
+fn complete(global_args: &GlobalArgs) {
+    if let Some(cmd) = quiet_cmd(global_args) {
+        cmd.complete();
+    }
+}
+
+/// Replace the running executable with the binary of the latest
+/// release, or of a given one.
+fn self_update(global_args: &GlobalArgs, args: &mut Peekable<Args>) {
+    let requested = args.next();
+    if let Some(arg) = args.next() {
+        unknown_argument(&arg);
+    }
+
+    let Ok(exe) = env::current_exe().and_then(fs::canonicalize) else {
+        eprintln!(
+            "{}",
+            tr!(language(), "fatal: Cannot find the current executable.")
+        );
+        std::process::exit(1);
+    };
+    let releases_url = update::releases_url();
+
+    let version = match requested {
+        Some(version) => version,
+        None => match update::latest_version(&releases_url) {
+            Ok(version) => {
+                if version.trim_start_matches('v') == env!("CARGO_PKG_VERSION") {
+                    if !global_args.quiet {
+                        println!("Already up to date ({version}).");
+                    }
+                    return;
+                }
+                version
+            }
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    tr!(language(), "error: Could not find the latest release.")
+                );
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        },
+    };
+
+    if !global_args.quiet {
+        println!("Downloading git-slides {version}...");
+    }
+    if let Err(err) = update::install(&releases_url, &version, &exe) {
+        eprintln!("{}", tr!(language(), "error: {}", err));
+        std::process::exit(1);
+    }
+    if !global_args.quiet {
+        println!("Updated to {version}.");
+    }
+}
+
+/// Write the man page, made from `--help`, as `<dir>/git-slides.1`.
+fn generate_man(args: &mut Peekable<Args>) {
+    let Some(dir) = args.next() else {
+        eprintln!("{}", tr!(language(), "fatal: Need a directory."));
+        std::process::exit(2);
+    };
+    if let Some(arg) = args.next() {
+        unknown_argument(&arg);
+    }
+
+    // Dated like the build, for reproducible builds.
+    let timestamp = env!("GIT_SLIDES_BUILD_TIMESTAMP").parse().unwrap_or(0);
+    let date = time::format_timestamp(timestamp);
+    let page = man::render(&help_text(), &date[.."YYYY-MM-DD".len()]);
+
+    let path = Path::new(&dir).join(concat!(env!("CARGO_BIN_NAME"), ".1"));
+    if fs::create_dir_all(&dir)
+        .and_then(|()| fs::write(&path, page))
+        .is_err()
+    {
+        eprintln!(
+            "{}",
+            tr!(language(), "fatal: Cannot write '{}'.", path.display())
+        );
+        std::process::exit(1);
+    }
+}
+
+/// A `Cmd` that skips config and doesn't exit outside of a repository.
+fn quiet_cmd(global_args: &GlobalArgs) -> Option<Cmd> {
+    if let Some(work_tree) = &global_args.work_tree {
+        set_work_tree_or_exit(work_tree);
+    }
+    let repository = git::repository().ok()?;
+    let options = Options {
+        store_file: global_args.store_file.clone(),
+        ..Options::default()
+    };
+    let cmd = Cmd::new(Box::new(git::Subprocess), repository.git_dir, options);
+    cmd.enter_worktree();
+    Some(cmd)
+}
+
+fn parse_global_args(args: &mut Peekable<Args>) -> GlobalArgs {
+    let mut global_args = GlobalArgs {
+        store_file: env::var_os("GIT_SLIDES_STORE")
+            .filter(|x| !x.is_empty())
+            .map(|x| std::path::absolute(&x).unwrap_or_else(|_| PathBuf::from(x))),
+        ..GlobalArgs::default()
+    };
+
+    while let Some(arg) = args.peek() {
         match arg.as_str() {
             "-h" | "--help" => {
-                args.next();
                 help();
-                return;
+                std::process::exit(0);
             }
             "-v" | "--version" => {
                 args.next();
-                version();
-                return;
+                let verbose = global_args.verbose || args.any(|arg| arg == "--verbose");
+                version(verbose);
+                std::process::exit(0);
+            }
+            "-C" => {
+                args.next();
+                let Some(path) = args.next() else {
+                    eprintln!("{}", tr!(language(), "fatal: Option '-C' requires a path."));
+                    std::process::exit(2);
+                };
+                // Like Git, right away, so later paths are relative to it.
+                if env::set_current_dir(&path).is_err() {
+                    eprintln!("{}", tr!(language(), "fatal: Cannot change to '{}'.", path));
+                    std::process::exit(1);
+                }
+            }
+            "--work-tree" => {
+                args.next();
+                let Some(path) = args.next() else {
+                    eprintln!(
+                        "{}",
+                        tr!(language(), "fatal: Option '--work-tree' requires a path.")
+                    );
+                    std::process::exit(2);
+                };
+                global_args.work_tree = Some(path);
+            }
+            arg if arg.starts_with("--work-tree=") => {
+                global_args.work_tree = Some(arg["--work-tree=".len()..].to_string());
+                args.next();
+            }
+            "--recurse-submodules" => {
+                args.next();
+                global_args.recurse_submodules = Some(true);
+            }
+            "--no-recurse-submodules" => {
+                args.next();
+                global_args.recurse_submodules = Some(false);
+            }
+            "-q" | "--quiet" => {
+                args.next();
+                global_args.quiet = true;
+            }
+            "--no-color" => {
+                args.next();
+                global_args.no_color = true;
+            }
+            "--strict" => {
+                args.next();
+                global_args.strict = true;
+            }
+            "--loop" => {
+                args.next();
+                global_args.loop_ = true;
+            }
+            "--recover" => {
+                args.next();
+                global_args.recover = true;
+            }
+            "--dry-run" => {
+                args.next();
+                global_args.dry_run = true;
+            }
+            "--verbose" => {
+                args.next();
+                global_args.verbose = true;
             }
-            _ => (),
+            _ => break,
         }
     }
 
-    ensure_git_executable_is_in_path();
-    let git_dir = get_git_directory_or_exit();
+    global_args
+}
 
-    let cmd = Cmd::new(git_dir);
+fn build_options(global_args: &GlobalArgs, config: &Config) -> Options {
+    // Validated even if unused, so mistakes don't go unnoticed.
+    let colors = colors_from_config(config);
 
-    if let Some(arg) = args.next() {
-        return match arg.as_str() {
-            "start" => {
-                // `start` may be followed by `ref`.
-                cmd.start(args.next());
-            }
-            "stop" => cmd.stop(),
-            "next" | "n" => {
-                // `next` may be followed by `n`.
-                if let Some(n) = args.peek() {
-                    if let Ok(n) = n.parse::<usize>() {
-                        return cmd.next(n);
-                    }
-                }
-                cmd.next(1);
+    Options {
+        recurse_submodules: global_args
+            .recurse_submodules
+            .or_else(|| config.get_bool("recurseSubmodules"))
+            .unwrap_or(false),
+        lfs: match config.get("lfs").map(str::to_lowercase).as_deref() {
+            None | Some("false" | "no" | "off" | "0") => None,
+            Some("checkout") => Some(Lfs::Checkout),
+            Some("pull") => Some(Lfs::Pull),
+            Some(value) => bad_config_value("lfs", value),
+        },
+        all_parents: config.get_bool("allParents").unwrap_or(false),
+        title_prefixes: config
+            .get_all("titlePrefix")
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        quiet: global_args.quiet,
+        language: match config.get("language") {
+            None => Language::from_env(),
+            Some(value) => {
+                Language::parse(value).unwrap_or_else(|| bad_config_value("language", value))
             }
-            "previous" | "p" => {
-                // `previous` may be followed by `n`.
-                if let Some(n) = args.peek() {
-                    if let Ok(n) = n.parse::<usize>() {
-                        return cmd.previous(n);
-                    }
-                }
-                cmd.previous(1);
+        },
+        strict: global_args.strict || config.get_bool("strict").unwrap_or(false),
+        loop_: global_args.loop_ || config.get_bool("loop").unwrap_or(false),
+        recover: global_args.recover,
+        dry_run: global_args.dry_run,
+        store_file: global_args.store_file.clone(),
+        exec: config.get_bool("exec").unwrap_or(false),
+        permalink: config.get("permalink").map(String::from),
+        viewer: config.get("viewer").map(String::from),
+        on_slide: config.get("onSlide").map(String::from),
+        info_file: config.get("infoFile").map(PathBuf::from),
+        info_format: config.get("infoFormat").unwrap_or("%i/%t %s").to_string(),
+        slide_file: config.get("slideFile").map(String::from),
+        slide_file_pipe: config.get("slideFilePipe").map(PathBuf::from),
+        scratch: config.get_bool("scratch").unwrap_or(false),
+        abbrev: config.get("abbrev").map(|value| {
+            value
+                .parse()
+                .ok()
+                .filter(|abbrev| (4..=40).contains(abbrev))
+                .unwrap_or_else(|| bad_config_value("abbrev", value))
+        }),
+        step: config.get("step").map_or(1, |value| {
+            value
+                .parse()
+                .ok()
+                .filter(|step| *step > 0)
+                .unwrap_or_else(|| bad_config_value("step", value))
+        }),
+        dirty_action: match config.get("dirtyAction").map(str::to_lowercase).as_deref() {
+            None | Some("stash") => DirtyAction::Stash,
+            Some("block") => DirtyAction::Block,
+            Some("discard") => DirtyAction::Discard,
+            Some(value) => bad_config_value("dirtyAction", value),
+        },
+        checkpoints: config.get("checkpoints").map_or_else(Vec::new, |value| {
+            value
+                .split_whitespace()
+                .map(|checkpoint| {
+                    time::parse_duration(checkpoint)
+                        .unwrap_or_else(|| bad_config_value("checkpoints", value))
+                })
+                .collect()
+        }),
+        checkpoint_alert: match config
+            .get("checkpointAlert")
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            None | Some("bell") => CheckpointAlert::Bell,
+            Some("notify") => CheckpointAlert::Notify,
+            Some(value) => bad_config_value("checkpointAlert", value),
+        },
+        tmux: config.get_bool("tmux").unwrap_or(false).then(|| Tmux {
+            refresh_pane: config.get("tmuxRefreshPane").map(String::from),
+            refresh_keys: config.get("tmuxRefreshKeys").map_or_else(
+                || Tmux::DEFAULT_REFRESH_KEYS.map(String::from).to_vec(),
+                |keys| keys.split_whitespace().map(String::from).collect(),
+            ),
+        }),
+        columns: Columns {
+            author: config.get_bool("showAuthor").unwrap_or(false),
+            date: config.get_bool("showDate").unwrap_or(false),
+        },
+        // Like colors, only worth it on a terminal.
+        terminal_title: config.get_bool("terminalTitle").unwrap_or(true)
+            && io::stdout().is_terminal()
+            && console::enable_ansi_sequences(),
+        colors: if use_color(global_args) {
+            colors
+        } else {
+            Colors::none()
+        },
+    }
+}
+
+fn colors_from_config(config: &Config) -> Colors {
+    let mut colors = match config.get("color.theme") {
+        None | Some("default") => Colors::default(),
+        Some("high-contrast") => Colors::high_contrast(),
+        Some(value) => bad_config_value("color.theme", value),
+    };
+    for (key, color) in [
+        ("color.dim", &mut colors.dim),
+        ("color.hash", &mut colors.hash),
+        ("color.warning", &mut colors.warning),
+    ] {
+        if let Some(value) = config.get(key) {
+            *color = git::parse_color(value).unwrap_or_else(|| bad_config_value(key, value));
+        }
+    }
+    colors
+}
+
+/// Color is on, unless disabled by `--no-color` or `NO_COLOR`, or if
+/// the output is not a terminal.
+fn use_color(global_args: &GlobalArgs) -> bool {
+    if global_args.no_color {
+        return false;
+    }
+    // https://no-color.org/
+    if env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty()) {
+        return false;
+    }
+    // Colors are only worth it if the terminal can render them.
+    io::stdout().is_terminal() && console::enable_ansi_sequences()
+}
+
+fn run_command(cmd: &mut Cmd, command: &str, args: &mut Peekable<Args>) {
+    match command {
+        "start" => cmd.start(parse_start_args(args)),
+        "stop" => cmd.stop(&parse_stop_args(args)),
+        "restart" => exit_on_error(cmd.restart()),
+        "next" | "n" => exit_on_error(cmd.next(parse_offset(args))),
+        "previous" | "p" => exit_on_error(cmd.previous(parse_offset(args))),
+        "swap" => exit_on_error(cmd.swap()),
+        "go" => {
+            // `go` must be followed by `n`, `n%` or a ref.
+            if let Some(target) = args.peek().and_then(|n| parse_go_target(n)) {
+                return exit_on_error(cmd.go_to(target));
             }
-            "go" => {
-                // `go` must be followed by `n`.
-                if let Some(n) = args.peek() {
-                    if let Ok(n) = n.parse::<usize>() {
-                        return cmd.go(n);
-                    }
-                }
-                eprintln!("fatal: Need a slide number.");
-                std::process::exit(2);
+            eprintln!(
+                "{}",
+                tr!(
+                    language(),
+                    "fatal: Need a slide number, a percentage or a ref."
+                )
+            );
+            std::process::exit(2);
+        }
+        "status" => {
+            let (format, columns) = parse_status_args(args);
+            cmd.show_columns(columns);
+            match format {
+                StatusFormat::Full => exit_on_error(cmd.status()),
+                StatusFormat::Oneline => cmd.status_oneline(),
+                StatusFormat::Index => cmd.status_index(),
             }
-            "status" => cmd.status(),
-            "list" => cmd.list(),
-            arg => {
-                eprintln!("Unknown argument: '{arg}'.\n");
-                help();
+        }
+        "list" => {
+            let (options, columns) = parse_list_args(args);
+            cmd.show_columns(columns);
+            cmd.list(&options);
+        }
+        "count" => cmd.count(parse_flag(args, "--remaining")),
+        "find" => cmd.find(&parse_pattern_args(args)),
+        "hide" => cmd.hide(&parse_displayed_slide_numbers(args)),
+        "unhide" => cmd.unhide(&parse_slide_numbers(args)),
+        "grep" => {
+            let ignore_case = args.next_if(|arg| arg == "-i" || arg == "--ignore-case");
+            cmd.grep(&parse_pattern_args(args), ignore_case.is_some());
+        }
+        "notes" => cmd.notes(parse_flag(args, "--edit")),
+        "timeline" => cmd.timeline(parse_timeline_args(args)),
+        "events" => cmd.events(),
+        "stats" => cmd.stats(),
+        "env" => cmd.env(),
+        "present" => cmd.present(),
+        "annotate" => {
+            let message = args.collect::<Vec<_>>().join(" ");
+            if message.trim().is_empty() {
+                eprintln!("{}", tr!(language(), "fatal: Need a message."));
                 std::process::exit(2);
             }
-        };
+            cmd.annotate(&message);
+        }
+        "export-state" => cmd.export_state(&parse_file_arg(args)),
+        "import-state" => cmd.import_state(&parse_file_arg(args)),
+        "web" => cmd.web(parse_web_args(args)),
+        "export" => cmd.export_html(&parse_export_args(args)),
+        "publish" => match args.next().as_deref() {
+            Some("--stop") => cmd.publish(None),
+            Some(arg) if arg.starts_with('-') => unknown_argument(arg),
+            remote => cmd.publish(Some(remote.unwrap_or("origin"))),
+        },
+        "follow" => {
+            let (remote, interval) = parse_follow_args(args);
+            cmd.follow(&remote, interval);
+        }
+        "permalink" => {
+            let remote = args.next();
+            if let Some(arg) = args.next() {
+                unknown_argument(&arg);
+            }
+            cmd.permalink(remote.as_deref().unwrap_or("origin"));
+        }
+        "current" => cmd.current(&parse_current_args(args)),
+        "info" => cmd.info(),
+        "doctor" => cmd.doctor(),
+        "verify" => cmd.verify(),
+        "repair" => {
+            let ref_ = args.next();
+            if let Some(arg) = args.next() {
+                unknown_argument(&arg);
+            }
+            cmd.repair(ref_.as_deref());
+        }
+        "reorder" => cmd.reorder(),
+        "insert" => {
+            let message = parse_message_args(args);
+            cmd.insert(message.as_deref());
+        }
+        "edit" => {
+            let message = parse_message_args(args);
+            cmd.edit(message.as_deref());
+        }
+        "absorb" => cmd.absorb(),
+        arg => unknown_argument(arg),
     }
+}
 
-    // No arguments.
+fn parse_start_args(args: &mut Peekable<Args>) -> StartOptions {
+    let mut options = StartOptions::default();
 
-    if cmd.is_presentation_started() {
-        return cmd.status();
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        let mut value = |option| option_value(option, value.take(), args);
+
+        match arg.as_str() {
+            "--all-parents" => options.all_parents = true,
+            "--from" => options.from = Some(value("--from")),
+            "--match" => options.pattern = Some(value("--match")),
+            "--tags" => options.tags = Some(value("--tags")),
+            "--tag-order" => options.tag_order = parse_tag_order(&value("--tag-order")),
+            "--manifest" => options.manifest = Some(value("--manifest")),
+            "--at" => {
+                let value = value("--at");
+                let Ok(n) = value.parse() else {
+                    eprintln!(
+                        "{}",
+                        tr!(language(), "fatal: Bad slide number: '{}'.", value)
+                    );
+                    std::process::exit(2);
+                };
+                options.at = StartAt::Index(n);
+            }
+            "--at-current" => options.at = StartAt::Current,
+            "--deepen" => options.deepen = true,
+            "--worktree" => options.worktree = true,
+            "--stash" => options.stash = true,
+            "-f" | "--force" => options.force = true,
+            "-y" | "--yes" => options.yes = true,
+            "--resume" => options.resume = true,
+            arg if arg.starts_with('-') => unknown_argument(arg),
+            _ if options.ref_.is_some() => options.more_refs.push(arg),
+            // `start` may be followed by `ref`.
+            _ => options.ref_ = Some(arg),
+        }
+    }
+
+    options
+}
+
+/// `next` and `previous` may be followed by `n`.
+fn parse_offset(args: &mut Peekable<Args>) -> Option<usize> {
+    args.peek().and_then(|n| n.parse::<usize>().ok())
+}
+
+fn parse_stop_args(args: &mut Peekable<Args>) -> StopAt {
+    let mut at = StopAt::default();
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "--keep" => at = StopAt::Current,
+            "--go" => at = StopAt::Ref(option_value(&arg, value.take(), args)),
+            arg => unknown_argument(arg),
+        }
+    }
+    at
+}
+
+/// Numbers are slide numbers, even if a ref could share the name.
+/// Except from 7 digits on, which look more like an abbreviated hash
+/// than like a slide.
+fn parse_go_target(arg: &str) -> Option<GoTarget> {
+    const MIN_HASH_LEN: usize = 7;
+
+    if let Some(percentage) = arg.strip_suffix('%') {
+        return percentage
+            .parse::<usize>()
+            .ok()
+            .filter(|percentage| *percentage <= 100)
+            .map(GoTarget::Percentage);
+    }
+    if arg.len() < MIN_HASH_LEN {
+        if let Ok(n) = arg.parse::<usize>() {
+            return Some(GoTarget::Index(n));
+        }
+    }
+    (!arg.starts_with('-')).then(|| GoTarget::Ref(arg.to_string()))
+}
+
+fn parse_export_args(args: &mut Peekable<Args>) -> PathBuf {
+    let mut html = None;
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "--html" => html = Some(option_value(&arg, value.take(), args)),
+            arg => unknown_argument(arg),
+        }
+    }
+    let Some(dir) = html else {
+        eprintln!(
+            "{}",
+            tr!(
+                language(),
+                "fatal: Need an export format, e.g., '--html <dir>'."
+            )
+        );
+        std::process::exit(2);
+    };
+    PathBuf::from(dir)
+}
+
+fn parse_web_args(args: &mut Peekable<Args>) -> u16 {
+    let mut port = 8080;
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "--port" => {
+                let value = option_value(&arg, value.take(), args);
+                let Ok(value) = value.parse() else {
+                    eprintln!("{}", tr!(language(), "fatal: Bad port: '{}'.", value));
+                    std::process::exit(2);
+                };
+                port = value;
+            }
+            arg => unknown_argument(arg),
+        }
+    }
+    port
+}
+
+fn parse_follow_args(args: &mut Peekable<Args>) -> (String, Duration) {
+    let mut remote = String::from("origin");
+    let mut interval = Duration::from_secs(2);
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "--remote" => remote = option_value(&arg, value.take(), args),
+            "--interval" => {
+                let value = option_value(&arg, value.take(), args);
+                let Some(seconds) = time::parse_duration(&value).filter(|seconds| *seconds > 0)
+                else {
+                    eprintln!("{}", tr!(language(), "fatal: Bad interval: '{}'.", value));
+                    std::process::exit(2);
+                };
+                interval = Duration::from_secs(seconds);
+            }
+            arg => unknown_argument(arg),
+        }
+    }
+    (remote, interval)
+}
+
+/// Whether `flag`, the only argument accepted, is given.
+fn parse_flag(args: &mut Peekable<Args>, flag: &str) -> bool {
+    let mut found = false;
+    for arg in args.by_ref() {
+        if arg == flag {
+            found = true;
+        } else {
+            unknown_argument(&arg);
+        }
+    }
+    found
+}
+
+fn parse_file_arg(args: &mut Peekable<Args>) -> String {
+    let Some(file) = args.next() else {
+        eprintln!("{}", tr!(language(), "fatal: Need a file."));
+        std::process::exit(2);
+    };
+    if let Some(arg) = args.next() {
+        unknown_argument(&arg);
+    }
+    file
+}
+
+fn parse_slide_numbers(args: &mut Peekable<Args>) -> Vec<usize> {
+    args.map(|arg| {
+        arg.parse().unwrap_or_else(|_| {
+            if arg.starts_with('-') {
+                unknown_argument(&arg);
+            }
+            eprintln!("{}", tr!(language(), "fatal: Bad slide number: '{}'.", arg));
+            std::process::exit(2);
+        })
+    })
+    .collect()
+}
+
+/// The remaining arguments, as one pattern (e.g., `find async fn`).
+/// Like [`parse_slide_numbers()`], but `<section>.<slide>` too, as
+/// shown with sections.
+fn parse_displayed_slide_numbers(args: &mut Peekable<Args>) -> Vec<String> {
+    args.inspect(|arg| {
+        let is_number = !arg.is_empty()
+            && arg.split('.').count() <= 2
+            && arg
+                .split('.')
+                .all(|x| !x.is_empty() && x.bytes().all(|b| b.is_ascii_digit()));
+        if !is_number {
+            if arg.starts_with('-') {
+                unknown_argument(arg);
+            }
+            eprintln!("{}", tr!(language(), "fatal: Bad slide number: '{}'.", arg));
+            std::process::exit(2);
+        }
+    })
+    .collect()
+}
+
+fn parse_pattern_args(args: &mut Peekable<Args>) -> String {
+    let pattern = args.collect::<Vec<_>>().join(" ");
+    if pattern.is_empty() {
+        eprintln!("{}", tr!(language(), "fatal: Need a pattern."));
+        std::process::exit(2);
     }
+    pattern
+}
 
+/// Format of `current`.
+fn parse_current_args(args: &mut Peekable<Args>) -> String {
+    let mut format = String::from("%s");
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "--format" => format = option_value(&arg, value.take(), args),
+            arg => unknown_argument(arg),
+        }
+    }
+    format
+}
+
+fn parse_timeline_args(args: &mut Peekable<Args>) -> TimelineFormat {
+    let mut format = TimelineFormat::default();
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "--format" => {
+                format = match option_value(&arg, value.take(), args).as_str() {
+                    "csv" => TimelineFormat::Csv,
+                    "json" => TimelineFormat::Json,
+                    value => {
+                        eprintln!(
+                            "{}",
+                            tr!(language(), "fatal: Unknown timeline format: '{}'.", value)
+                        );
+                        std::process::exit(2);
+                    }
+                };
+            }
+            arg => unknown_argument(arg),
+        }
+    }
+    format
+}
+
+fn parse_status_args(args: &mut Peekable<Args>) -> (StatusFormat, Columns) {
+    let mut format = StatusFormat::default();
+    let mut columns = Columns::default();
+
+    for arg in args.by_ref() {
+        match arg.as_str() {
+            "--oneline" => format = StatusFormat::Oneline,
+            "--index" => format = StatusFormat::Index,
+            "--author" => columns.author = true,
+            "--date" => columns.date = true,
+            arg => unknown_argument(arg),
+        }
+    }
+
+    (format, columns)
+}
+
+fn parse_list_args(args: &mut Peekable<Args>) -> (ListOptions, Columns) {
+    let mut options = ListOptions {
+        verbose: false,
+        pager: env::var_os("GIT_SLIDES_NO_PAGER").is_none_or(|x| x.is_empty()),
+        stat: false,
+    };
+    let mut columns = Columns::default();
+
+    for arg in args.by_ref() {
+        match arg.as_str() {
+            "-v" | "--verbose" => options.verbose = true,
+            "--no-pager" => options.pager = false,
+            "--stat" => options.stat = true,
+            "--author" => columns.author = true,
+            "--date" => columns.date = true,
+            arg => unknown_argument(arg),
+        }
+    }
+
+    (options, columns)
+}
+
+fn parse_message_args(args: &mut Peekable<Args>) -> Option<String> {
+    let mut message = None;
+
+    while let Some(arg) = args.next() {
+        let (arg, mut value) = split_inline_value(arg);
+        match arg.as_str() {
+            "-m" | "--message" => message = Some(option_value(&arg, value.take(), args)),
+            arg => unknown_argument(arg),
+        }
+    }
+
+    message
+}
+
+fn parse_tag_order(order: &str) -> TagOrder {
+    match order {
+        "name" => TagOrder::Name,
+        "date" => TagOrder::Date,
+        order => {
+            eprintln!(
+                "{}",
+                tr!(
+                    language(),
+                    "fatal: Bad tag order: '{}'. Expected 'name' or 'date'.",
+                    order
+                )
+            );
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Split `--option=value` into `--option` and `value`.
+fn split_inline_value(arg: String) -> (String, Option<String>) {
+    if arg.starts_with("--") {
+        if let Some((option, value)) = arg.split_once('=') {
+            return (option.to_string(), Some(value.to_string()));
+        }
+    }
+    (arg, None)
+}
+
+/// Get the value of `--option=value` or `--option value`.
+fn option_value(option: &str, inline_value: Option<String>, args: &mut Peekable<Args>) -> String {
+    let Some(value) = inline_value.or_else(|| args.next()) else {
+        eprintln!(
+            "{}",
+            tr!(language(), "fatal: Option '{}' requires a value.", option)
+        );
+        std::process::exit(2);
+    };
+    value
+}
+
+fn unknown_argument(arg: &str) -> ! {
+    eprintln!("{}", tr!(language(), "Unknown argument: '{}'.\n", arg));
     help();
+    std::process::exit(2);
+}
+
+/// Log to `path` (unless already logging), starting with the command
+/// line.
+fn open_log(path: &Path) {
+    if log::is_open() {
+        return;
+    }
+    if log::open(path).is_err() {
+        eprintln!(
+            "{}",
+            tr!(
+                language(),
+                "warning: Cannot open log file '{}'.",
+                path.display()
+            )
+        );
+        return;
+    }
+    let args: Vec<String> = env::args().skip(1).collect();
+    log::write(
+        "command",
+        &format!("{} {}", env!("CARGO_BIN_NAME"), args.join(" ")),
+    );
 }
 
-fn ensure_git_executable_is_in_path() {
-    if !git::is_git_in_path() {
-        eprintln!("fatal: Did not find git executable.");
+fn set_work_tree_or_exit(work_tree: &str) {
+    let Ok(work_tree) = fs::canonicalize(work_tree) else {
+        eprintln!(
+            "{}",
+            tr!(
+                language(),
+                "fatal: Cannot use '{}' as a work tree.",
+                work_tree
+            )
+        );
+        std::process::exit(1);
+    };
+    if !work_tree.is_dir() {
+        eprintln!(
+            "{}",
+            tr!(
+                language(),
+                "fatal: Cannot use '{}' as a work tree.",
+                work_tree.display()
+            )
+        );
         std::process::exit(1);
     }
+    // Git sub-processes inherit the environment, so every command we
+    // run from now on will operate on this work tree.
+    env::set_var("GIT_WORK_TREE", work_tree);
 }
 
 fn get_git_directory_or_exit() -> PathBuf {
-    let Some(git_dir) = git::find_git_directory() else {
-        eprintln!("fatal: Not a git repository (or any of the parent directories): .git");
-        std::process::exit(1);
+    let repository = match git::repository() {
+        Ok(repository) => repository,
+        Err(RepositoryError::GitNotFound) => {
+            eprintln!("{}", tr!(language(), "fatal: Did not find git executable."));
+            std::process::exit(1);
+        }
+        Err(RepositoryError::NotARepository) => {
+            eprintln!(
+                "{}",
+                tr!(
+                    language(),
+                    "fatal: Not a git repository (or any of the parent directories): .git"
+                )
+            );
+            std::process::exit(1);
+        }
     };
-    git_dir
+    if repository.is_bare {
+        eprintln!(
+            "{}",
+            tr!(language(), "fatal: Cannot present from a bare repository.")
+        );
+        eprintln!(
+            "{}",
+            tr!(
+                language(),
+                "Use '--work-tree <path>' to provide a working tree."
+            )
+        );
+        std::process::exit(1);
+    }
+    repository.git_dir
+}
+
+fn bad_config_value(key: &str, value: &str) -> ! {
+    eprintln!(
+        "{}",
+        tr!(
+            language(),
+            "fatal: Bad config value for 'slides.{}': '{}'.",
+            key,
+            value
+        )
+    );
+    std::process::exit(1);
 }
 
 fn help() {
-    println!(
+    println!("{}", help_text());
+}
+
+#[allow(clippy::too_many_lines)] // Mostly text.
+fn help_text() -> String {
+    format!(
         "\
 usage: {bin} [<options>] <command> [<args>]
 
 Commands:
-  start [<ref>]        Start presentation.
+  start [<ref>...]     Start presentation. With several refs (e.g., a
+                       branch per chapter), each adds a section.
+    --from <ref>       Start presentation at <ref> instead of root.
+    --all-parents      Include commits from merged branches.
+    --match <regex>    Only present commits matching <regex>.
+    --tags <pattern>   Present tagged commits instead of history.
+    --tag-order <order>
+                       Order tags by 'name' (default) or 'date'.
+    --manifest <file>  Present slides listed in <file>.
+    --at <n>           Begin at slide <n> instead of the first one.
+    --at-current       Begin at the slide HEAD is on.
+    --deepen           Fetch the full history first, in a shallow clone.
+    --worktree         Present in a dedicated worktree, leaving the
+                       working tree alone.
+    --stash            Stash uncommitted changes, and restore them on
+                       'stop'.
+    -f, --force        Discard uncommitted changes, after confirmation
+                       (or without, with -y, --yes).
+    --resume           Continue the presentation in progress.
   stop                 End presentation.
+    --keep             Stay on the current slide, with its changes.
+    --go <ref>         Check out <ref> instead of going back.
+  restart              Go back to slide 1 and reset the clock.
   next, n [<n>]        Go forward one or <n> slides.
   previous, p [<n>]    Go back one or <n> slides.
-  go <n>               Go to slide <n>.
+  go <n>, go <n>%      Go to slide <n>, or <n>% of the way in.
+  go <ref>             Go to the slide containing <ref>.
+  swap                 Go back to the previously visited slide.
+  hide [<n>...]        Leave slides out of the presentation (e.g., when
+                       running late), or list hidden slides. <n> may
+                       be numbered as displayed (e.g., '2.3').
+  unhide [<n>...]      Put hidden slides back (as listed by 'hide'), or
+                       all of them.
   status               Show current status.
+    --oneline          Show the current slide on a single line.
+    --index            Show '<n> <total>' only, for scripts.
+    --author, --date   Show author and relative date of slides.
   list                 List all slides.
+    -v, --verbose      Include message excerpts and 'Slide-*' trailers.
+    --no-pager         Print directly, without a pager.
+    --stat             Show the size of each slide's diff.
+    --author, --date   Show author and relative date of slides.
+  current              Show the title of the current slide.
+    --format <format>  Use %s (title), %h/%H (hash), %i (number) and
+                       %t (total) instead.
+  count [--remaining]  Show the number of slides (left).
+  find <pattern>       List slides whose title contains <pattern>.
+  grep <regex>         List slides whose files match <regex>, and the
+                       files, without checking them out.
+    -i, --ignore-case  Ignore case.
+  env                  Show SLIDE_INDEX, SLIDE_TOTAL, SLIDE_HASH and
+                       SLIDE_TITLE, for 'eval' in shell scripts.
+  timeline             Show slide changes (of the last presentation).
+    --format <format>  Use 'csv' (default) or 'json'.
+  stats                Show time spent per slide, across runs.
+  events               Print a JSON line on every slide change, until
+                       interrupted (e.g., for stream overlays).
+  publish [<remote>]   Push every slide change to <remote> (default:
+                       origin), for the audience to follow.
+    --stop             Stop publishing.
+  follow               Check out slides as they are published.
+    --remote <remote>  Follow <remote> instead of origin.
+    --interval <time>  Check every <time> (default: 2s).
+  present              Show slides full-screen, in the terminal (Enter
+                       or 'n' for next, 'p' previous, 'q' to quit).
+  web                  Serve the current slide on a local web page.
+    --port <port>      Listen on <port> (default: 8080).
+  export --html <dir>  Export slides as a static site in <dir>.
+  export-state <file>  Save the presentation state (position, timings)
+                       to <file>.
+  import-state <file>  Continue a presentation saved with export-state.
+  permalink [<remote>] Show the web URL of the current slide on
+                       <remote>'s forge (default: origin).
+  prompt               Show '[slides <n>/<total>]', for shell prompts.
+  notes [--edit]       Show (or edit) notes of the current slide.
+  annotate <message>   Add a timestamped note to the current slide.
+  info                 Show presentation metadata.
+  repair [<ref>]       Remap slides onto rewritten history.
+  doctor               Check the environment and presentation state.
+  verify               Check that every slide still exists.
+  reorder              Reorder or drop slides in an editor.
+  insert [-m <msg>]    Add a slide after the current one.
+  edit [-m <msg>]      Amend the current slide with staged changes.
+  absorb               Amend the current slide with tracked changes.
+  self-update [<version>]
+                       Install the latest release (or <version>) over
+                       this executable.
+  generate-man <dir>   Write the man page to <dir>/git-slides.1.
 
 Options:
   -h, --help           Show this message and exit.
-  -v, --version        Show the version and exit.
+  -v, --version        Show the version and exit (with '--verbose',
+                       also the commit, date and target of the build).
+  -q, --quiet          Suppress informational messages.
+  --no-color           Disable colored output.
+  --strict             Exit with 3 if 'next' or 'previous' goes past
+                       the end or start.
+  --loop               Wrap around past the end or start, instead.
+  --recover            If HEAD left the presentation, go back to the
+                       last slide it descends from.
+  --dry-run            Show what navigation would check out (or stash),
+                       without touching the working tree.
+  --verbose            Print Git commands as they run, with their exit
+                       status.
+  -C <path>            Run as if started in <path>.
+  --work-tree <path>   Use <path> as the working tree.
+  --[no-]recurse-submodules
+                       Update submodules after each slide change.
 ",
         bin = env!("CARGO_BIN_NAME"),
-    );
+    )
 }
 
-fn version() {
+fn version(verbose: bool) {
     println!("{} {}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
+    if !verbose {
+        return;
+    }
+    let timestamp = env!("GIT_SLIDES_BUILD_TIMESTAMP").parse().unwrap_or(0);
+    let features = match env!("GIT_SLIDES_BUILD_FEATURES") {
+        "" => "none",
+        features => features,
+    };
+    println!("commit:     {}", env!("GIT_SLIDES_BUILD_COMMIT"));
+    println!("build date: {}", time::format_timestamp(timestamp));
+    println!("target:     {}", env!("GIT_SLIDES_BUILD_TARGET"));
+    println!("features:   {features}");
 }
@@ -0,0 +1,119 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Man page rendering, for `generate-man`.
+//!
+//! The page is made from `--help`, so they cannot drift apart. It
+//! relies on the layout of the help: unindented `Section:` headings,
+//! and entries with their description starting at [`DESCRIPTION`] (or
+//! on the next lines, if the entry is too long).
+
+use std::fmt::Write as _;
+
+/// Column descriptions start at, in `--help`.
+const DESCRIPTION: usize = 23;
+
+/// Render `help` as a roff man page, for section 1.
+///
+/// `date` is the date of the page (`YYYY-MM-DD`).
+pub fn render(help: &str, date: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH GIT-SLIDES 1 \"{date}\" \"{} {}\" \"Git Manual\"",
+        env!("CARGO_BIN_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    );
+    out.push_str(".SH NAME\n");
+    let _ = writeln!(
+        out,
+        "{} \\- {}",
+        env!("CARGO_BIN_NAME"),
+        escape(env!("CARGO_PKG_DESCRIPTION"))
+    );
+
+    // Whether in a block of options, nested under a command.
+    let mut nested = false;
+
+    for line in help.lines() {
+        let indent = line.len() - line.trim_start().len();
+
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(usage) = line.strip_prefix("usage: ") {
+            let (bin, args) = usage.split_once(' ').unwrap_or((usage, ""));
+            out.push_str(".SH SYNOPSIS\n");
+            let _ = writeln!(out, ".B {bin}\n{}", escape(args));
+            continue;
+        }
+        if indent == 0 {
+            if nested {
+                out.push_str(".RE\n");
+                nested = false;
+            }
+            let heading = line.trim_end_matches(':').to_uppercase();
+            let _ = writeln!(out, ".SH {}", escape(&heading));
+            continue;
+        }
+        if indent >= DESCRIPTION {
+            // Continued description.
+            let _ = writeln!(out, "{}", escape(line.trim()));
+            continue;
+        }
+
+        // An entry, with its description on the same line if it is
+        // short enough.
+        let (term, description) = match line.get(DESCRIPTION - 1..DESCRIPTION) {
+            Some(" ") => (&line[..DESCRIPTION], Some(&line[DESCRIPTION..])),
+            _ => (line, None),
+        };
+        if indent > 2 && !nested {
+            out.push_str(".RS\n");
+            nested = true;
+        } else if indent == 2 && nested {
+            out.push_str(".RE\n");
+            nested = false;
+        }
+        let _ = writeln!(out, ".TP\n.B {}", escape_term(term.trim()));
+        if let Some(description) = description {
+            let _ = writeln!(out, "{}", escape(description.trim()));
+        }
+    }
+    if nested {
+        out.push_str(".RE\n");
+    }
+
+    out.push_str(".SH SEE ALSO\n");
+    out.push_str(".BR git (1)\n");
+    out
+}
+
+/// Escape text for roff: backslashes, and leading characters that
+/// would start a request.
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e");
+    if text.starts_with(['.', '\'']) {
+        format!("\\&{text}")
+    } else {
+        text
+    }
+}
+
+/// Escape an option or command, so dashes aren't turned into hyphens.
+fn escape_term(term: &str) -> String {
+    escape(term).replace('-', "\\-")
+}
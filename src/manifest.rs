@@ -0,0 +1,60 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Presentation manifest.
+//!
+//! A manifest lists slides explicitly, one per line, as a ref (or
+//! commit hash) optionally followed by a title:
+//!
+//! ```text
+//! # Introduction
+//! v1.0        Where we start
+//! a1b2c3d
+//! feat/async  Going async
+//! ```
+//!
+//! Blank lines and lines starting with `#` are ignored.
+
+pub struct Entry {
+    /// Line number in the manifest, starting at 1.
+    pub line: usize,
+    pub ref_: String,
+    /// Title override (defaults to the commit title).
+    pub title: Option<String>,
+}
+
+pub fn parse(content: &str) -> Vec<Entry> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (ref_, title) = match line.split_once(char::is_whitespace) {
+                Some((ref_, title)) => (ref_, Some(title.trim().to_string())),
+                None => (line, None),
+            };
+            Some(Entry {
+                line: line_number,
+                ref_: ref_.to_string(),
+                title,
+            })
+        })
+        .collect()
+}
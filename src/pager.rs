@@ -0,0 +1,96 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Output text through the user's pager, the way Git does.
+
+use std::env;
+use std::io::{self, IsTerminal as _, Write};
+use std::process::{Command, Stdio};
+
+use git_slides::git;
+
+/// Output `content` through the pager, or print it if there is no
+/// pager, or if it fails.
+///
+/// The pager is the one Git would use (`GIT_PAGER`, `core.pager`,
+/// `PAGER`), and it only kicks in if the output is a terminal.
+pub fn page_or_print(content: &str) {
+    if !io::stdout().is_terminal() || page(content).is_err() {
+        print!("{content}");
+    }
+}
+
+fn page(content: &str) -> io::Result<()> {
+    let pager = git::pager().unwrap_or_default();
+    if pager.is_empty() || pager == "cat" {
+        return Err(io::ErrorKind::Unsupported.into());
+    }
+
+    // Like Git, run it through the shell, so it can contain arguments
+    // (e.g., `less -RFX`).
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(&pager);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&pager);
+        command
+    };
+
+    // Git's defaults, unless the user has their own.
+    for (var, value) in [("LESS", "FRX"), ("LV", "-c"), ("LESSCHARSET", "utf-8")] {
+        if env::var_os(var).is_none() {
+            command.env(var, value);
+        }
+    }
+
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+
+    // Closing stdin (by dropping it) lets the pager know it has it all.
+    let (written, result) = match child.stdin.take() {
+        Some(mut stdin) => write_counting(&mut stdin, content.as_bytes()),
+        None => (0, Ok(())),
+    };
+
+    // Even if writing failed, the pager is running, don't leave it behind.
+    child.wait()?;
+
+    match result {
+        // The user may quit before reading everything, that's fine.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        // Printing it all again would repeat what the pager showed.
+        Err(e) if written == 0 => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Write all of `bytes`, and tell how many made it, even on error.
+fn write_counting(writer: &mut impl Write, mut bytes: &[u8]) -> (usize, io::Result<()>) {
+    let mut written = 0;
+    while !bytes.is_empty() {
+        match writer.write(bytes) {
+            Ok(0) => return (written, Err(io::ErrorKind::WriteZero.into())),
+            Ok(n) => {
+                written += n;
+                bytes = &bytes[n..];
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return (written, Err(e)),
+        }
+    }
+    (written, Ok(()))
+}
@@ -0,0 +1,193 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Full-screen rendering of slides, for `present`.
+
+use std::env;
+use std::fmt::Write as _;
+use std::process::{Command, Stdio};
+
+/// Widest the text gets, however wide the terminal.
+const MAX_TEXT_WIDTH: usize = 72;
+
+/// A slide, as shown full-screen.
+pub struct Slide<'a> {
+    /// Slide number, 1-indexed.
+    pub number: usize,
+    pub total: usize,
+    pub title: &'a str,
+    /// Message without title and trailers.
+    pub body: &'a str,
+}
+
+/// Columns and lines of the terminal.
+///
+/// `COLUMNS` and `LINES` take precedence, then `stty` is asked. Falls
+/// back to 80x24.
+#[cfg(not(tarpaulin_include))]
+pub fn size() -> (usize, usize) {
+    let from_env = |var| env::var(var).ok().and_then(|x| x.parse().ok());
+    if let (Some(columns), Some(lines)) = (from_env("COLUMNS"), from_env("LINES")) {
+        return (columns, lines);
+    }
+
+    // `stty` reads the size of the terminal on its stdin.
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output();
+    if let Ok(output) = output {
+        let output = String::from_utf8_lossy(&output.stdout);
+        let mut parts = output.split_whitespace().map(str::parse::<usize>);
+        if let (Some(Ok(lines)), Some(Ok(columns))) = (parts.next(), parts.next()) {
+            if lines > 0 && columns > 0 {
+                return (columns, lines);
+            }
+        }
+    }
+
+    (80, 24)
+}
+
+/// Render a slide for a terminal of `columns` x `lines`.
+///
+/// The screen is cleared, the title and body are centered as a block,
+/// and the slide number sits in the bottom right corner. The last line
+/// is left empty, for input.
+///
+/// Markdown-ish bodies are tidied up: `-`, `*` and `+` bullets become
+/// `•`, `#` is dropped from headings (which are emphasized instead),
+/// and paragraphs are wrapped.
+pub fn render(slide: &Slide, columns: usize, lines: usize, bold: bool) -> String {
+    let (bold, reset) = if bold {
+        ("\x1b[1m", "\x1b[m")
+    } else {
+        ("", "")
+    };
+    let width = columns.saturating_sub(4).clamp(1, MAX_TEXT_WIDTH);
+
+    let mut text: Vec<String> = wrap(slide.title, width)
+        .into_iter()
+        .map(|line| format!("{bold}{}{reset}", center(&line, width)))
+        .collect();
+    let body = body_lines(slide.body, width, bold, reset);
+    if !body.is_empty() {
+        text.push(String::new());
+        text.extend(body);
+    }
+
+    let margin = " ".repeat(columns.saturating_sub(width) / 2);
+    // Title and body, footer, and the input line.
+    let available = lines.saturating_sub(2);
+    let top = available.saturating_sub(text.len()) / 2;
+
+    let mut screen = String::from("\x1b[2J\x1b[H");
+    let mut used = 0;
+    for _ in 0..top {
+        screen.push('\n');
+        used += 1;
+    }
+    for line in text.iter().take(available.saturating_sub(top)) {
+        if line.is_empty() {
+            screen.push('\n');
+        } else {
+            let _ = writeln!(screen, "{margin}{line}");
+        }
+        used += 1;
+    }
+    for _ in used..available {
+        screen.push('\n');
+    }
+
+    let footer = format!("{}/{}", slide.number, slide.total);
+    let padding = columns.saturating_sub(footer.chars().count() + 1);
+    let _ = writeln!(screen, "{}{footer}", " ".repeat(padding));
+
+    screen
+}
+
+/// Format and wrap body lines.
+fn body_lines(body: &str, width: usize, bold: &str, reset: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut paragraph = String::new();
+
+    let flush = |paragraph: &mut String, lines: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            lines.extend(wrap(paragraph, width));
+            paragraph.clear();
+        }
+    };
+
+    for line in body.trim().lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut lines);
+            lines.push(String::new());
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            flush(&mut paragraph, &mut lines);
+            // Nested bullets keep their indentation.
+            let indent = " ".repeat(line.len() - line.trim_start().len());
+            let wrapped = wrap(item, width.saturating_sub(indent.len() + 2).max(1));
+            for (i, part) in wrapped.into_iter().enumerate() {
+                let bullet = if i == 0 { "• " } else { "  " };
+                lines.push(format!("{indent}{bullet}{part}"));
+            }
+        } else if trimmed.starts_with('#') {
+            flush(&mut paragraph, &mut lines);
+            let heading = trimmed.trim_start_matches('#').trim();
+            for part in wrap(heading, width) {
+                lines.push(format!("{bold}{part}{reset}"));
+            }
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(trimmed);
+        }
+    }
+    flush(&mut paragraph, &mut lines);
+
+    lines
+}
+
+/// Wrap text on spaces. Words longer than `width` are kept whole.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Center a line within `width`.
+fn center(line: &str, width: usize) -> String {
+    let padding = width.saturating_sub(line.chars().count()) / 2;
+    format!("{}{line}", " ".repeat(padding))
+}
@@ -0,0 +1,441 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use git_slides::git::Commit;
+use git_slides::{time, toml};
+
+/// Presentation state, persisted in `.git/git-slides` (by default).
+///
+/// The file is a small TOML document, versioned by a top-level
+/// `version` key:
+///
+/// ```toml
+/// version = 2
+/// branch = "main"
+/// head = "<commit hash>"
+/// started = 1729075845
+///
+/// [[slide]]
+/// hash = "<commit hash>"
+/// title = "Closures"
+/// ```
+///
+/// Slides resolved from history are cached the same way, in
+/// `[[cached-slide]]` tables, along with a top-level `cached-head`.
+/// Slide changes are logged in `[[transition]]` tables, and sections
+/// (e.g., one per branch) are listed in `[[section]]` tables, and
+/// hidden slides in `[[hidden]]` tables.
+///
+/// Unknown keys and tables are ignored, they may come from a newer
+/// minor addition. Version 1 files (a `<branch>:<hash>` first line,
+/// followed by `<key>:<value>` lines) are still read, and get written
+/// back as the current version.
+#[derive(Default, Clone)]
+pub struct Store {
+    /// Branch checked out when the presentation started, if any.
+    pub branch: Option<String>,
+    /// Last commit of the presentation.
+    pub head: String,
+    /// First commit of the presentation (defaults to the root commit).
+    pub from: Option<String>,
+    /// Follow all parents of merge commits, not only the first one.
+    pub all_parents: bool,
+    /// Only commits whose message matches this regex are slides.
+    pub pattern: Option<String>,
+    /// Explicit list of slides, instead of the history up to `head`.
+    pub slides: Option<Vec<Commit>>,
+    /// Groups of consecutive slides, in order. Empty if there are none.
+    pub sections: Vec<Section>,
+    /// Slides left out of the presentation (e.g., cut when running
+    /// late), in any order.
+    pub hidden: Vec<Commit>,
+    /// When the presentation started, in seconds since the Unix epoch.
+    pub started: Option<u64>,
+    /// Remote to publish the current slide to, on every navigation.
+    pub publish: Option<String>,
+    /// Linked worktree the presentation runs in, instead of the main
+    /// working tree.
+    pub worktree: Option<String>,
+    /// Changes stashed by `start --stash`, to restore on `stop`.
+    pub start_stash: Option<String>,
+    /// When the presentation stopped, in seconds since the Unix epoch.
+    /// Only set in the copy kept after stopping.
+    pub stopped: Option<u64>,
+    /// Current slide, 1-indexed. Only set in exported state (HEAD says
+    /// where the presentation is, otherwise).
+    pub position: Option<usize>,
+    /// Number of slides when the presentation started (or was last
+    /// rewritten), for `verify`.
+    pub slide_count: Option<usize>,
+    /// [`Store::checksum()`] of the slides, along with `slide_count`.
+    pub checksum: Option<String>,
+    /// Every slide change, oldest first.
+    pub transitions: Vec<Transition>,
+    /// Stashes of uncommitted changes made while navigating, oldest
+    /// first.
+    pub stashes: Vec<Stash>,
+    /// Slides resolved from history, to save a `git rev-list` on every
+    /// invocation. Only valid for the head it was resolved from.
+    pub cache: Option<HistoryCache>,
+}
+
+/// Slides resolved from the history up to `head`.
+#[derive(Clone)]
+pub struct HistoryCache {
+    pub head: String,
+    pub slides: Vec<Commit>,
+}
+
+/// Named group of consecutive slides (e.g., a chapter on its own
+/// branch). It lasts until the next section.
+#[derive(Clone)]
+pub struct Section {
+    pub title: String,
+    /// First slide of the section, 1-indexed.
+    pub first: usize,
+}
+
+/// Slide change, for pacing and timelines.
+#[derive(Clone)]
+pub struct Transition {
+    /// When the slide was reached, in seconds since the Unix epoch.
+    pub at: u64,
+    /// Slide number, 1-indexed.
+    pub slide: usize,
+    pub hash: String,
+    pub title: String,
+}
+
+/// Uncommitted changes, stashed by git-slides.
+#[derive(Clone)]
+pub struct Stash {
+    /// Stash commit (stable, unlike `stash@{n}`).
+    pub hash: String,
+    /// Slide the changes were made on, 1-indexed, if any.
+    pub slide: Option<usize>,
+}
+
+/// Transition, placed on the presentation's timeline.
+pub struct TimelineEntry<'a> {
+    pub transition: &'a Transition,
+    /// Seconds since the presentation started.
+    pub offset: u64,
+    /// Seconds spent on the slide, until the next transition (or now).
+    pub duration: u64,
+}
+
+/// Why a store file could not be read.
+#[derive(Debug)]
+pub enum Error {
+    /// The content is not a valid store, of any version.
+    Malformed,
+    /// The file was written by a newer version of git-slides.
+    UnsupportedVersion(i64),
+}
+
+impl Store {
+    /// Slides resolved from history, if cached for the current head.
+    pub fn cached_slides(&self) -> Option<&Vec<Commit>> {
+        self.cache
+            .as_ref()
+            .filter(|cache| cache.head == self.head)
+            .map(|cache| &cache.slides)
+    }
+
+    /// Fingerprint of a list of slides (their hashes, in order).
+    ///
+    /// 64-bit FNV-1a, in hex. It only needs to catch accidental changes,
+    /// and must stay the same across Rust versions (unlike `Hasher`).
+    pub fn checksum(slides: &[Commit]) -> String {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = OFFSET_BASIS;
+        for slide in slides {
+            for byte in slide.hash.bytes().chain([b'\n']) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(PRIME);
+            }
+        }
+        format!("{hash:016x}")
+    }
+
+    /// Every transition, with when it happened and how long it lasted.
+    ///
+    /// The last one lasts until the presentation stopped, or until now
+    /// if it is still in progress.
+    pub fn timeline(&self) -> Vec<TimelineEntry<'_>> {
+        let start = self
+            .started
+            .or_else(|| self.transitions.first().map(|transition| transition.at))
+            .unwrap_or_default();
+        let end = self.stopped.unwrap_or_else(time::now);
+
+        let transitions = &self.transitions;
+        transitions
+            .iter()
+            .enumerate()
+            .map(|(i, transition)| {
+                let left = transitions.get(i + 1).map_or(end, |next| next.at);
+                TimelineEntry {
+                    transition,
+                    offset: transition.at.saturating_sub(start),
+                    duration: left.saturating_sub(transition.at),
+                }
+            })
+            .collect()
+    }
+
+    /// Version of the format [`Self::to_string()`] writes.
+    pub const VERSION: i64 = 2;
+
+    /// Parse a store file, of any supported version.
+    ///
+    /// Returns the store, and the version it was read from.
+    pub fn parse(content: &str) -> Result<(Self, i64), Error> {
+        if Self::is_v1(content) {
+            return Self::parse_v1(content).map(|store| (store, 1));
+        }
+
+        let tables = toml::parse(content).map_err(|_| Error::Malformed)?;
+        let root = &tables[0];
+
+        match root.get_integer("version") {
+            Some(Self::VERSION) => (),
+            Some(version) => return Err(Error::UnsupportedVersion(version)),
+            None => return Err(Error::Malformed),
+        }
+
+        let mut store = Self {
+            branch: root.get_str("branch").map(String::from),
+            head: root.get_str("head").ok_or(Error::Malformed)?.to_string(),
+            from: root.get_str("from").map(String::from),
+            all_parents: root.get_bool("all-parents").unwrap_or(false),
+            pattern: root.get_str("match").map(String::from),
+            slides: None,
+            sections: Vec::new(),
+            hidden: Vec::new(),
+            started: root
+                .get_integer("started")
+                .and_then(|started| started.try_into().ok()),
+            publish: root.get_str("publish").map(String::from),
+            worktree: root.get_str("worktree").map(String::from),
+            start_stash: root.get_str("start-stash").map(String::from),
+            stopped: root
+                .get_integer("stopped")
+                .and_then(|stopped| stopped.try_into().ok()),
+            position: root
+                .get_integer("position")
+                .and_then(|position| position.try_into().ok()),
+            slide_count: root
+                .get_integer("slide-count")
+                .and_then(|count| count.try_into().ok()),
+            checksum: root.get_str("checksum").map(String::from),
+            transitions: Vec::new(),
+            stashes: Vec::new(),
+            cache: root.get_str("cached-head").map(|head| HistoryCache {
+                head: head.to_string(),
+                slides: Vec::new(),
+            }),
+        };
+
+        for table in tables.iter().skip(1) {
+            if !table.is_array_element {
+                continue;
+            }
+            let commit = || -> Result<Commit, Error> {
+                Ok(Commit {
+                    hash: table.get_str("hash").ok_or(Error::Malformed)?.to_string(),
+                    title: table.get_str("title").unwrap_or_default().to_string(),
+                })
+            };
+            match table.name.as_str() {
+                "slide" => store.slides.get_or_insert_with(Vec::new).push(commit()?),
+                "hidden" => store.hidden.push(commit()?),
+                "cached-slide" => {
+                    if let Some(cache) = &mut store.cache {
+                        cache.slides.push(commit()?);
+                    }
+                }
+                "transition" => {
+                    let Commit { hash, title } = commit()?;
+                    let integer = |key| {
+                        table
+                            .get_integer(key)
+                            .and_then(|value| value.try_into().ok())
+                            .ok_or(Error::Malformed)
+                    };
+                    store.transitions.push(Transition {
+                        at: integer("at")?,
+                        slide: integer("slide")?.try_into().map_err(|_| Error::Malformed)?,
+                        hash,
+                        title,
+                    });
+                }
+                "section" => store.sections.push(Section {
+                    title: table.get_str("title").unwrap_or_default().to_string(),
+                    first: table
+                        .get_integer("first")
+                        .and_then(|first| first.try_into().ok())
+                        .ok_or(Error::Malformed)?,
+                }),
+                "stash" => store.stashes.push(Stash {
+                    hash: table.get_str("hash").ok_or(Error::Malformed)?.to_string(),
+                    slide: table
+                        .get_integer("slide")
+                        .and_then(|slide| slide.try_into().ok()),
+                }),
+                _ => (),
+            }
+        }
+
+        Ok((store, Self::VERSION))
+    }
+
+    /// Version 1 starts with `<branch>:<hash>`, instead of a comment or
+    /// `key = value`.
+    fn is_v1(content: &str) -> bool {
+        content
+            .lines()
+            .next()
+            .is_some_and(|line| !line.starts_with('#') && !line.contains('='))
+    }
+
+    fn parse_v1(content: &str) -> Result<Self, Error> {
+        let mut lines = content.lines();
+
+        let (branch, head) = lines
+            .next()
+            .and_then(|line| line.trim().split_once(':'))
+            .ok_or(Error::Malformed)?;
+        let mut store = Self {
+            branch: (!branch.is_empty()).then(|| branch.to_string()),
+            head: head.to_string(),
+            ..Self::default()
+        };
+
+        for line in lines {
+            let (key, value) = line.split_once(':').unwrap_or((line, ""));
+            match key {
+                "from" => store.from = Some(value.to_string()),
+                "all-parents" => store.all_parents = true,
+                "match" => store.pattern = Some(value.to_string()),
+                "started" => store.started = value.parse().ok(),
+                "slide" => {
+                    let (hash, title) = value.split_once(' ').unwrap_or((value, ""));
+                    store.slides.get_or_insert_with(Vec::new).push(Commit {
+                        hash: hash.to_string(),
+                        title: title.to_string(),
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        Ok(store)
+    }
+}
+
+impl fmt::Display for Store {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# git-slides presentation state.")?;
+        writeln!(f, "version = {}", Self::VERSION)?;
+        if let Some(branch) = &self.branch {
+            writeln!(f, "branch = {}", toml::quote(branch))?;
+        }
+        writeln!(f, "head = {}", toml::quote(&self.head))?;
+        if let Some(from) = &self.from {
+            writeln!(f, "from = {}", toml::quote(from))?;
+        }
+        if self.all_parents {
+            writeln!(f, "all-parents = true")?;
+        }
+        if let Some(pattern) = &self.pattern {
+            writeln!(f, "match = {}", toml::quote(pattern))?;
+        }
+        if let Some(started) = self.started {
+            writeln!(f, "started = {started}")?;
+        }
+        if let Some(publish) = &self.publish {
+            writeln!(f, "publish = {}", toml::quote(publish))?;
+        }
+        if let Some(worktree) = &self.worktree {
+            writeln!(f, "worktree = {}", toml::quote(worktree))?;
+        }
+        if let Some(start_stash) = &self.start_stash {
+            writeln!(f, "start-stash = {}", toml::quote(start_stash))?;
+        }
+        if let Some(stopped) = self.stopped {
+            writeln!(f, "stopped = {stopped}")?;
+        }
+        if let Some(position) = self.position {
+            writeln!(f, "position = {position}")?;
+        }
+        if let Some(slide_count) = self.slide_count {
+            writeln!(f, "slide-count = {slide_count}")?;
+        }
+        if let Some(checksum) = &self.checksum {
+            writeln!(f, "checksum = {}", toml::quote(checksum))?;
+        }
+        if let Some(cache) = &self.cache {
+            writeln!(f, "cached-head = {}", toml::quote(&cache.head))?;
+        }
+        let slides = self.slides.iter().flatten().map(|slide| ("slide", slide));
+        let cached_slides = self
+            .cache
+            .iter()
+            .flat_map(|cache| &cache.slides)
+            .map(|slide| ("cached-slide", slide));
+        let hidden = self.hidden.iter().map(|slide| ("hidden", slide));
+        for (table, Commit { hash, title }) in slides.chain(cached_slides).chain(hidden) {
+            writeln!(f)?;
+            writeln!(f, "[[{table}]]")?;
+            writeln!(f, "hash = {}", toml::quote(hash))?;
+            writeln!(f, "title = {}", toml::quote(title))?;
+        }
+        for Section { title, first } in &self.sections {
+            writeln!(f)?;
+            writeln!(f, "[[section]]")?;
+            writeln!(f, "title = {}", toml::quote(title))?;
+            writeln!(f, "first = {first}")?;
+        }
+        for Transition {
+            at,
+            slide,
+            hash,
+            title,
+        } in &self.transitions
+        {
+            writeln!(f)?;
+            writeln!(f, "[[transition]]")?;
+            writeln!(f, "at = {at}")?;
+            writeln!(f, "slide = {slide}")?;
+            writeln!(f, "hash = {}", toml::quote(hash))?;
+            writeln!(f, "title = {}", toml::quote(title))?;
+        }
+        for Stash { hash, slide } in &self.stashes {
+            writeln!(f)?;
+            writeln!(f, "[[stash]]")?;
+            writeln!(f, "hash = {}", toml::quote(hash))?;
+            if let Some(slide) = slide {
+                writeln!(f, "slide = {slide}")?;
+            }
+        }
+        Ok(())
+    }
+}
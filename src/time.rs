@@ -0,0 +1,159 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Formatting of timestamps and durations, without a date library.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current time, in seconds since the Unix epoch.
+#[must_use]
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM:SS UTC`.
+///
+/// # Examples
+///
+/// ```
+/// use git_slides::time;
+///
+/// assert_eq!(time::format_timestamp(0), "1970-01-01 00:00:00 UTC");
+/// assert_eq!(time::format_timestamp(1_729_075_845), "2024-10-16 10:50:45 UTC");
+/// ```
+#[must_use]
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let seconds = timestamp % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    )
+}
+
+/// Format a Unix timestamp as RFC 3339, like `YYYY-MM-DDTHH:MM:SSZ`.
+///
+/// # Examples
+///
+/// ```
+/// use git_slides::time;
+///
+/// assert_eq!(time::format_rfc3339(1_729_075_845), "2024-10-16T10:50:45Z");
+/// ```
+#[must_use]
+pub fn format_rfc3339(timestamp: u64) -> String {
+    let days = timestamp / 86_400;
+    let seconds = timestamp % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    )
+}
+
+/// Format a duration in seconds, like `1h 02m 03s`.
+///
+/// # Examples
+///
+/// ```
+/// use git_slides::time;
+///
+/// assert_eq!(time::format_duration(42), "42s");
+/// assert_eq!(time::format_duration(125), "2m 05s");
+/// assert_eq!(time::format_duration(3723), "1h 02m 03s");
+/// ```
+#[must_use]
+pub fn format_duration(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Parse a duration like `90s`, `2m`, `1m30s` or `1h 15m`, in seconds.
+///
+/// A bare number is in seconds.
+///
+/// # Examples
+///
+/// ```
+/// use git_slides::time;
+///
+/// assert_eq!(time::parse_duration("90"), Some(90));
+/// assert_eq!(time::parse_duration("2m"), Some(120));
+/// assert_eq!(time::parse_duration("1m30s"), Some(90));
+/// assert_eq!(time::parse_duration("1h 15m"), Some(4500));
+/// assert_eq!(time::parse_duration("2 min"), None);
+/// assert_eq!(time::parse_duration(""), None);
+/// ```
+#[must_use]
+pub fn parse_duration(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    if let Ok(seconds) = duration.parse() {
+        return Some(seconds);
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in duration.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        let value: u64 = number.parse().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        number.clear();
+    }
+
+    // Trailing number without a unit (e.g., `1m30`).
+    if !number.is_empty() || duration.is_empty() {
+        return None;
+    }
+    Some(total)
+}
+
+/// Convert days since the Unix epoch to a `(year, month, day)` date.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html>.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,57 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! tmux integration, to turn it into a presenter display.
+
+use std::env;
+use std::process::{Command, Stdio};
+
+/// Whether we run inside tmux.
+pub fn is_inside() -> bool {
+    env::var_os("TMUX").is_some_and(|x| !x.is_empty())
+}
+
+/// Rename the current window.
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn rename_window(name: &str) -> bool {
+    tmux(&["rename-window", "--", name])
+}
+
+/// Send `keys` to pane `target` (e.g., `C-c Up Enter`, to re-run the
+/// last command of a shell).
+#[cfg(not(tarpaulin_include))] // Does not ignore 'return false'.
+#[must_use]
+pub fn send_keys(target: &str, keys: &[String]) -> bool {
+    let mut args = vec!["send-keys", "-t", target];
+    args.extend(keys.iter().map(String::as_str));
+    tmux(&args)
+}
+
+fn tmux(args: &[&str]) -> bool {
+    let status = Command::new("tmux")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    let Ok(status) = status else {
+        return false;
+    };
+
+    status.success()
+}
@@ -0,0 +1,251 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal TOML subset, for git-slides' own files.
+//!
+//! Supported are `key = value` pairs, with string, integer and boolean
+//! values, `[table]` headers, and `[[array]]` of tables headers. Keys
+//! are bare (`A-Za-z0-9_-`). Comments (`#`) are only allowed on their
+//! own line. That's enough to write state files that stay readable,
+//! without pulling in a dependency.
+//!
+//! # Examples
+//!
+//! ```
+//! use git_slides::toml::{self, Value};
+//!
+//! let tables = toml::parse(
+//!     r#"
+//! version = 2
+//! head = "a1b2c3d"
+//!
+//! [[slide]]
+//! title = "Closures"
+//! "#,
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(tables[0].get("version"), Some(&Value::Integer(2)));
+//! assert_eq!(tables[1].name, "slide");
+//! assert_eq!(tables[1].get_str("title"), Some("Closures"));
+//! ```
+
+use std::fmt::{self, Write as _};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(string) => write!(f, "{}", quote(string)),
+            Self::Integer(integer) => write!(f, "{integer}"),
+            Self::Boolean(boolean) => write!(f, "{boolean}"),
+        }
+    }
+}
+
+/// Key-value pairs, under a header (or at the top, unnamed).
+#[derive(Debug, Default)]
+pub struct Table {
+    /// Header name, empty for the top-level table.
+    pub name: String,
+    /// Whether the header is `[[name]]` (`true`) or `[name]` (`false`).
+    pub is_array_element: bool,
+    /// Entries, in order.
+    pub entries: Vec<(String, Value)>,
+}
+
+impl Table {
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .rev()
+            .find_map(|(k, value)| (k == key).then_some(value))
+    }
+
+    #[must_use]
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn get_integer(&self, key: &str) -> Option<i64> {
+        match self.get(key)? {
+            Value::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            Value::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+}
+
+/// Syntax error, with the 1-indexed line it occurred on.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Parse `content` into tables, in order of appearance.
+///
+/// The first table is always the top-level one (possibly empty).
+///
+/// # Errors
+///
+/// Returns an error if a line is not a comment, a header, or a valid
+/// `key = value` pair.
+pub fn parse(content: &str) -> Result<Vec<Table>, Error> {
+    let mut tables = vec![Table::default()];
+
+    for (i, line) in content.lines().enumerate() {
+        let error = |message| Error {
+            line: i + 1,
+            message,
+        };
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix("[[") {
+            let name = name.strip_suffix("]]").ok_or(error("Unclosed header."))?;
+            tables.push(Table {
+                name: parse_key(name).ok_or(error("Bad header name."))?,
+                is_array_element: true,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name.strip_suffix(']').ok_or(error("Unclosed header."))?;
+            tables.push(Table {
+                name: parse_key(name).ok_or(error("Bad header name."))?,
+                is_array_element: false,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or(error("Expected '='."))?;
+        let key = parse_key(key).ok_or(error("Bad key."))?;
+        let value = parse_value(value.trim()).ok_or(error("Bad value."))?;
+
+        // There is always a top-level table.
+        let last = tables.len() - 1;
+        tables[last].entries.push((key, value));
+    }
+
+    Ok(tables)
+}
+
+fn parse_key(key: &str) -> Option<String> {
+    let key = key.trim();
+    let is_valid = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    is_valid.then(|| key.to_string())
+}
+
+fn parse_value(value: &str) -> Option<Value> {
+    match value {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        value if value.starts_with('"') => parse_string(value).map(Value::String),
+        value => value.parse().ok().map(Value::Integer),
+    }
+}
+
+/// Parse a basic string (`"..."`), with escape sequences.
+fn parse_string(value: &str) -> Option<String> {
+    let mut chars = value.strip_prefix('"')?.chars();
+    let mut string = String::with_capacity(value.len());
+
+    loop {
+        match chars.next()? {
+            '"' => break,
+            '\\' => string.push(match chars.next()? {
+                '"' => '"',
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                }
+                _ => return None,
+            }),
+            c => string.push(c),
+        }
+    }
+
+    // Nothing allowed after the closing quote.
+    chars.as_str().is_empty().then_some(string)
+}
+
+/// Quote `string` as a basic string, escaping what needs to be.
+///
+/// # Examples
+///
+/// ```
+/// use git_slides::toml;
+///
+/// assert_eq!(toml::quote(r#"Say "hi""#), r#""Say \"hi\"""#);
+/// ```
+#[must_use]
+pub fn quote(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04X}", u32::from(c));
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,185 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Self-update, from the prebuilt binaries of GitHub releases.
+//!
+//! Downloads go through `curl`, and checksums through `sha256sum` (or
+//! `shasum` on macOS), so we need neither an HTTP nor a crypto library.
+//!
+//! Release `X.Y.Z` has a binary `git-slides-X.Y.Z-<target>` per target
+//! triple, each with a `git-slides-X.Y.Z-<target>.sha256` checksum.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const RELEASES_URL: &str = "https://github.com/qrichert/git-slides/releases";
+
+/// Releases page, overridden by `GIT_SLIDES_RELEASES_URL` (e.g., for a
+/// mirror on a workshop network).
+pub fn releases_url() -> String {
+    env::var("GIT_SLIDES_RELEASES_URL")
+        .ok()
+        .filter(|x| !x.is_empty())
+        .map_or_else(
+            || RELEASES_URL.to_string(),
+            |x| x.trim_end_matches('/').to_string(),
+        )
+}
+
+/// Version of the latest release, from where `<releases>/latest`
+/// redirects to (`<releases>/tag/X.Y.Z`).
+///
+/// # Errors
+///
+/// Errors if the releases page cannot be reached, or doesn't redirect
+/// to a release.
+pub fn latest_version(releases_url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", "--head"])
+        .args(["--output", if cfg!(windows) { "NUL" } else { "/dev/null" }])
+        .args(["--write-out", "%{url_effective}"])
+        .arg(format!("{releases_url}/latest"))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| String::from("Did not find curl executable."))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout);
+    match url.rsplit_once("/tag/") {
+        Some((_, version)) if !version.is_empty() => Ok(version.to_string()),
+        _ => Err(format!("No release found at '{releases_url}'.")),
+    }
+}
+
+/// Install the binary of release `version` over `exe`.
+///
+/// The binary is verified against its checksum before replacing
+/// anything, and replaces `exe` atomically (on Unix).
+///
+/// # Errors
+///
+/// Errors if a download fails, if the checksum doesn't match, or if
+/// `exe` cannot be replaced.
+pub fn install(releases_url: &str, version: &str, exe: &Path) -> Result<(), String> {
+    let asset = format!(
+        "git-slides-{version}-{}{}",
+        env!("GIT_SLIDES_BUILD_TARGET"),
+        env::consts::EXE_SUFFIX
+    );
+    let url = format!("{releases_url}/download/{version}/{asset}");
+
+    let checksum = download(&format!("{url}.sha256"), None)?;
+    // `<hash>  <file>`, like `sha256sum` outputs.
+    let expected = String::from_utf8_lossy(&checksum)
+        .split_whitespace()
+        .next()
+        .map(str::to_lowercase)
+        .ok_or_else(|| format!("Empty checksum file for '{asset}'."))?;
+
+    // Next to `exe`, so the rename doesn't cross file systems.
+    let new = sibling(exe, ".new");
+    let result = download(&url, Some(&new))
+        .and_then(|_| sha256(&new))
+        .and_then(|actual| {
+            if actual == expected {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Checksum mismatch for '{asset}' (expected {expected}, got {actual})."
+                ))
+            }
+        })
+        .and_then(|()| replace(&new, exe));
+    if result.is_err() {
+        let _ = fs::remove_file(&new);
+    }
+    result
+}
+
+/// Download `url` to `path`, or return it if `path` is `None`.
+fn download(url: &str, path: Option<&Path>) -> Result<Vec<u8>, String> {
+    let mut command = Command::new("curl");
+    command.args(["--fail", "--silent", "--show-error", "--location"]);
+    if let Some(path) = path {
+        command.arg("--output").arg(path);
+    }
+    let output = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| String::from("Did not find curl executable."))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Could not download '{url}': {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// SHA-256 of the file at `path`, in lowercase hex.
+fn sha256(path: &Path) -> Result<String, String> {
+    let tools: [(&str, &[&str]); 2] = [("sha256sum", &[]), ("shasum", &["-a", "256"])];
+    for (program, args) in tools {
+        let Ok(output) = Command::new(program)
+            .args(args)
+            .arg(path)
+            .stdin(Stdio::null())
+            .output()
+        else {
+            continue;
+        };
+        if let Some(hash) = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .next()
+            .filter(|_| output.status.success())
+        {
+            return Ok(hash.to_lowercase());
+        }
+    }
+    Err(String::from("Did not find sha256sum or shasum executable."))
+}
+
+/// Make `new` executable, and move it over `exe`.
+fn replace(new: &Path, exe: &Path) -> Result<(), String> {
+    let error = |err: std::io::Error| format!("Cannot replace '{}': {err}.", exe.display());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        fs::set_permissions(new, fs::Permissions::from_mode(0o755)).map_err(error)?;
+    }
+    // Windows doesn't let a running executable be overwritten, but it
+    // lets it be renamed.
+    #[cfg(windows)]
+    {
+        let old = sibling(exe, ".old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).map_err(error)?;
+    }
+
+    fs::rename(new, exe).map_err(error)
+}
+
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(extension);
+    PathBuf::from(sibling)
+}
@@ -0,0 +1,92 @@
+// git-slides — Navigate through Git commits like presentation slides.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal HTTP server, for `web`.
+//!
+//! It only answers `GET` requests, one at a time, which is all a local
+//! view of the current slide needs.
+
+use std::io::{self, BufRead as _, BufReader, Write as _};
+use std::net::{TcpListener, TcpStream};
+
+/// Response to a request.
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    pub fn ok(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    pub fn not_found() -> Self {
+        Self {
+            status: 404,
+            content_type: "text/plain; charset=utf-8",
+            body: String::from("Not found.\n"),
+        }
+    }
+}
+
+/// Serve requests on `listener` forever, answering them with `handle`,
+/// which gets the requested path.
+pub fn serve(listener: &TcpListener, handle: impl Fn(&str) -> Response) {
+    for stream in listener.incoming().flatten() {
+        // A misbehaving client must not bring the server down.
+        let _ = respond(stream, &handle);
+    }
+}
+
+fn respond(mut stream: TcpStream, handle: &impl Fn(&str) -> Response) -> io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    // `GET /path HTTP/1.1`.
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => handle(path.split('?').next().unwrap_or(path)),
+        _ => Response {
+            status: 405,
+            content_type: "text/plain; charset=utf-8",
+            body: String::from("Method not allowed.\n"),
+        },
+    };
+
+    let reason = match response.status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\n\
+         Content-Type: {}\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-store\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body,
+    )?;
+    stream.flush()
+}
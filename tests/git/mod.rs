@@ -16,6 +16,7 @@
 
 use std::env;
 use std::fs;
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
@@ -66,6 +67,51 @@ pub fn init(dir: &str) -> PathBuf {
     dir
 }
 
+pub fn clone_bare(src: &Path, dir: &str) -> PathBuf {
+    let dir = PathBuf::from(TMP_DIR).join(dir);
+    println!("git clone --bare: '{}'.", dir.display());
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    Command::new("git")
+        .arg("clone")
+        .arg("--bare")
+        .arg(src)
+        .arg(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+
+    dir
+}
+
+/// Clone with only the last commit of history.
+pub fn clone_shallow(src: &Path, dir: &str) -> PathBuf {
+    let dir = PathBuf::from(TMP_DIR).join(dir);
+    println!("git clone --depth 1: '{}'.", dir.display());
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    Command::new("git")
+        .arg("clone")
+        .arg("--depth")
+        .arg("1")
+        // Local clones ignore `--depth`, unless given a URL.
+        .arg(format!("file://{}", src.display()))
+        .arg(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+
+    dir
+}
+
 pub fn commit(dir: &Path, message: &str) {
     Command::new("git")
         .arg("commit")
@@ -80,6 +126,44 @@ pub fn commit(dir: &Path, message: &str) {
         .unwrap();
 }
 
+pub fn commit_encoded(dir: &Path, message: &[u8], encoding: Option<&str>) {
+    // Commit a message as raw bytes, declaring `encoding` if any.
+    let mut command = Command::new("git");
+    if let Some(encoding) = encoding {
+        command
+            .arg("-c")
+            .arg(format!("i18n.commitEncoding={encoding}"));
+    }
+    let mut child = command
+        .arg("commit")
+        .arg("--allow-empty")
+        .arg("--file=-")
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(message).unwrap();
+    child.wait().unwrap();
+}
+
+pub fn commit_with_date(dir: &Path, message: &str, date: &str) {
+    Command::new("git")
+        .arg("commit")
+        .arg("--allow-empty")
+        .arg("--message")
+        .arg(message)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+}
+
 pub fn status(dir: &Path) -> String {
     // Returns the commit title.
     let output = Command::new("git")
@@ -129,3 +213,31 @@ pub fn has_stashed_changes(dir: &Path) -> bool {
 
     !String::from_utf8_lossy(&output.stdout).trim().is_empty()
 }
+
+pub fn add_submodule(dir: &Path, src: &Path, path: &str) {
+    Command::new("git")
+        .arg("submodule")
+        .arg("add")
+        .arg(src)
+        .arg(path)
+        .env("GIT_ALLOW_PROTOCOL", "file")
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+}
+
+pub fn exec(dir: &Path, args: &[&str]) -> String {
+    // Run any git command, and return its stdout.
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .unwrap();
+
+    String::from_utf8_lossy(&output.stdout).trim().to_owned()
+}
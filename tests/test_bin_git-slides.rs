@@ -18,7 +18,7 @@ mod git;
 
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 const GIT_SLIDES: &str = env!("CARGO_BIN_EXE_git-slides");
@@ -30,12 +30,30 @@ struct Output {
 }
 
 fn run(dir: &Path, args: &[&str]) -> Output {
+    run_with_env(dir, args, &[])
+}
+
+fn run_with_env(dir: &Path, args: &[&str], env: &[(&str, &str)]) -> Output {
     let mut output = Command::new(GIT_SLIDES);
 
+    // Messages are in English, whatever the locale of the machine.
+    output
+        .env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env_remove("LANG");
+    // Don't touch the tmux session running the tests, if any.
+    output.env_remove("TMUX");
+    output.env_remove("GIT_SLIDES_STORE");
+    output.env_remove("GIT_SLIDES_LOG");
+
     for arg in args {
         output.arg(arg);
     }
 
+    for (key, value) in env {
+        output.env(key, value);
+    }
+
     let output = output.current_dir(dir).output().unwrap();
 
     Output {
@@ -53,13 +71,15 @@ fn help() {
     assert_eq!(output.exit_code, 0);
     assert!(output.stdout.contains("-h, --help"));
     assert!(output.stdout.contains("-v, --version"));
-    assert!(output.stdout.contains("start [<ref>]"));
+    assert!(output.stdout.contains("--work-tree <path>"));
+    assert!(output.stdout.contains("start [<ref>...]"));
     assert!(output.stdout.contains("stop"));
     assert!(output.stdout.contains("next, n [<n>]"));
     assert!(output.stdout.contains("previous, p [<n>]"));
     assert!(output.stdout.contains("go <n>"));
     assert!(output.stdout.contains("status"));
     assert!(output.stdout.contains("list"));
+    assert!(output.stdout.contains("reorder"));
 }
 
 #[test]
@@ -95,6 +115,135 @@ fn version() {
     assert!(output.stdout.contains(env!("CARGO_PKG_VERSION")));
 }
 
+#[test]
+fn version_verbose() {
+    let output = run(&env::temp_dir(), &["--version", "--verbose"]);
+
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert!(lines[0].ends_with(env!("CARGO_PKG_VERSION")));
+    assert!(lines[1].starts_with("commit:     "));
+    assert!(lines[2].starts_with("build date: ") && lines[2].ends_with(" UTC"));
+    assert_eq!(
+        lines[3],
+        format!("target:     {}", env!("GIT_SLIDES_BUILD_TARGET"))
+    );
+    assert!(lines[4].starts_with("features:   "));
+
+    // Either order.
+    let output = run(&env::temp_dir(), &["--verbose", "-v"]);
+    assert_eq!(output.stdout.lines().count(), 5);
+
+    let output = run(&env::temp_dir(), &["--version"]);
+    assert_eq!(output.stdout.lines().count(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn self_update() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("self_update");
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir(&dir).unwrap();
+
+    // Work on a copy, the other tests need the real one.
+    let exe = dir.join("git-slides");
+    fs::copy(GIT_SLIDES, &exe).unwrap();
+
+    let release = dir.join("releases/download/9.9.9");
+    fs::create_dir_all(&release).unwrap();
+    let asset = format!("git-slides-9.9.9-{}", env!("GIT_SLIDES_BUILD_TARGET"));
+    fs::write(release.join(&asset), "#!/bin/sh\necho 'git-slides 9.9.9'\n").unwrap();
+    let releases_url = format!("file://{}", dir.join("releases").display());
+
+    let self_update = |version: &str| {
+        let output = Command::new(&exe)
+            .args(["self-update", version])
+            .env("GIT_SLIDES_RELEASES_URL", &releases_url)
+            .output()
+            .unwrap();
+        (
+            output.status.code().unwrap(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+    let version = || {
+        let output = Command::new(&exe).arg("--version").output().unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // Bad checksum, nothing is replaced.
+    fs::write(
+        release.join(format!("{asset}.sha256")),
+        format!("{}  {asset}\n", "0".repeat(64)),
+    )
+    .unwrap();
+    let (exit_code, _, stderr) = self_update("9.9.9");
+    assert_eq!(exit_code, 1);
+    assert!(stderr.starts_with(&format!("error: Checksum mismatch for '{asset}'")));
+    assert!(!dir.join("git-slides.new").exists());
+    assert_eq!(
+        version(),
+        format!("git-slides {}\n", env!("CARGO_PKG_VERSION"))
+    );
+
+    // No such release.
+    let (exit_code, _, stderr) = self_update("9.9.8");
+    assert_eq!(exit_code, 1);
+    assert!(stderr.starts_with("error: Could not download '"));
+
+    let checksum = Command::new("sha256sum")
+        .arg(release.join(&asset))
+        .current_dir(&release)
+        .output()
+        .unwrap();
+    let checksum = String::from_utf8_lossy(&checksum.stdout);
+    let checksum = checksum.split_whitespace().next().unwrap();
+    fs::write(
+        release.join(format!("{asset}.sha256")),
+        format!("{checksum}  {asset}\n"),
+    )
+    .unwrap();
+    let (exit_code, stdout, _) = self_update("9.9.9");
+    assert_eq!(exit_code, 0);
+    assert_eq!(
+        stdout,
+        "Downloading git-slides 9.9.9...\nUpdated to 9.9.9.\n"
+    );
+    assert_eq!(version(), "git-slides 9.9.9\n");
+}
+
+#[test]
+fn generate_man() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("generate_man");
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // Works outside of git repository.
+    let output = run(&env::temp_dir(), &["generate-man", dir.to_str().unwrap()]);
+    assert_eq!(output.exit_code, 0);
+
+    let page = fs::read_to_string(dir.join("git-slides.1")).unwrap();
+    println!("{page}");
+    assert!(page.starts_with(".TH GIT-SLIDES 1 "));
+    assert!(page.contains(".SH SYNOPSIS\n.B git-slides\n[<options>] <command> [<args>]\n"));
+    assert!(page.contains(".SH COMMANDS\n.TP\n.B start [<ref>...]\nStart presentation."));
+    // Options of a command are nested under it.
+    assert!(page.contains(".RS\n.TP\n.B \\-\\-from <ref>\n"));
+    // Long entries have their description on the next lines.
+    assert!(page.contains(".B \\-\\-tag\\-order <order>\nOrder tags by"));
+    assert!(page.contains(".SH OPTIONS\n"));
+    assert!(page.contains(".B \\-\\-[no\\-]recurse\\-submodules\nUpdate submodules"));
+
+    let output = run(&env::temp_dir(), &["generate-man"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Need a directory.\n");
+}
+
 #[test]
 fn git_not_in_path() {
     let output = Command::new(GIT_SLIDES).env("PATH", "").output().unwrap();
@@ -117,6 +266,144 @@ fn not_a_git_directory() {
     );
 }
 
+#[test]
+fn bare_repository() {
+    let dir = git::init("bare_repository_src");
+    git::commit(&dir, "Slide 1");
+    let bare = git::clone_bare(&dir, "bare_repository");
+
+    let output = run(&bare, &["start"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Cannot present from a bare repository.\nUse '--work-tree <path>' to provide a working tree.\n"
+    );
+}
+
+#[test]
+fn bare_repository_with_work_tree() {
+    let dir = git::init("bare_repository_with_work_tree_src");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    let bare = git::clone_bare(&dir, "bare_repository_with_work_tree");
+
+    let work_tree = bare.join("work-tree");
+    fs::create_dir(&work_tree).unwrap();
+    let work_tree = work_tree.to_str().unwrap();
+
+    let output = run(&bare, &["--work-tree", work_tree, "start"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/2"));
+    assert_eq!(git::status(&bare), "Slide 1");
+    assert!(bare.join("git-slides").is_file()); // Store in bare git dir.
+
+    let output = run(&bare, &[&format!("--work-tree={work_tree}"), "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&bare), "Slide 2");
+}
+
+#[test]
+fn work_tree_missing_path() {
+    let output = run(&env::temp_dir(), &["--work-tree"]);
+
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(
+        output.stderr,
+        "fatal: Option '--work-tree' requires a path.\n"
+    );
+}
+
+#[test]
+fn change_directory() {
+    let dir = git::init("change_directory");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    let parent = dir.parent().unwrap();
+
+    let output = run(parent, &["-C", "change_directory", "start"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&env::temp_dir(), &["-C", dir.to_str().unwrap(), "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    let output = run(parent, &["-C", "nonexistent", "next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "fatal: Cannot change to 'nonexistent'.\n");
+
+    let output = run(parent, &["-C"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Option '-C' requires a path.\n");
+}
+
+fn init_with_submodule(name: &str) -> (PathBuf, String, String) {
+    let sub = git::init(&format!("{name}_sub"));
+    git::commit(&sub, "Sub 1");
+    let sub_1 = git::exec(&sub, &["rev-parse", "HEAD"]);
+    git::commit(&sub, "Sub 2");
+    let sub_2 = git::exec(&sub, &["rev-parse", "HEAD"]);
+
+    let dir = git::init(name);
+    git::exec(&dir, &["config", "protocol.file.allow", "always"]);
+    git::add_submodule(&dir, &sub, "sub");
+    git::exec(&dir.join("sub"), &["checkout", &sub_1]);
+    git::exec(&dir, &["add", "sub"]);
+    git::commit(&dir, "Slide 1");
+    git::exec(&dir.join("sub"), &["checkout", &sub_2]);
+    git::exec(&dir, &["add", "sub"]);
+    git::commit(&dir, "Slide 2");
+
+    (dir, sub_1, sub_2)
+}
+
+#[test]
+fn recurse_submodules() {
+    let (dir, sub_1, sub_2) = init_with_submodule("recurse_submodules");
+
+    let output = run(&dir, &["--recurse-submodules", "start"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::exec(&dir.join("sub"), &["rev-parse", "HEAD"]), sub_1);
+
+    run(&dir, &["--recurse-submodules", "next"]);
+    assert_eq!(git::exec(&dir.join("sub"), &["rev-parse", "HEAD"]), sub_2);
+}
+
+#[test]
+fn recurse_submodules_from_config() {
+    let (dir, sub_1, _) = init_with_submodule("recurse_submodules_from_config");
+    git::exec(&dir, &["config", "slides.recurseSubmodules", "true"]);
+
+    run(&dir, &["start"]);
+    assert_eq!(git::exec(&dir.join("sub"), &["rev-parse", "HEAD"]), sub_1);
+}
+
+#[test]
+fn no_recurse_submodules_by_default() {
+    let (dir, _, sub_2) = init_with_submodule("no_recurse_submodules_by_default");
+
+    run(&dir, &["start"]);
+    // Submodule is left stale.
+    assert_eq!(git::exec(&dir.join("sub"), &["rev-parse", "HEAD"]), sub_2);
+}
+
+#[test]
+fn lfs_bad_config_value() {
+    let dir = git::init("lfs_bad_config_value");
+    git::commit(&dir, "Slide 1");
+    git::exec(&dir, &["config", "slides.lfs", "always"]);
+
+    let output = run(&dir, &["start"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Bad config value for 'slides.lfs': 'always'.\n"
+    );
+}
+
 #[test]
 fn start_regular() {
     let dir = git::init("start_regular");
@@ -168,710 +455,4232 @@ fn start_at_ref() {
 }
 
 #[test]
-fn start_bad_ref() {
-    let dir = git::init("start_bad_ref");
+fn start_at_slide() {
+    let dir = git::init("start_at_slide");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
 
-    // Will always work, as there are no commits in this repo.
-    let output = run(&dir, &["start", "abcdefg"]);
+    let output = run(&dir, &["start", "--at", "2"]);
 
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "error: Bad ref input: 'abcdefg'.\n");
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(output.stdout.contains("* 2/3"));
 }
 
 #[test]
-fn start_in_dirty_working_directory() {
-    let dir = git::init("start_in_dirty_working_directory");
-    git::commit(&dir, "Initial commit");
-
-    let new_file = dir.join("hello.txt");
-
-    let _ = fs::write(&new_file, ":)");
-    git::add(&dir, &new_file);
-
-    let output = run(&dir, &["start"]);
+fn start_at_bad_slide() {
+    let dir = git::init("start_at_bad_slide");
+    git::commit(&dir, "Slide 1");
 
+    let output = run(&dir, &["start", "--at", "2"]);
     assert_eq!(output.exit_code, 1);
     assert_eq!(
         output.stderr,
-        "error: Working directory contains uncommitted changes.\n"
+        "error: Bad slide index. Slide 2 does not exist.\n"
     );
+    // Not started.
+    assert!(!dir.join(".git/git-slides").is_file());
+
+    let output = run(&dir, &["start", "--at", "x"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Bad slide number: 'x'.\n");
 }
 
 #[test]
-fn start_in_half_dirty_working_directory() {
-    let dir = git::init("start_in_half_dirty_working_directory");
-    git::commit(&dir, "Initial commit");
-
-    let new_file = dir.join("hello.txt");
-
-    let _ = fs::write(new_file, ":)");
-
-    // This version doesn't 'git add' the file; it remains untracked.
+fn start_at_current() {
+    let dir = git::init("start_at_current");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::exec(&dir, &["checkout", "--quiet", "HEAD~"]);
 
-    let output = run(&dir, &["start"]);
+    let output = run(&dir, &["start", "main", "--at-current"]);
 
     assert_eq!(output.exit_code, 0);
-    assert!(output.stdout.starts_with("Presentation started at"));
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(output.stdout.contains("* 2/3"));
 }
 
 #[test]
-fn start_in_repo_without_commits() {
-    let dir = git::init("start_in_repo_without_commits");
+fn start_at_current_not_a_slide() {
+    let dir = git::init("start_at_current_not_a_slide");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
 
-    let output = run(&dir, &["start"]);
+    let output = run(&dir, &["start", "HEAD~", "--at-current"]);
 
     assert_eq!(output.exit_code, 1);
     assert_eq!(
         output.stderr,
-        "error: No HEAD commit. Please provide a valid ref.\n"
+        "error: Current HEAD not part of presentation.\n"
     );
 }
 
-#[test]
-fn all_methods_requiring_presentation_to_be_started() {
-    let dir = git::init("all_methods_requiring_presentation_to_be_started");
+fn init_with_merged_branch(name: &str) -> PathBuf {
+    let dir = git::init(name);
+    git::commit(&dir, "Slide 1");
+    git::exec(&dir, &["switch", "--create", "topic"]);
+    git::commit(&dir, "Topic 1");
+    git::commit(&dir, "Topic 2");
+    git::exec(&dir, &["switch", "main"]);
+    git::exec(
+        &dir,
+        &["merge", "--no-ff", "--message", "Merge topic", "topic"],
+    );
+    dir
+}
 
-    let output = run(&dir, &["stop"]);
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+#[test]
+fn start_first_parent_by_default() {
+    let dir = init_with_merged_branch("start_first_parent_by_default");
 
-    let output = run(&dir, &["next"]);
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+    let output = run(&dir, &["start"]);
 
-    let output = run(&dir, &["previous"]);
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/2"));
+    assert!(output.stdout.contains("Merge topic"));
+    assert!(!output.stdout.contains("Topic 1"));
+}
 
-    let output = run(&dir, &["go", "1"]);
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+#[test]
+fn start_all_parents() {
+    let dir = init_with_merged_branch("start_all_parents");
 
-    let output = run(&dir, &["status"]);
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+    let output = run(&dir, &["start", "--all-parents"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/4"));
 
     let output = run(&dir, &["list"]);
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+    assert!(output.stdout.contains("2/4"));
+    assert!(output.stdout.contains("Topic 1"));
+    assert!(output.stdout.contains("Topic 2"));
+    assert!(output.stdout.contains("Merge topic"));
 }
 
 #[test]
-fn next_regular() {
-    let dir = git::init("next_regular");
+fn start_all_parents_from_config() {
+    let dir = init_with_merged_branch("start_all_parents_from_config");
+    git::exec(&dir, &["config", "slides.allParents", "true"]);
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/4"));
+}
+
+#[test]
+fn start_from_ref() {
+    let dir = git::init("start_from_ref");
+    git::commit(&dir, "Setup 1");
+    git::commit(&dir, "Setup 2");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
 
-    run(&dir, &["start"]);
+    let output = run(&dir, &["start", "--from", "HEAD~"]);
+    assert_eq!(output.exit_code, 0);
     assert_eq!(git::status(&dir), "Slide 1");
+    assert!(output.stdout.contains("* 1/2"));
+    assert!(!output.stdout.contains("Setup"));
 
     let output = run(&dir, &["next"]);
     assert_eq!(git::status(&dir), "Slide 2");
-    assert_eq!(output.exit_code, 0);
-    assert!(!output
-        .stdout
-        .contains("You've reached the end of the presentation.\n"));
-
-    let output = run(&dir, &["next"]);
-    assert_eq!(git::status(&dir), "Slide 3");
-    assert_eq!(output.exit_code, 0);
-    assert!(output
-        .stdout
-        .contains("You've reached the end of the presentation.\n"));
+    assert!(output.stdout.contains("* 2/2"));
 }
 
 #[test]
-fn next_shorthand() {
-    let dir = git::init("next_shorthand");
+fn start_from_ref_up_to_ref() {
+    let dir = git::init("start_from_ref_up_to_ref");
+    git::commit(&dir, "Setup 1");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Teardown");
 
-    run(&dir, &["start"]);
-    assert_eq!(git::status(&dir), "Slide 1");
-
-    let output = run(&dir, &["n"]);
-    assert_eq!(git::status(&dir), "Slide 2");
+    let output = run(&dir, &["start", "--from=HEAD~2", "HEAD~"]);
     assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/2"));
+    assert!(!output.stdout.contains("Setup"));
+    assert!(!output.stdout.contains("Teardown"));
 }
 
 #[test]
-fn next_with_offset() {
-    let dir = git::init("next_with_offset");
+fn start_from_not_an_ancestor() {
+    let dir = git::init("start_from_not_an_ancestor");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
 
-    run(&dir, &["start"]);
-    assert_eq!(git::status(&dir), "Slide 1");
+    let output = run(&dir, &["start", "--from", "HEAD", "HEAD~"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: 'HEAD' is not an ancestor of the last slide.\n"
+    );
+}
 
-    let output = run(&dir, &["next", "2"]);
-    assert_eq!(git::status(&dir), "Slide 3");
-    assert_eq!(output.exit_code, 0);
-    assert!(!output
-        .stdout
-        .contains("You've reached the end of the presentation.\n"));
+#[test]
+fn start_from_missing_value() {
+    let dir = git::init("start_from_missing_value");
+    git::commit(&dir, "Slide 1");
 
-    // Does not overflow.
-    let output = run(&dir, &["next", "10"]);
-    assert_eq!(git::status(&dir), "Slide 4");
+    let output = run(&dir, &["start", "--from"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Option '--from' requires a value.\n");
+}
+
+#[test]
+fn start_match() {
+    let dir = git::init("start_match");
+    git::commit(&dir, "slide: Introduction");
+    git::commit(&dir, "Fix typo");
+    git::commit(&dir, "slide: Closures");
+    git::commit(&dir, "Bump dependencies");
+
+    let output = run(&dir, &["start", "--match", "^slide:"]);
     assert_eq!(output.exit_code, 0);
-    assert!(output
-        .stdout
-        .contains("You've reached the end of the presentation.\n"));
+    assert_eq!(git::status(&dir), "slide: Introduction");
+    assert!(output.stdout.contains("* 1/2"));
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("slide: Closures"));
+    assert!(!output.stdout.contains("Fix typo"));
+    assert!(!output.stdout.contains("Bump dependencies"));
+
+    // Infrastructure commits are skipped.
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "slide: Closures");
 }
 
 #[test]
-fn next_with_overlow() {
-    let dir = git::init("next_with_overflow");
+fn start_match_nothing() {
+    let dir = git::init("start_match_nothing");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
-
-    run(&dir, &["start"]);
-    assert_eq!(git::status(&dir), "Slide 1");
-
-    run(&dir, &["next"]);
-    run(&dir, &["next"]);
-    run(&dir, &["next"]);
-    let output = run(&dir, &["next"]);
 
-    assert_eq!(git::status(&dir), "Slide 4");
-    assert_eq!(output.exit_code, 0);
-    assert!(output
-        .stdout
-        .contains("You've reached the end of the presentation.\n"));
+    let output = run(&dir, &["start", "--match=^slide:"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: No commit matches '^slide:'.\n");
+    assert!(!dir.join(".git/git-slides").is_file());
 }
 
 #[test]
-fn next_error_getting_current_commit() {
-    let dir = git::init("next_error_getting_current_commit");
+fn start_tags() {
+    let dir = git::init("start_tags");
+    git::commit(&dir, "Setup");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Not in presentation");
+    git::exec(&dir, &["tag", "slide-1"]);
+    git::commit(&dir, "Work in progress");
+    git::commit(&dir, "Slide 2");
+    git::exec(&dir, &["tag", "slide-2"]);
+    git::commit(&dir, "Slide 10");
+    git::exec(
+        &dir,
+        &["tag", "--annotate", "--message", "Last", "slide-10"],
+    );
+    git::exec(&dir, &["tag", "unrelated"]);
 
-    run(&dir, &["start", "HEAD~"]);
+    let output = run(&dir, &["start", "--tags", "slide-*"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert!(output.stdout.contains("* 1/3"));
 
-    git::checkout(&dir, "main"); // Commit not in presentation.
+    let output = run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(output.stdout.contains("* 2/3"));
 
+    // Natural order, annotated tags are peeled.
     let output = run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 10");
+    assert!(output.stdout.contains("* 3/3"));
 
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(
-        output.stderr,
-        "error: Current HEAD not part of presentation.\n"
-    );
+    let output = run(&dir, &["list"]);
+    assert!(!output.stdout.contains("Setup"));
+    assert!(!output.stdout.contains("Work in progress"));
+
+    run(&dir, &["stop"]);
+    assert_eq!(git::status(&dir), "Slide 10");
 }
 
 #[test]
-fn previous_regular() {
-    let dir = git::init("previous_regular");
-    git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
-
-    run(&dir, &["start"]);
-    run(&dir, &["go", "3"]);
-    assert_eq!(git::status(&dir), "Slide 3");
-
-    let output = run(&dir, &["previous"]);
-    assert_eq!(git::status(&dir), "Slide 2");
+fn start_tags_by_date() {
+    let dir = git::init("start_tags_by_date");
+    git::commit_with_date(&dir, "Slide 1", "2024-01-01T12:00:00");
+    git::exec(&dir, &["tag", "b"]);
+    git::commit_with_date(&dir, "Slide 2", "2024-01-02T12:00:00");
+    git::exec(&dir, &["tag", "a"]);
+
+    let output = run(&dir, &["start", "--tags", "*", "--tag-order", "date"]);
     assert_eq!(output.exit_code, 0);
-    assert!(!output
-        .stdout
-        .contains("You're at the start of the presentation.\n"));
-
-    let output = run(&dir, &["previous"]);
     assert_eq!(git::status(&dir), "Slide 1");
-    assert_eq!(output.exit_code, 0);
-    assert!(output
-        .stdout
-        .contains("You're at the start of the presentation.\n"));
 }
 
 #[test]
-fn previous_shorthand() {
-    let dir = git::init("previous_shorthand");
+fn start_tags_no_match() {
+    let dir = git::init("start_tags_no_match");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-
-    run(&dir, &["start"]);
-    run(&dir, &["go", "2"]);
-    assert_eq!(git::status(&dir), "Slide 2");
 
-    let output = run(&dir, &["p"]);
-    assert_eq!(git::status(&dir), "Slide 1");
-    assert_eq!(output.exit_code, 0);
+    let output = run(&dir, &["start", "--tags=slide-*"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: No tag matches 'slide-*'.\n");
 }
 
 #[test]
-fn previous_with_offset() {
-    let dir = git::init("previous_with_offset");
+fn start_manifest() {
+    let dir = git::init("start_manifest");
     git::commit(&dir, "Slide 1");
+    git::exec(&dir, &["tag", "intro"]);
     git::commit(&dir, "Slide 2");
     git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
 
-    run(&dir, &["start"]);
-    run(&dir, &["go", "4"]);
-    assert_eq!(git::status(&dir), "Slide 4");
+    let manifest = dir.join("slides.txt");
+    fs::write(
+        &manifest,
+        "# Out of order.\n\nHEAD~1\nintro  Welcome!\n\nHEAD Last one\n",
+    )
+    .unwrap();
 
-    let output = run(&dir, &["previous", "2"]);
-    assert_eq!(git::status(&dir), "Slide 2");
+    let output = run(&dir, &["start", "--manifest", "slides.txt"]);
     assert_eq!(output.exit_code, 0);
-    assert!(!output
-        .stdout
-        .contains("You're at the start of the presentation.\n"));
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(output.stdout.contains("* 1/3"));
 
-    // Does not overflow.
-    let output = run(&dir, &["previous", "10"]);
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("1/3"));
+    assert!(output.stdout.contains("Slide 2\n"));
+    assert!(output.stdout.contains("Welcome!\n"));
+    assert!(output.stdout.contains("Last one\n"));
+    assert!(!output.stdout.contains("Slide 3"));
+
+    let output = run(&dir, &["next"]);
     assert_eq!(git::status(&dir), "Slide 1");
-    assert_eq!(output.exit_code, 0);
-    assert!(output
-        .stdout
-        .contains("You're at the start of the presentation.\n"));
+    assert!(output.stdout.contains("* 2/3"));
 }
 
 #[test]
-fn previous_with_overlow() {
-    let dir = git::init("previous_with_overflow");
+fn start_manifest_bad_refs() {
+    let dir = git::init("start_manifest_bad_refs");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
-
-    run(&dir, &["start"]);
-    run(&dir, &["go", "4"]);
-    assert_eq!(git::status(&dir), "Slide 4");
 
-    run(&dir, &["previous"]);
-    run(&dir, &["previous"]);
-    run(&dir, &["previous"]);
-    let output = run(&dir, &["previous"]);
+    fs::write(dir.join("slides.txt"), "HEAD\nnope\n\nnope-again\n").unwrap();
 
-    assert_eq!(git::status(&dir), "Slide 1");
-    assert_eq!(output.exit_code, 0);
-    assert!(output
-        .stdout
-        .contains("You're at the start of the presentation.\n"));
+    let output = run(&dir, &["start", "--manifest", "slides.txt"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Bad ref in manifest, line 2: 'nope'.\nerror: Bad ref in manifest, line 4: 'nope-again'.\n"
+    );
+    assert!(!dir.join(".git/git-slides").is_file());
 }
 
 #[test]
-fn previous_error_getting_current_commit() {
-    let dir = git::init("previous_error_getting_current_commit");
+fn start_manifest_duplicate() {
+    let dir = git::init("start_manifest_duplicate");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Not in presentation");
-
-    run(&dir, &["start", "HEAD~"]);
-
-    git::checkout(&dir, "main"); // Commit not in presentation.
+    git::commit(&dir, "Slide 2");
 
-    let output = run(&dir, &["previous"]);
+    fs::write(dir.join("slides.txt"), "HEAD~\nHEAD\nmain~\n").unwrap();
 
+    let output = run(&dir, &["start", "--manifest", "slides.txt"]);
     assert_eq!(output.exit_code, 1);
     assert_eq!(
         output.stderr,
-        "error: Current HEAD not part of presentation.\n"
+        "error: Duplicate slide in manifest, line 3: 'main~' (same as line 1).\n"
     );
 }
 
 #[test]
-fn stop_regular() {
-    let dir = git::init("stop_regular");
+fn start_manifest_missing() {
+    let dir = git::init("start_manifest_missing");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
 
-    let store_file = dir.join(".git/git-slides");
+    let output = run(&dir, &["start", "--manifest", "slides.txt"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: Cannot read manifest 'slides.txt'.\n");
+}
 
-    assert_eq!(git::status(&dir), "Slide 3");
-    assert!(!store_file.is_file());
+#[test]
+fn start_bad_ref() {
+    let dir = git::init("start_bad_ref");
 
-    run(&dir, &["start"]);
-    assert_eq!(git::status(&dir), "Slide 1");
-    assert!(store_file.is_file());
+    // Will always work, as there are no commits in this repo.
+    let output = run(&dir, &["start", "abcdefg"]);
 
-    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: Bad ref input: 'abcdefg'.\n");
+}
+
+#[test]
+fn start_in_shallow_clone() {
+    let src = git::init("start_in_shallow_clone_src");
+    git::commit(&src, "Slide 1");
+    git::commit(&src, "Slide 2");
+    git::commit(&src, "Slide 3");
+    let dir = git::clone_shallow(&src, "start_in_shallow_clone");
 
+    let output = run(&dir, &["start"]);
     assert_eq!(output.exit_code, 0);
-    assert!(output.stdout.starts_with("Presentation stopped.\n"));
-    assert!(!store_file.is_file()); // Removed store file.
+    assert!(output
+        .stderr
+        .starts_with("warning: This is a shallow clone, the first slides may be missing.\n"));
+    assert!(output.stdout.contains("* 1/1"));
 
-    // By default, switch back to initial branch.
-    assert!(output.stdout.contains("Going back to branch 'main'.\n"));
-    assert_eq!(git::status(&dir), "Slide 3");
+    run(&dir, &["stop"]);
+
+    let output = run(&dir, &["start", "--deepen"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stderr, "");
+    assert!(output.stdout.contains("* 1/3"));
 }
 
 #[test]
-fn stop_started_from_detached() {
-    let dir = git::init("stop_started_from_detached");
+fn start_worktree() {
+    let dir = git::init("start_worktree");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
 
-    git::checkout(&dir, "HEAD~");
-    assert_eq!(git::status(&dir), "Slide 2"); // Start from slide 2.
+    // Work in progress, not to be touched.
+    let wip = dir.join("wip.txt");
+    fs::write(&wip, "wip").unwrap();
 
-    run(&dir, &["start"]);
-    assert_eq!(git::status(&dir), "Slide 1");
+    let output = run(&dir, &["start", "--worktree"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Presenting in worktree '"));
+    assert!(output.stdout.contains("* 1/2"));
 
-    let output = run(&dir, &["stop"]);
+    let worktree = dir.join(".git/git-slides-worktree");
+    assert_eq!(git::status(&worktree), "Slide 1");
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
 
-    // Switch back to initial commit.
-    assert!(output.stdout.contains("Going back to commit"));
-    assert_eq!(git::status(&dir), "Slide 2"); // Back to slide 2.
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&worktree), "Slide 2");
+    assert_eq!(git::status(&dir), "Slide 2"); // Main tree's `main`.
+
+    let output = run(&dir, &["status", "--index"]);
+    assert_eq!(output.stdout, "2 2\n");
+    assert_eq!(run(&dir, &["prompt"]).stdout, "[slides 2/2]\n");
+
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Removed worktree '"));
+    assert!(!worktree.exists());
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
+    assert_eq!(fs::read_to_string(&wip).unwrap(), "wip");
 }
 
 #[test]
-fn stop_in_dirty_working_directory() {
-    let dir = git::init("stop_in_dirty_working_directory");
-    git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-
-    run(&dir, &["start"]);
+fn start_in_dirty_working_directory() {
+    let dir = git::init("start_in_dirty_working_directory");
+    git::commit(&dir, "Initial commit");
 
     let new_file = dir.join("hello.txt");
 
     let _ = fs::write(&new_file, ":)");
     git::add(&dir, &new_file);
 
-    assert!(!git::has_stashed_changes(&dir));
-
-    let output = run(&dir, &["stop"]);
-
-    assert_eq!(output.exit_code, 0);
-    assert!(output.stdout.contains("Stashed uncommitted changes."));
+    let output = run(&dir, &["start"]);
 
-    assert!(git::has_stashed_changes(&dir));
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Working directory contains uncommitted changes.\n"
+    );
 }
 
 #[test]
-fn go_regular() {
-    let dir = git::init("go_regular");
+fn operation_in_progress() {
+    let dir = git::init("operation_in_progress");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
     git::commit(&dir, "Slide 3");
 
-    let output = run(&dir, &["start"]);
-    assert_eq!(output.exit_code, 0);
+    run(&dir, &["start"]);
+
+    // As left by a conflicted merge.
+    let merge_head = dir.join(".git/MERGE_HEAD");
+    fs::write(&merge_head, git::exec(&dir, &["rev-parse", "HEAD"])).unwrap();
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: A merge is in progress.\n\
+         hint: Use 'git merge --continue' to finish it, or 'git merge --abort' to cancel it, first.\n"
+    );
     assert_eq!(git::status(&dir), "Slide 1");
+    let output = run_with_env(&dir, &["next"], &[("LANG", "fr_FR.UTF-8")]);
+    assert_eq!(
+        output.stderr,
+        "erreur : Un merge est en cours.\n\
+         astuce : Utilisez d'abord 'git merge --continue' pour le terminer, ou 'git merge --abort' pour l'annuler.\n"
+    );
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 1);
+    fs::remove_file(&merge_head).unwrap();
 
-    let output = run(&dir, &["go", "2"]);
-    assert_eq!(output.exit_code, 0);
-    assert_eq!(git::status(&dir), "Slide 2");
+    git::exec(&dir, &["bisect", "start"]);
+    let output = run(&dir, &["go", "3"]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .starts_with("error: A bisect is in progress.\n"));
+    git::exec(&dir, &["bisect", "reset"]);
 
     let output = run(&dir, &["go", "3"]);
     assert_eq!(output.exit_code, 0);
     assert_eq!(git::status(&dir), "Slide 3");
-
-    // Go backwards.
-    let output = run(&dir, &["go", "1"]);
-    assert_eq!(output.exit_code, 0);
-    assert_eq!(git::status(&dir), "Slide 1");
 }
 
 #[test]
-fn go_to_current() {
-    let dir = git::init("go_to_current");
+fn start_force() {
+    use std::io::Write as _;
+    use std::process::Stdio;
+
+    let dir = git::init("start_force");
     git::commit(&dir, "Slide 1");
+    let file = dir.join("hello.txt");
+    fs::write(&file, "hello").unwrap();
+    git::add(&dir, &file);
+    git::commit(&dir, "Slide 2");
 
-    let output = run(&dir, &["start"]);
-    assert_eq!(output.exit_code, 0);
+    fs::write(&file, "scribbles").unwrap();
+
+    // No confirmation (stdin is empty).
+    let output = run(&dir, &["start", "--force"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        " M hello.txt\nDiscard these changes? [y/N] Aborting.\n"
+    );
+    assert_eq!(fs::read_to_string(&file).unwrap(), "scribbles");
+
+    let mut child = Command::new(GIT_SLIDES)
+        .args(["start", "--force"])
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"y\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Discarded uncommitted changes.\n"));
     assert_eq!(git::status(&dir), "Slide 1");
+    run(&dir, &["stop"]);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "hello");
 
-    // Go to current.
-    let output = run(&dir, &["go", "1"]);
+    fs::write(&file, "scribbles").unwrap();
+    let output = run(&dir, &["start", "--force", "--yes"]);
     assert_eq!(output.exit_code, 0);
-    assert_eq!(git::status(&dir), "Slide 1");
+    assert_eq!(output.stderr, "");
+    run(&dir, &["stop"]);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "hello");
+
+    let output = run(&dir, &["start", "--force", "--stash"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: '--stash' cannot be combined with '--force'.\n"
+    );
 }
 
 #[test]
-fn go_shows_status() {
-    let dir = git::init("go_shows_status");
+fn start_stash() {
+    let dir = git::init("start_stash");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
 
-    run(&dir, &["start"]);
-
-    let output = run(&dir, &["go", "2"]);
+    let new_file = dir.join("hello.txt");
+    fs::write(&new_file, "mid-edit").unwrap();
+    git::add(&dir, &new_file);
 
+    let output = run(&dir, &["start", "--stash"]);
     assert_eq!(output.exit_code, 0);
-    assert_eq!(git::status(&dir), "Slide 2");
-    assert!(output.stdout.contains("* 2/2"));
-}
+    assert!(output.stdout.starts_with("Stashed uncommitted changes.\n"));
+    assert!(!new_file.exists());
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    run(&dir, &["next"]);
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .ends_with("Restored changes from before the presentation.\n"));
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
+    assert_eq!(fs::read_to_string(&new_file).unwrap(), "mid-edit");
+    assert_eq!(git::exec(&dir, &["stash", "list"]), "");
+
+    // If they cannot be restored, say where they are.
+    run(&dir, &["start", "--stash"]);
+    let stash = git::exec(&dir, &["rev-parse", "stash@{0}"]);
+    fs::write(&new_file, "in the way").unwrap();
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stderr.starts_with(
+        "warning: Could not restore changes from before the presentation, they are still stashed.\n"
+    ));
+    assert!(output.stderr.ends_with(&format!(
+        "hint: Use 'git stash apply {stash}' to restore them by hand.\n"
+    )));
+    fs::remove_file(&new_file).unwrap();
+    git::exec(&dir, &["stash", "pop"]);
+
+    // Not restored elsewhere than where they were made.
+    run(&dir, &["start", "--stash"]);
+    let output = run(&dir, &["stop", "--keep"]);
+    assert!(output
+        .stdout
+        .ends_with("Changes from before the presentation are still stashed.\n"));
+    assert!(!new_file.exists());
+}
+
+#[test]
+fn start_in_half_dirty_working_directory() {
+    let dir = git::init("start_in_half_dirty_working_directory");
+    git::commit(&dir, "Initial commit");
+
+    let new_file = dir.join("hello.txt");
+
+    let _ = fs::write(new_file, ":)");
+
+    // This version doesn't 'git add' the file; it remains untracked.
+
+    let output = run(&dir, &["start"]);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.starts_with("Presentation started at"));
+}
+
+#[test]
+fn start_in_repo_without_commits() {
+    let dir = git::init("start_in_repo_without_commits");
+
+    let output = run(&dir, &["start"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: No HEAD commit. Please provide a valid ref.\n"
+    );
+}
+
+#[test]
+fn all_methods_requiring_presentation_to_be_started() {
+    let dir = git::init("all_methods_requiring_presentation_to_be_started");
+
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["go", "1"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["status"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["list"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["reorder"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["info"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+
+    let output = run(&dir, &["repair"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "You need to start by 'git slides start'.\n");
+}
+
+#[test]
+fn next_regular() {
+    let dir = git::init("next_regular");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(output.exit_code, 0);
+    assert!(!output
+        .stdout
+        .contains("You've reached the end of the presentation.\n"));
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("You've reached the end of the presentation.\n"));
+}
+
+#[test]
+fn next_shorthand() {
+    let dir = git::init("next_shorthand");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["n"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(output.exit_code, 0);
+}
+
+#[test]
+fn next_with_offset() {
+    let dir = git::init("next_with_offset");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+
+    run(&dir, &["start"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["next", "2"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert_eq!(output.exit_code, 0);
+    assert!(!output
+        .stdout
+        .contains("You've reached the end of the presentation.\n"));
+
+    // Does not overflow.
+    let output = run(&dir, &["next", "10"]);
+    assert_eq!(git::status(&dir), "Slide 4");
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("You've reached the end of the presentation.\n"));
+}
+
+#[test]
+fn next_with_overlow() {
+    let dir = git::init("next_with_overflow");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+
+    run(&dir, &["start"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    run(&dir, &["next"]);
+    run(&dir, &["next"]);
+    run(&dir, &["next"]);
+    let output = run(&dir, &["next"]);
+
+    assert_eq!(git::status(&dir), "Slide 4");
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("You've reached the end of the presentation.\n"));
+}
+
+#[test]
+fn recover() {
+    let dir = git::init("recover");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    // Oops, a detour mid-talk.
+    git::exec(&dir, &["switch", "--quiet", "--create", "detour"]);
+    git::commit(&dir, "Detour");
+
+    let output = run(&dir, &["--recover", "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(
+        output.stderr,
+        "warning: Current HEAD not part of presentation, going back to slide 2.\n"
+    );
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    // Nothing to go back to.
+    git::exec(&dir, &["switch", "--quiet", "--orphan", "unrelated"]);
+    git::commit(&dir, "Unrelated");
+
+    let output = run(&dir, &["--recover", "status"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Current HEAD not part of presentation.\n"
+    );
+}
+
+#[test]
+fn next_error_getting_current_commit() {
+    let dir = git::init("next_error_getting_current_commit");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Not in presentation");
+
+    run(&dir, &["start", "HEAD~"]);
+
+    git::checkout(&dir, "main"); // Commit not in presentation.
+
+    let output = run(&dir, &["next"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Current HEAD not part of presentation.\n\
+         hint: Use 'git slides --recover <command>' to go back to the nearest slide.\n"
+    );
+}
+
+#[test]
+fn previous_regular() {
+    let dir = git::init("previous_regular");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["go", "3"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    let output = run(&dir, &["previous"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(output.exit_code, 0);
+    assert!(!output
+        .stdout
+        .contains("You're at the start of the presentation.\n"));
+
+    let output = run(&dir, &["previous"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("You're at the start of the presentation.\n"));
+}
+
+#[test]
+fn previous_shorthand() {
+    let dir = git::init("previous_shorthand");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    run(&dir, &["go", "2"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    let output = run(&dir, &["p"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert_eq!(output.exit_code, 0);
+}
+
+#[test]
+fn previous_with_offset() {
+    let dir = git::init("previous_with_offset");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+
+    run(&dir, &["start"]);
+    run(&dir, &["go", "4"]);
+    assert_eq!(git::status(&dir), "Slide 4");
+
+    let output = run(&dir, &["previous", "2"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(output.exit_code, 0);
+    assert!(!output
+        .stdout
+        .contains("You're at the start of the presentation.\n"));
+
+    // Does not overflow.
+    let output = run(&dir, &["previous", "10"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("You're at the start of the presentation.\n"));
+}
+
+#[test]
+fn previous_with_overlow() {
+    let dir = git::init("previous_with_overflow");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+
+    run(&dir, &["start"]);
+    run(&dir, &["go", "4"]);
+    assert_eq!(git::status(&dir), "Slide 4");
+
+    run(&dir, &["previous"]);
+    run(&dir, &["previous"]);
+    run(&dir, &["previous"]);
+    let output = run(&dir, &["previous"]);
+
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("You're at the start of the presentation.\n"));
+}
+
+#[test]
+fn previous_error_getting_current_commit() {
+    let dir = git::init("previous_error_getting_current_commit");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Not in presentation");
+
+    run(&dir, &["start", "HEAD~"]);
+
+    git::checkout(&dir, "main"); // Commit not in presentation.
+
+    let output = run(&dir, &["previous"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Current HEAD not part of presentation.\n\
+         hint: Use 'git slides --recover <command>' to go back to the nearest slide.\n"
+    );
+}
+
+#[test]
+fn stop_regular() {
+    let dir = git::init("stop_regular");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    let store_file = dir.join(".git/git-slides");
+
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert!(!store_file.is_file());
+
+    run(&dir, &["start"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert!(store_file.is_file());
+
+    let output = run(&dir, &["stop"]);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.starts_with("Presentation stopped.\n"));
+    assert!(!store_file.is_file()); // Removed store file.
+
+    // By default, switch back to initial branch.
+    assert!(output.stdout.contains("Going back to branch 'main'.\n"));
+    assert_eq!(git::status(&dir), "Slide 3");
+}
+
+#[test]
+fn stop_started_from_detached() {
+    let dir = git::init("stop_started_from_detached");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    git::checkout(&dir, "HEAD~");
+    assert_eq!(git::status(&dir), "Slide 2"); // Start from slide 2.
+
+    run(&dir, &["start"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["stop"]);
+
+    // Switch back to initial commit.
+    assert!(output.stdout.contains("Going back to commit"));
+    assert_eq!(git::status(&dir), "Slide 2"); // Back to slide 2.
+}
+
+#[test]
+fn stop_in_dirty_working_directory() {
+    let dir = git::init("stop_in_dirty_working_directory");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    let new_file = dir.join("hello.txt");
+
+    let _ = fs::write(&new_file, ":)");
+    git::add(&dir, &new_file);
+
+    assert!(!git::has_stashed_changes(&dir));
+
+    let output = run(&dir, &["stop"]);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Stashed uncommitted changes."));
+
+    assert!(git::has_stashed_changes(&dir));
+}
+
+#[test]
+fn stop_keep() {
+    let dir = git::init("stop_keep");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let new_file = dir.join("hello.txt");
+    let _ = fs::write(&new_file, ":)");
+    git::add(&dir, &new_file);
+
+    let output = run(&dir, &["stop", "--keep"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .starts_with("Presentation stopped.\nStaying on commit "));
+    assert!(!dir.join(".git/git-slides").is_file());
+
+    // Right where we were, changes included.
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(new_file.is_file());
+    assert!(!git::has_stashed_changes(&dir));
+}
+
+#[test]
+fn stop_go() {
+    let dir = git::init("stop_go");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::exec(&dir, &["branch", "answers", "HEAD~"]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["stop", "--go", "nope"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: Bad ref input: 'nope'.\n");
+    assert!(dir.join(".git/git-slides").is_file());
+
+    let output = run(&dir, &["stop", "--go=answers"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Going to 'answers'.\n"));
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "answers");
+}
+
+#[test]
+fn go_regular() {
+    let dir = git::init("go_regular");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["go", "2"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    let output = run(&dir, &["go", "3"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    // Go backwards.
+    let output = run(&dir, &["go", "1"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+}
+
+#[test]
+fn go_to_current() {
+    let dir = git::init("go_to_current");
+    git::commit(&dir, "Slide 1");
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    // Go to current.
+    let output = run(&dir, &["go", "1"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+}
+
+#[test]
+fn go_shows_status() {
+    let dir = git::init("go_shows_status");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["go", "2"]);
+
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(output.stdout.contains("* 2/2"));
+}
+
+#[test]
+fn dry_run() {
+    let dir = git::init("dry_run");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    let store = fs::read_to_string(dir.join(".git/git-slides")).unwrap();
+
+    let new_file = dir.join("hello.txt");
+    let _ = fs::write(&new_file, ":)");
+    git::add(&dir, &new_file);
+
+    let output = run(&dir, &["--dry-run", "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .starts_with("Would stash uncommitted changes.\nWould check out slide 2/3: "));
+    assert!(output.stdout.ends_with(" Slide 2\n"));
+
+    let output = run(&dir, &["--dry-run", "go", "3"]);
+    assert!(output.stdout.contains("Would check out slide 3/3: "));
+
+    let output = run(&dir, &["--dry-run", "restart"]);
+    assert!(!output.stdout.contains("Presentation restarted."));
+    assert!(output.stdout.contains("Would check out slide 1/3: "));
+
+    // Nothing moved.
+    assert_eq!(git::status(&dir), "Slide 1");
+    assert!(new_file.is_file());
+    assert!(!git::has_stashed_changes(&dir));
+    assert_eq!(
+        fs::read_to_string(dir.join(".git/git-slides")).unwrap(),
+        store
+    );
+}
+
+#[test]
+fn verbose() {
+    let dir = git::init("verbose");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    let slide_2 = git::exec(&dir, &["rev-parse", "main"]);
+
+    let output = run(&dir, &["--verbose", "next"]);
+    println!("{}", output.stderr);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stderr
+        .contains(&format!("trace: git checkout {slide_2} (exit 0)\n")));
+    assert!(output.stderr.contains("trace: git log "));
+
+    let output = run(&dir, &["next"]);
+    assert!(!output.stderr.contains("trace: "));
+}
+
+#[test]
+fn log_file() {
+    let dir = git::init("log_file");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    let slide_2 = git::exec(&dir, &["rev-parse", "main"]);
+
+    let log_file = dir.parent().unwrap().join("log_file.log");
+    let _ = fs::remove_file(&log_file);
+
+    run_with_env(
+        &dir,
+        &["next"],
+        &[("GIT_SLIDES_LOG", log_file.to_str().unwrap())],
+    );
+    let log = fs::read_to_string(&log_file).unwrap();
+    println!("{log}");
+    assert!(log
+        .lines()
+        .next()
+        .unwrap()
+        .ends_with("] command: git-slides next"));
+    assert!(log.contains(&format!("] git: git checkout {slide_2} (exit 0)\n")));
+
+    // Appended to, from config too.
+    git::exec(
+        &dir,
+        &["config", "slides.logFile", log_file.to_str().unwrap()],
+    );
+    run(&dir, &["previous"]);
+    let log = fs::read_to_string(&log_file).unwrap();
+    assert!(log.contains("] command: git-slides next\n"));
+    assert!(log.contains("] command: git-slides previous\n"));
+}
+
+#[test]
+fn checkout_failure_shows_git_error() {
+    let dir = git::init("checkout_failure_shows_git_error");
+    git::commit(&dir, "Slide 1");
+    let file = dir.join("hello.txt");
+    let _ = fs::write(&file, ":)");
+    git::add(&dir, &file);
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start", "--at", "1"]);
+
+    // Untracked, so not stashed, but in the way.
+    let _ = fs::write(&file, ":(");
+
+    let output = run(&dir, &["next"]);
+    println!("{}", output.stderr);
+    assert_eq!(output.exit_code, 1);
+    assert!(output.stderr.starts_with("error: Could not checkout "));
+    assert!(output
+        .stderr
+        .contains("untracked working tree files would be overwritten by checkout"));
+    assert!(output.stderr.contains("hello.txt"));
+}
+
+#[test]
+fn dirty_action() {
+    let dir = git::init("dirty_action");
+    let file = dir.join("hello.txt");
+    let _ = fs::write(&file, ":)");
+    git::add(&dir, &file);
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    let _ = fs::write(&file, ":(");
+
+    git::exec(&dir, &["config", "slides.dirtyAction", "block"]);
+    let output = run(&dir, &["next"]);
+    println!("{}", output.stderr);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .starts_with("error: Working directory contains uncommitted changes.\n"));
+    assert!(output.stderr.contains(" M hello.txt\n"));
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["--dry-run", "next"]);
+    assert!(output
+        .stdout
+        .starts_with("Would refuse to change slides, there are uncommitted changes.\n"));
+
+    git::exec(&dir, &["config", "slides.dirtyAction", "discard"]);
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Discarded uncommitted changes."));
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(fs::read_to_string(&file).unwrap(), ":)");
+    assert!(!git::has_stashed_changes(&dir));
+}
+
+#[test]
+fn go_in_dirty_working_directory() {
+    let dir = git::init("go_in_dirty_working_directory");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    let new_file = dir.join("hello.txt");
+
+    let _ = fs::write(&new_file, ":)");
+    git::add(&dir, &new_file);
+
+    assert!(!git::has_stashed_changes(&dir));
+
+    let output = run(&dir, &["go", "2"]);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Stashed uncommitted changes."));
+    assert!(output.stdout.contains("* 2/2"));
+
+    assert!(git::has_stashed_changes(&dir));
+
+    // Labeled, and recorded.
+    let stash = git::exec(&dir, &["stash", "list", "--format=%H %s"]);
+    let (hash, message) = stash.split_once(' ').unwrap();
+    assert!(message.ends_with(": git-slides: slide 1"));
+    let store = fs::read_to_string(dir.join(".git/git-slides")).unwrap();
+    assert!(store.contains(&format!("[[stash]]\nhash = \"{hash}\"\nslide = 1\n")));
+
+    let output = run(&dir, &["info"]);
+    assert!(output.stdout.contains("Stashes:       1\n"));
+}
+
+#[test]
+fn scratch() {
+    let dir = git::init("scratch");
+    let file = dir.join("demo.txt");
+    fs::write(&file, "1\n").unwrap();
+    git::add(&dir, &file);
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    git::exec(&dir, &["config", "slides.scratch", "true"]);
+
+    // Attendee suggestion, on slide 1.
+    fs::write(&file, "1\nsuggestion\n").unwrap();
+
+    let output = run(&dir, &["next"]);
+    assert!(output.stdout.contains("Stashed uncommitted changes."));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "1\n");
+
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Restored changes of slide 1."));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "1\nsuggestion\n");
+    assert!(!git::has_stashed_changes(&dir));
+
+    // Only once.
+    run(&dir, &["next"]);
+    git::exec(&dir, &["stash", "drop", "--quiet"]);
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.stderr, "");
+    assert!(!output.stdout.contains("Restored"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "1\n");
+}
+
+#[test]
+fn go_no_index() {
+    let dir = git::init("go_no_index");
+
+    let output = run(&dir, &["go"]);
+
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(
+        output.stderr,
+        "fatal: Need a slide number, a percentage or a ref.\n"
+    );
+}
+
+#[test]
+fn go_percentage() {
+    let dir = git::init("go_percentage");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+    git::commit(&dir, "Slide 5");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["go", "100%"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 5");
+
+    let output = run(&dir, &["go", "0%"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["go", "50%"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    // Nearest slide.
+    let output = run(&dir, &["go", "66%"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 4");
+}
+
+#[test]
+fn go_bad_percentage() {
+    let dir = git::init("go_bad_percentage");
+
+    let output = run(&dir, &["go", "101%"]);
+    assert_eq!(output.exit_code, 2);
+
+    let output = run(&dir, &["go", "half%"]);
+    assert_eq!(output.exit_code, 2);
+}
+
+#[test]
+fn go_ref() {
+    let dir = git::init("go_ref");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["go", "main~1"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    let hash = git::exec(&dir, &["rev-parse", "main~2"]);
+    let output = run(&dir, &["go", hash.trim()]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    // Long enough to be an abbreviated hash (which may be all digits).
+    git::exec(&dir, &["tag", "1234567", "main~1"]);
+    let output = run(&dir, &["go", "1234567"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+}
+
+#[test]
+fn swap() {
+    let dir = git::init("swap");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["swap"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: No previous slide to go back to.\n");
+
+    run(&dir, &["go", "4"]);
+    run(&dir, &["swap"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+    run(&dir, &["swap"]);
+    assert_eq!(git::status(&dir), "Slide 4");
+
+    run(&dir, &["previous"]);
+    run(&dir, &["swap"]);
+    assert_eq!(git::status(&dir), "Slide 4");
+}
+
+#[test]
+fn go_ref_to_skipped_commit() {
+    let dir = git::init("go_ref_to_skipped_commit");
+    git::commit(&dir, "slide: Introduction");
+    git::commit(&dir, "Fix typo");
+    git::commit(&dir, "slide: Closures");
+
+    run(&dir, &["start", "--match", "^slide:"]);
+
+    // Lands on the slide the commit leads to.
+    let output = run(&dir, &["go", "main~1"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "slide: Closures");
+}
+
+#[test]
+fn go_ref_not_in_presentation() {
+    let dir = git::init("go_ref_not_in_presentation");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start", "--from", "HEAD~1"]);
+
+    let output = run(&dir, &["go", "main~2"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: 'main~2' is not part of the presentation.\n"
+    );
+
+    let output = run(&dir, &["go", "nonexistent"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: Bad ref input: 'nonexistent'.\n");
+}
+
+#[test]
+fn go_bad_index() {
+    let dir = git::init("go_bad_index");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["go", "0"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Bad slide index. Slide 0 does not exist.\nPossible values range from 1 to 2.\n"
+    );
+
+    let output = run(&dir, &["go", "3"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Bad slide index. Slide 3 does not exist.\nPossible values range from 1 to 2.\n"
+    );
+}
+
+#[test]
+fn notes_shown_on_navigation() {
+    let dir = git::init("notes_shown_on_navigation");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::exec(
+        &dir,
+        &[
+            "notes",
+            "--ref=slides",
+            "add",
+            "-m",
+            "Mention closures.",
+            "main",
+        ],
+    );
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["notes"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "No notes for the current slide.\n");
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.ends_with("\nMention closures.\n"));
+
+    let output = run(&dir, &["notes"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "Mention closures.\n");
+}
+
+#[test]
+fn notes_edit() {
+    let dir = git::init("notes_edit");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+
+    let output = run_with_env(
+        &dir,
+        &["notes", "--edit"],
+        &[("GIT_EDITOR", "echo 'Say hello.' >")],
+    );
+    assert_eq!(output.exit_code, 0);
+
+    let output = run(&dir, &["notes"]);
+    assert_eq!(output.stdout, "Say hello.\n");
+
+    // Slide is untouched.
+    assert_eq!(git::status(&dir), "Slide 1");
+}
+
+#[test]
+fn annotate() {
+    let dir = git::init("annotate");
+    git::commit(&dir, "Slide 1");
+    git::exec(
+        &dir,
+        &["notes", "--ref=slides", "add", "-m", "Say hello.", "main"],
+    );
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["annotate", "audience", "asked about lifetimes"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "Annotated slide 1.\n");
+    run(&dir, &["annotate", "Follow up on Pin."]);
+
+    let output = run(&dir, &["notes"]);
+    println!("{}", output.stdout);
+    assert!(output.stdout.starts_with("Say hello.\n\nAnnotations:\n["));
+    assert!(output
+        .stdout
+        .contains(" UTC] audience asked about lifetimes\n"));
+    assert!(output.stdout.ends_with(" UTC] Follow up on Pin.\n"));
+
+    // Kept apart from speaker notes.
+    let note = git::exec(&dir, &["notes", "--ref=slides", "show"]);
+    assert_eq!(note, "Say hello.");
+
+    run(&dir, &["export", "--html", "site"]);
+    let page = fs::read_to_string(dir.join("site/slide-1.html")).unwrap();
+    assert!(page.contains(r#"<div class="annotations">["#));
+
+    let output = run(&dir, &["annotate"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Need a message.\n");
+}
+
+#[test]
+fn slide_exec() {
+    let dir = git::init("slide_exec");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2\n\nSlide-Exec: echo demo > demo.txt");
+
+    run(&dir, &["start"]);
+    git::exec(&dir, &["config", "slides.exec", "true"]);
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(fs::read_to_string(dir.join("demo.txt")).unwrap(), "demo\n");
+}
+
+#[test]
+fn slide_exec_is_opt_in() {
+    let dir = git::init("slide_exec_is_opt_in");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2\n\nSlide-Exec: echo demo > demo.txt");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stderr
+        .contains("'Slide-Exec' commands, they were not run"));
+    assert!(!dir.join("demo.txt").exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn viewer() {
+    let dir = git::init("viewer");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Don't panic");
+
+    run(&dir, &["start"]);
+    git::exec(
+        &dir,
+        &[
+            "config",
+            "slides.viewer",
+            "cat SLIDE; echo %i/%t %h %s > .git/viewer.txt",
+        ],
+    );
+    fs::create_dir(dir.join("sub")).unwrap();
+    fs::write(dir.join(".git/info/exclude"), "SLIDE\n").unwrap();
+    fs::write(dir.join("SLIDE"), "Rendered\n").unwrap();
+
+    // From the root of the working tree.
+    let output = run(&dir.join("sub"), &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.ends_with("Rendered\n"));
+    let hash = git::exec(&dir, &["rev-parse", "--short=7", "HEAD"]);
+    assert_eq!(
+        fs::read_to_string(dir.join(".git/viewer.txt")).unwrap(),
+        format!("2/2 {hash} Don't panic\n")
+    );
+
+    git::exec(&dir, &["config", "slides.viewer", "false"]);
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stderr, "warning: Viewer command failed: 'false'.\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn on_slide() {
+    let dir = git::init("on_slide");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Don't panic");
+
+    run(&dir, &["start"]);
+    git::exec(
+        &dir,
+        &[
+            "config",
+            "slides.onSlide",
+            "echo \"$SLIDE_INDEX/$SLIDE_TOTAL $SLIDE_HASH $SLIDE_TITLE\" > .git/on-slide.txt",
+        ],
+    );
+    fs::create_dir(dir.join("sub")).unwrap();
+
+    // From the root of the working tree.
+    let output = run(&dir.join("sub"), &["next"]);
+    assert_eq!(output.exit_code, 0);
+    let hash = git::exec(&dir, &["rev-parse", "HEAD"]);
+    assert_eq!(
+        fs::read_to_string(dir.join(".git/on-slide.txt")).unwrap(),
+        format!("2/2 {hash} Don't panic\n")
+    );
+
+    git::exec(&dir, &["config", "slides.onSlide", "false"]);
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stderr, "warning: onSlide command failed: 'false'.\n");
+}
+
+#[test]
+fn info_file() {
+    let dir = git::init("info_file");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Closures");
+
+    let info_file = dir.join(".git/slide.txt");
+    git::exec(
+        &dir,
+        &["config", "slides.infoFile", info_file.to_str().unwrap()],
+    );
+    run(&dir, &["start"]);
+    assert_eq!(fs::read_to_string(&info_file).unwrap(), "1/2 Slide 1");
+
+    git::exec(&dir, &["config", "slides.infoFormat", "Now: %s"]);
+    run(&dir, &["next"]);
+    assert_eq!(fs::read_to_string(&info_file).unwrap(), "Now: Closures");
+
+    run(&dir, &["stop"]);
+    assert_eq!(fs::read_to_string(&info_file).unwrap(), "");
+}
+
+#[test]
+fn slide_file() {
+    let dir = git::init("slide_file");
+    fs::create_dir(dir.join("slides")).unwrap();
+    fs::write(dir.join("slides/1.md"), "# Intro\n").unwrap();
+    git::add(&dir, Path::new("slides/1.md"));
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2, code only");
+
+    git::exec(&dir, &["config", "slides.slideFile", "slides/%i.md"]);
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(!output.stdout.contains("slides"));
+
+    let output = run(&dir, &["previous"]);
+    let path = dir.join("slides/1.md").canonicalize().unwrap();
+    assert!(output.stdout.ends_with(&format!("{}\n", path.display())));
+
+    git::exec(&dir, &["config", "slides.slideFilePipe", ".git/hand-off"]);
+    run(&dir, &["go", "1"]);
+    assert_eq!(
+        fs::read_to_string(dir.join(".git/hand-off")).unwrap(),
+        format!("{}\n", path.display())
+    );
+}
+
+#[cfg(unix)]
+fn write_hook(dir: &Path, name: &str, script: &str) {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let hooks = dir.join(".git/slides-hooks");
+    let _ = fs::create_dir_all(&hooks);
+    let hook = hooks.join(name);
+    let _ = fs::write(&hook, format!("#!/bin/sh\n{script}\n"));
+    let _ = fs::set_permissions(&hook, fs::Permissions::from_mode(0o755));
+}
+
+#[cfg(unix)]
+#[test]
+fn hook_post_slide() {
+    let dir = git::init("hook_post_slide");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    write_hook(
+        &dir,
+        "post-slide",
+        r#"echo "$GIT_SLIDES_INDEX/$GIT_SLIDES_TOTAL $GIT_SLIDES_TITLE" >> .git/hook.log"#,
+    );
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let log = fs::read_to_string(dir.join(".git/hook.log")).unwrap();
+    assert_eq!(log, "1/2 Slide 1\n2/2 Slide 2\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn hook_pre_start_aborts() {
+    let dir = git::init("hook_pre_start_aborts");
+    git::commit(&dir, "Slide 1");
+    write_hook(&dir, "pre-start", "exit 1");
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: The 'pre-start' hook failed. Aborting.\n"
+    );
+
+    let output = run(&dir, &["status"]);
+    assert_eq!(output.exit_code, 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn hook_pre_stop_aborts() {
+    let dir = git::init("hook_pre_stop_aborts");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    write_hook(&dir, "pre-stop", r#"test "$GIT_SLIDES_INDEX" = 2"#);
+
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    run(&dir, &["next"]);
+
+    let output = run(&dir, &["stop"]);
+    assert_eq!(output.exit_code, 0);
+}
+
+#[test]
+fn slide_duration() {
+    let dir = git::init("slide_duration");
+    git::commit(&dir, "Slide 1\n\nSlide-Duration: 2m");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["status"]);
+    assert!(output.stdout.contains(" / 2m 00s)\n"));
+    assert!(!output.stdout.contains("over time"));
+
+    // Pretend we arrived on the slide 3 minutes ago.
+    let store_file = dir.join(".git/git-slides");
+    let store = fs::read_to_string(&store_file).unwrap();
+    let store = store
+        .lines()
+        .map(|line| match line.strip_prefix("at = ") {
+            Some(at) => format!("at = {}", at.parse::<u64>().unwrap() - 180),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&store_file, store).unwrap();
+
+    let output = run(&dir, &["status"]);
+    assert!(output.stdout.contains("(3m 0"));
+    assert!(output.stdout.contains(" / 2m 00s) over time\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn tmux_integration() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let dir = git::init("tmux_integration");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::exec(&dir, &["config", "slides.tmux", "true"]);
+    git::exec(&dir, &["config", "slides.tmuxRefreshPane", "demo.1"]);
+
+    // Fake `tmux`, logging its arguments.
+    let bin = dir.join(".git/bin");
+    fs::create_dir_all(&bin).unwrap();
+    let tmux = bin.join("tmux");
+    fs::write(
+        &tmux,
+        "#!/bin/sh\necho \"$@\" >> \"$(dirname \"$0\")/tmux.log\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&tmux, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), env::var("PATH").unwrap());
+
+    run(&dir, &["start"]);
+    run_with_env(&dir, &["next"], &[("TMUX", "1"), ("PATH", &path)]);
+
+    let log = fs::read_to_string(bin.join("tmux.log")).unwrap();
+    assert_eq!(
+        log,
+        "rename-window -- Slide 2\nsend-keys -t demo.1 C-c Up Enter\n"
+    );
+}
+
+#[test]
+fn terminal_title_not_set_if_not_a_terminal() {
+    let dir = git::init("terminal_title_not_set_if_not_a_terminal");
+    git::commit(&dir, "Slide 1");
+
+    let output = run(&dir, &["start"]);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(!output.stdout.contains("\x1b]0;"));
+}
+
+#[test]
+fn permalink() {
+    let dir = git::init("permalink");
+    git::commit(&dir, "Slide 1");
+    git::exec(
+        &dir,
+        &["remote", "add", "origin", "git@github.com:owner/repo.git"],
+    );
+    git::exec(
+        &dir,
+        &["remote", "add", "lab", "https://gitlab.com/group/repo.git"],
+    );
+
+    run(&dir, &["start"]);
+    let hash = git::exec(&dir, &["rev-parse", "HEAD"]);
+    let hash = hash.trim();
+
+    let output = run(&dir, &["permalink"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(
+        output.stdout,
+        format!("https://github.com/owner/repo/commit/{hash}\n")
+    );
+
+    let output = run(&dir, &["permalink", "lab"]);
+    assert_eq!(
+        output.stdout,
+        format!("https://gitlab.com/group/repo/-/commit/{hash}\n")
+    );
+
+    git::exec(&dir, &["config", "slides.permalink", "%u/c/%H"]);
+    let output = run(&dir, &["permalink"]);
+    assert_eq!(
+        output.stdout,
+        format!("https://github.com/owner/repo/c/{hash}\n")
+    );
+}
+
+#[test]
+fn permalink_no_remote() {
+    let dir = git::init("permalink_no_remote");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["permalink"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: No such remote: 'origin'.\n");
+}
+
+#[test]
+fn timeline() {
+    let dir = git::init("timeline");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide, 2");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+    run(&dir, &["previous"]);
+
+    let output = run(&dir, &["timeline"]);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], "slide,hash,title,entered_at,offset,duration");
+    assert!(lines[1].starts_with("1,"));
+    assert!(lines[2].starts_with("2,"));
+    assert!(lines[2].contains(",\"Slide, 2\","));
+    assert!(lines[3].starts_with("1,"));
+
+    // Still available after the presentation.
+    run(&dir, &["stop"]);
+
+    let output = run(&dir, &["timeline", "--format", "json"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .starts_with("[\n  {\"slide\": 1, \"hash\": \""));
+    assert!(output.stdout.contains("\"title\": \"Slide, 2\""));
+    assert!(output.stdout.ends_with("}\n]\n"));
+}
+
+#[test]
+fn start_warns_about_branches_in_slides() {
+    let dir = git::init("start_warns_about_branches_in_slides");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.stderr, "");
+    run(&dir, &["stop"]);
+
+    git::exec(&dir, &["branch", "topic", "HEAD~"]);
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(
+        output.stderr,
+        "warning: Branch 'topic' points to slide 2/3, in the middle of the presentation.\n\
+         hint: Commits made on a slide are not on any branch, use 'git switch -c <branch>' to keep them.\n"
+    );
+}
+
+#[test]
+fn start_several_branches() {
+    let dir = git::init("start_several_branches");
+    git::commit(&dir, "Intro");
+    git::exec(&dir, &["checkout", "-b", "chapter-1"]);
+    git::commit(&dir, "Chapter 1, slide 1");
+    git::commit(&dir, "Chapter 1, slide 2");
+    git::exec(&dir, &["checkout", "-b", "chapter-2", "main"]);
+    git::commit(&dir, "Chapter 2, slide 1");
+    git::checkout(&dir, "main");
+
+    let output = run(&dir, &["start", "main", "chapter-1", "chapter-2"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Intro");
+
+    let output = run(&dir, &["list"]);
+    println!("{}", output.stdout);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines[0], "main");
+    // Numbered by section.
+    assert!(lines[1].starts_with("* 1.1/3.1 "));
+    assert_eq!(lines[3], "chapter-1");
+    assert!(lines[4].starts_with("  2.1/3.1 ") && lines[4].ends_with("Chapter 1, slide 1"));
+    assert!(lines[5].starts_with("  2.2/3.1 ") && lines[5].ends_with("Chapter 1, slide 2"));
+    assert_eq!(lines[7], "chapter-2");
+    assert!(lines[8].starts_with("  3.1/3.1 ") && lines[8].ends_with("Chapter 2, slide 1"));
+
+    let output = run(&dir, &["go", "4"]);
+    assert!(output.stdout.contains("\n* 3.1/3.1 "));
+    assert_eq!(git::status(&dir), "Chapter 2, slide 1");
+    run(&dir, &["stop"]);
+    assert_eq!(git::status(&dir), "Intro");
+
+    // Nothing new in `main` after `chapter-1`.
+    let output = run(&dir, &["start", "chapter-1", "main"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: 'main' has no commits that the refs before it don't have.\n"
+    );
+}
+
+#[test]
+fn start_twice() {
+    let dir = git::init("start_twice");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: A presentation is already in progress.\n\
+         hint: Use 'git slides start --resume' to continue it, or 'git slides stop' to end it.\n"
+    );
+
+    // Wandered off, then back.
+    git::exec(&dir, &["checkout", "-b", "wip"]);
+    git::commit(&dir, "WIP");
+    let output = run(&dir, &["start", "--resume"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.starts_with("Resuming presentation.\n"));
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    // The original branch is still known.
+    run(&dir, &["stop"]);
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
+
+    let output = run(&dir, &["start", "--resume"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: No presentation to resume.\n");
+}
+
+#[test]
+fn restart() {
+    let dir = git::init("restart");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next", "2"]);
+
+    let output = run(&dir, &["restart"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.starts_with("Presentation restarted.\n"));
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    // Timeline starts over.
+    let output = run(&dir, &["timeline"]);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let columns: Vec<&str> = lines[1].split(',').collect();
+    assert_eq!(columns[0], "1");
+    assert_eq!(columns[1], git::exec(&dir, &["rev-parse", "HEAD"]));
+    // Offset from the new start (the clock may tick in between).
+    assert!(columns[4].parse::<u64>().unwrap() <= 1);
+
+    // Still presenting, and going back to the branch on stop.
+    run(&dir, &["stop"]);
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
+}
+
+#[test]
+fn export_import_state() {
+    let dir = git::init("export_import_state");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let state = dir.parent().unwrap().join("export_import_state.toml");
+    let state = state.to_str().unwrap();
+    let output = run(&dir, &["export-state", state]);
+    assert_eq!(output.exit_code, 0);
+    assert!(fs::read_to_string(state)
+        .unwrap()
+        .contains("position = 2\n"));
+    run(&dir, &["stop"]);
+
+    // Rehearsed here, presented there.
+    let other = dir.parent().unwrap().join("export_import_state_other");
+    let _ = fs::remove_dir_all(&other);
+    git::exec(&dir, &["clone", "--quiet", ".", other.to_str().unwrap()]);
+
+    let output = run(&other, &["import-state", state]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 2/3"));
+    assert_eq!(git::status(&other), "Slide 2");
+
+    // Timings came along.
+    let output = run(&other, &["timeline"]);
+    assert_eq!(output.stdout.lines().count(), 4);
+
+    let output = run(&other, &["import-state", state]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: A presentation is already in progress.\n"
+    );
+
+    // Stops back on the branch of this clone.
+    run(&other, &["stop"]);
+    assert_eq!(git::exec(&other, &["branch", "--show-current"]), "main");
+}
+
+#[test]
+fn checkpoints() {
+    let dir = git::init("checkpoints");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    git::exec(&dir, &["config", "slides.checkpoints", "2s 1h"]);
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["next"]);
+    assert!(!output.stdout.contains("Checkpoint"));
+
+    std::thread::sleep(std::time::Duration::from_secs(2));
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("\nCheckpoint: 2s elapsed.\n\x07"));
+
+    // Only once.
+    let output = run(&dir, &["previous"]);
+    assert!(!output.stdout.contains("Checkpoint"));
+
+    git::exec(&dir, &["config", "slides.checkpoints", "20 minutes"]);
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Bad config value for 'slides.checkpoints': '20 minutes'.\n"
+    );
+}
+
+#[test]
+fn stats() {
+    let dir = git::init("stats");
+    git::commit(&dir, "Slide 1\n\nSlide-Duration: 2m");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+    run(&dir, &["stop"]);
+
+    // Runs are named after when they started, avoid a collision.
+    std::thread::sleep(std::time::Duration::from_secs(1));
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["stats"]);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines[0], "Runs: 2");
+    assert!(lines[3].starts_with("  1 "));
+    assert!(lines[3].ends_with("2m 00s  Slide 1"));
+    assert!(lines[4].starts_with("  2 "));
+    assert!(lines[4].ends_with("-  Slide 2"));
+    assert!(lines[5].ends_with("Total"));
+}
+
+#[test]
+fn stats_nothing() {
+    let dir = git::init("stats_nothing");
+    git::commit(&dir, "Slide 1");
+
+    let output = run(&dir, &["stats"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: No recorded presentation yet.\n");
+}
+
+#[test]
+fn timeline_nothing() {
+    let dir = git::init("timeline_nothing");
+    git::commit(&dir, "Slide 1");
+
+    let output = run(&dir, &["timeline"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: No presentation to show the timeline of.\n"
+    );
+}
+
+#[test]
+fn publish() {
+    let dir = git::init("publish");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    let remote = git::clone_bare(&dir, "publish_remote");
+    git::exec(&dir, &["remote", "add", "origin", remote.to_str().unwrap()]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["publish"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "Publishing slides to 'origin'.\n");
+    assert_eq!(
+        git::exec(&remote, &["rev-parse", "refs/slides/current"]),
+        git::exec(&dir, &["rev-parse", "HEAD"]),
+    );
+
+    run(&dir, &["next"]);
+    assert_eq!(
+        git::exec(&remote, &["rev-parse", "refs/slides/current"]),
+        git::exec(&dir, &["rev-parse", "HEAD"]),
+    );
+
+    let output = run(&dir, &["publish", "--stop"]);
+    assert_eq!(output.stdout, "Stopped publishing.\n");
+
+    run(&dir, &["previous"]);
+    assert_ne!(
+        git::exec(&remote, &["rev-parse", "refs/slides/current"]),
+        git::exec(&dir, &["rev-parse", "HEAD"]),
+    );
+}
+
+#[test]
+fn export_html() {
+    let dir = git::init("export_html");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::exec(
+        &dir,
+        &["notes", "--ref=slides", "add", "-m", "Say <hi>.", "main"],
+    );
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["export", "--html", "site"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "Exported 2 slides to 'site'.\n");
+
+    let index = fs::read_to_string(dir.join("site/index.html")).unwrap();
+    assert!(index.contains(r#"<a href="slide-1.html">Slide 1</a>"#));
+    assert!(index.contains(r#"<a href="slide-2.html">Slide 2</a>"#));
+
+    let slide = fs::read_to_string(dir.join("site/slide-1.html")).unwrap();
+    assert!(slide.contains("<h1>Slide 1</h1>"));
+    let hash = git::exec(&dir, &["rev-parse", "HEAD"]);
+    assert!(slide.contains(&format!(r#"<span class="hash">{}</span>"#, &hash[..7])));
+    assert!(slide.contains(r#"<a href="slide-2.html">Next →</a>"#));
+    assert!(!slide.contains("Previous"));
+
+    let slide = fs::read_to_string(dir.join("site/slide-2.html")).unwrap();
+    assert!(slide.contains(r#"<div class="notes">Say &lt;hi&gt;.</div>"#));
+    assert!(slide.contains(r#"<a href="slide-1.html">← Previous</a>"#));
+}
+
+#[test]
+fn export_no_format() {
+    let dir = git::init("export_no_format");
+
+    let output = run(&dir, &["export"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(
+        output.stderr,
+        "fatal: Need an export format, e.g., '--html <dir>'.\n"
+    );
+}
+
+#[test]
+fn present() {
+    use std::io::Write as _;
+    use std::process::Stdio;
+
+    let dir = git::init("present");
+    git::commit(&dir, "Slide 1");
+    git::commit(
+        &dir,
+        "Slide 2\n\nIntro text,\nspanning lines.\n\n- First point\n* Second point\n\n## Heading",
+    );
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let mut present = Command::new(GIT_SLIDES)
+        .arg("present")
+        .current_dir(&dir)
+        .env("COLUMNS", "40")
+        .env("LINES", "12")
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Next, go to 3 and back, out of range (ignored), quit.
+    present
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"\n3\np\n99\nq\n")
+        .unwrap();
+    let output = present.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let screens: Vec<&str> = stdout.split("\x1b[2J\x1b[H").skip(1).collect();
+    assert_eq!(screens.len(), 6); // 5 slides shown, and cleared on exit.
+
+    let slide_2 = screens[1];
+    assert_eq!(slide_2.lines().count(), 11); // Last line left for input.
+    assert!(slide_2.contains("\n                Slide 2\n"));
+    assert!(slide_2.contains("\n  Intro text, spanning lines.\n"));
+    assert!(slide_2.contains("\n  • First point\n  • Second point\n"));
+    assert!(slide_2.contains("\n  Heading\n"));
+    assert!(slide_2.ends_with("                                  2/3\n"));
+
+    assert!(screens[2].contains("Slide 3"));
+    assert!(screens[3].contains("Slide 2"));
+    assert!(screens[4].contains("Slide 2"));
+    assert_eq!(screens[5], "");
+    assert_eq!(git::status(&dir), "Slide 2");
+}
+
+#[test]
+fn events() {
+    use std::io::{BufRead as _, BufReader};
+    use std::process::Stdio;
+
+    let dir = git::init("events");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide \"2\"");
+
+    run(&dir, &["start"]);
+
+    let mut events = Command::new(GIT_SLIDES)
+        .arg("events")
+        .current_dir(&dir)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(events.stdout.take().unwrap()).lines();
+    let mut next_event = || lines.next().unwrap().unwrap();
+
+    // The current slide first.
+    let event = next_event();
+    assert!(event.starts_with(r#"{"event": "slide", "slide": 1, "total": 2, "hash": ""#));
+    assert!(event.contains(r#""title": "Slide 1", "at": ""#));
+
+    run(&dir, &["next"]);
+    let hash = git::exec(&dir, &["rev-parse", "HEAD"]);
+    let event = next_event();
+    assert!(event.starts_with(&format!(
+        r#"{{"event": "slide", "slide": 2, "total": 2, "hash": "{hash}", "title": "Slide \"2\"", "at": ""#
+    )));
+
+    run(&dir, &["stop"]);
+    assert!(next_event().starts_with(r#"{"event": "stop", "at": ""#));
+
+    run(&dir, &["start"]);
+    assert!(next_event().contains(r#""slide": 1,"#));
+
+    events.kill().unwrap();
+    let _ = events.wait();
+}
+
+#[test]
+fn web() {
+    use std::io::{Read as _, Write as _};
+    use std::net::TcpStream;
+
+    let dir = git::init("web");
+    git::commit(&dir, "Slide <1>");
+
+    run(&dir, &["start"]);
+
+    let mut server = Command::new(GIT_SLIDES)
+        .args(["web", "--port", "18437"])
+        .current_dir(&dir)
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let get = |path: &str| -> Option<String> {
+        let mut stream = TcpStream::connect(("127.0.0.1", 18437)).ok()?;
+        write!(stream, "GET {path} HTTP/1.1\r\n\r\n").ok()?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok()?;
+        Some(response)
+    };
+
+    // Wait for the server to be up.
+    let mut page = None;
+    for _ in 0..50 {
+        page = get("/");
+        if page.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let hash = get("/hash");
+    let missing = get("/missing");
+    server.kill().unwrap();
+    let _ = server.wait();
+
+    let page = page.unwrap();
+    assert!(page.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(page.contains("<h1>Slide &lt;1&gt;</h1>"));
+    let head = git::exec(&dir, &["rev-parse", "HEAD"]);
+    assert!(hash.unwrap().ends_with(head.trim()));
+    assert!(missing.unwrap().starts_with("HTTP/1.1 404 Not Found\r\n"));
+}
+
+#[test]
+fn status_full() {
+    let dir = git::init("status_full");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("(Start)"));
+    assert!(output.stdout.contains("* 1/3"));
+    assert!(output.stdout.contains("2/3"));
+    assert!(output.stdout.contains("3/3"));
+    assert!(output.stdout.contains("(End)"));
+}
+
+#[test]
+fn status_oneline() {
+    let dir = git::init("status_oneline");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let hash = git::exec(&dir, &["rev-parse", "--short=7", "HEAD"]);
+
+    let output = run(&dir, &["status", "--oneline"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, format!("2/3  {hash}  Slide 2\n"));
+}
+
+#[test]
+fn status_index() {
+    let dir = git::init("status_index");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["go", "3"]);
+
+    let output = run(&dir, &["status", "--index"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "3 3\n");
+}
+
+#[test]
+fn status_progress_bar() {
+    let dir = git::init("status_progress_bar");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+
+    run(&dir, &["start"]);
+    run(&dir, &["go", "2"]);
+
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+
+    let bar = output.stdout.lines().next().unwrap();
+    assert!(bar.starts_with("[##########"));
+    assert!(!bar.contains("###########"));
+    assert!(bar.ends_with("] 2/4  50%"));
+
+    run(&dir, &["go", "4"]);
+    let output = run(&dir, &["status"]);
+    assert!(output.stdout.contains("[####################"));
+    assert!(output.stdout.contains("] 4/4  100%"));
+}
+
+#[test]
+#[allow(clippy::cognitive_complexity)]
+fn status_cut() {
+    let dir = git::init("status_cut");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+    git::commit(&dir, "Slide 5");
+    git::commit(&dir, "Slide 6");
+    git::commit(&dir, "Slide 7");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(output.stdout.contains("(Start)"));
+    assert!(output.stdout.contains("* 1/7"));
+    assert!(output.stdout.contains("2/7"));
+    assert!(output.stdout.contains("3/7"));
+    assert!(output.stdout.contains("4/7"));
+    assert!(!output.stdout.contains("5/7"));
+
+    run(&dir, &["go", "2"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(output.stdout.contains("(Start)"));
+    assert!(output.stdout.contains("1/7"));
+    assert!(output.stdout.contains("* 2/7"));
+    assert!(output.stdout.contains("3/7"));
+    assert!(output.stdout.contains("4/7"));
+    assert!(output.stdout.contains("5/7"));
+    assert!(!output.stdout.contains("6/7"));
+
+    run(&dir, &["go", "3"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(!output.stdout.contains("(Start)"));
+    assert!(output.stdout.contains("1/7"));
+    assert!(output.stdout.contains("2/7"));
+    assert!(output.stdout.contains("* 3/7"));
+    assert!(output.stdout.contains("4/7"));
+    assert!(output.stdout.contains("5/7"));
+    assert!(output.stdout.contains("6/7"));
+    assert!(!output.stdout.contains("7/7"));
+
+    run(&dir, &["go", "4"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(!output.stdout.contains("1/7"));
+    assert!(output.stdout.contains("2/7"));
+    assert!(output.stdout.contains("3/7"));
+    assert!(output.stdout.contains("* 4/7"));
+    assert!(output.stdout.contains("5/7"));
+    assert!(output.stdout.contains("6/7"));
+    assert!(output.stdout.contains("7/7"));
+    assert!(!output.stdout.contains("(End)"));
+
+    run(&dir, &["go", "5"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(!output.stdout.contains("2/7"));
+    assert!(output.stdout.contains("3/7"));
+    assert!(output.stdout.contains("4/7"));
+    assert!(output.stdout.contains("* 5/7"));
+    assert!(output.stdout.contains("6/7"));
+    assert!(output.stdout.contains("7/7"));
+    assert!(output.stdout.contains("(End)"));
+
+    run(&dir, &["go", "6"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(!output.stdout.contains("3/7"));
+    assert!(output.stdout.contains("4/7"));
+    assert!(output.stdout.contains("5/7"));
+    assert!(output.stdout.contains("* 6/7"));
+    assert!(output.stdout.contains("7/7"));
+    assert!(output.stdout.contains("(End)"));
+
+    run(&dir, &["go", "7"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(!output.stdout.contains("4/7"));
+    assert!(output.stdout.contains("5/7"));
+    assert!(output.stdout.contains("6/7"));
+    assert!(output.stdout.contains("* 7/7"));
+    assert!(output.stdout.contains("(End)"));
+}
+
+#[test]
+fn status_number_padding() {
+    let dir = git::init("status_number_padding");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+    git::commit(&dir, "Slide 5");
+    git::commit(&dir, "Slide 6");
+    git::commit(&dir, "Slide 7");
+    git::commit(&dir, "Slide 8");
+    git::commit(&dir, "Slide 9");
+    git::commit(&dir, "Slide 10");
+
+    run(&dir, &["start"]);
+
+    // Padded, even if bigger number not in output.
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(output.stdout.contains("*  1/10"));
+    assert!(output.stdout.contains("   2/10"));
+    assert!(!output.stdout.contains("  10/10"));
+
+    run(&dir, &["go", "9"]);
+    let output = run(&dir, &["status"]);
+    println!("{}", output.stdout);
+    assert!(!output.stdout.contains("6/10"));
+    assert!(output.stdout.contains("*  9/10"));
+    assert!(output.stdout.contains("  10/10"));
+}
+
+#[test]
+fn status_error_getting_current_commit() {
+    let dir = git::init("status_error_getting_current_commit");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Not in presentation");
+
+    run(&dir, &["start", "HEAD~"]);
+
+    git::checkout(&dir, "main"); // Commit not in presentation.
+
+    let output = run(&dir, &["status"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Current HEAD not part of presentation.\n\
+         hint: Use 'git slides --recover <command>' to go back to the nearest slide.\n"
+    );
+}
+
+#[test]
+fn list() {
+    let dir = git::init("list");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list"]);
+    println!("{}", output.stdout);
+
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/3"));
+    assert!(output.stdout.contains("2/3"));
+    assert!(output.stdout.contains("3/3"));
+}
+
+#[test]
+fn find() {
+    let dir = git::init("find");
+    git::commit(&dir, "Add closure");
+    git::commit(&dir, "Add struct");
+    git::commit(&dir, "Capture by move in Closure");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["find", "closure"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("* 1/3 ") && lines[0].ends_with(" Add closure"));
+    assert!(lines[1].starts_with("  3/3 ") && lines[1].ends_with(" Capture by move in Closure"));
+
+    // Several words are one pattern.
+    let output = run(&dir, &["find", "by", "move"]);
+    assert_eq!(output.stdout.lines().count(), 1);
+
+    let output = run(&dir, &["find", "enum"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stdout, "");
+    assert_eq!(output.stderr, "No slide matches 'enum'.\n");
+
+    let output = run(&dir, &["find"]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Need a pattern.\n");
+}
+
+#[test]
+fn non_utf8_titles() {
+    let dir = git::init("non_utf8_titles");
+    git::commit(&dir, "Café crème");
+    // Declared encoding, Git converts it.
+    git::commit_encoded(&dir, b"D\xe9j\xe0 vu", Some("ISO-8859-1"));
+    // Undeclared, assumed to be Windows-1252.
+    git::commit_encoded(&dir, b"\x93Cr\xeape\x94", None);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list"]);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert!(lines[0].ends_with(" Café crème"));
+    assert!(lines[1].ends_with(" Déjà vu"));
+    assert!(lines[2].ends_with(" “Crêpe”"));
+
+    let output = run(&dir, &["find", "CRÊPE"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.ends_with(" “Crêpe”\n"));
+
+    // Undeclared, but the repository says it is Latin-1.
+    run(&dir, &["stop"]);
+    git::exec(&dir, &["config", "i18n.commitEncoding", "ISO-8859-1"]);
+    run(&dir, &["start"]);
+    let output = run(&dir, &["list"]);
+    assert!(output
+        .stdout
+        .lines()
+        .nth(2)
+        .unwrap()
+        .ends_with(" \u{93}Crêpe\u{94}"));
+}
+
+#[test]
+fn grep() {
+    let dir = git::init("grep");
+    fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    git::add(&dir, Path::new("main.rs"));
+    git::commit(&dir, "Slide 1");
+    fs::write(dir.join("lib.rs"), "pub fn parse() {}\n").unwrap();
+    git::add(&dir, Path::new("lib.rs"));
+    git::commit(&dir, "Slide 2");
+    fs::write(dir.join("main.rs"), "fn main() { lib::parse(); }\n").unwrap();
+    git::add(&dir, Path::new("main.rs"));
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["grep", "parse"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("  2/3 ") && lines[0].ends_with(" Slide 2"));
+    assert_eq!(lines[1].trim(), "lib.rs");
+    assert!(lines[2].starts_with("  3/3 ") && lines[2].ends_with(" Slide 3"));
+    assert_eq!(lines[3].trim(), "lib.rs");
+    assert_eq!(lines[4].trim(), "main.rs");
+
+    // Nothing was checked out.
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    let output = run(&dir, &["grep", "PARSE"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "No slide contains 'PARSE'.\n");
+    let output = run(&dir, &["grep", "-i", "PARSE"]);
+    assert_eq!(output.exit_code, 0);
+
+    let output = run(&dir, &["grep", "["]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .starts_with("error: Could not search slides for '['.\n"));
+}
+
+#[test]
+fn abbrev() {
+    let dir = git::init("abbrev");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    let head = git::exec(&dir, &["rev-parse", "HEAD"]);
+
+    let output = run(&dir, &["status", "--oneline"]);
+    assert!(output.stdout.starts_with(&format!("1/2  {}  ", &head[..7])));
+
+    git::exec(&dir, &["config", "core.abbrev", "12"]);
+    let output = run(&dir, &["current", "--format", "%h"]);
+    assert_eq!(output.stdout, format!("{}\n", &head[..12]));
+
+    // Takes precedence.
+    git::exec(&dir, &["config", "slides.abbrev", "9"]);
+    let output = run(&dir, &["list"]);
+    assert!(output
+        .stdout
+        .contains(&format!(" {} Slide 1\n", &head[..9])));
+
+    git::exec(&dir, &["config", "slides.abbrev", "3"]);
+    let output = run(&dir, &["list"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Bad config value for 'slides.abbrev': '3'.\n"
+    );
+}
+
+#[test]
+fn tags_decoration() {
+    let dir = git::init("tags_decoration");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::exec(&dir, &["tag", "chapter-2", "HEAD~"]);
+    git::exec(&dir, &["tag", "-a", "-m", "Release", "v1", "HEAD~"]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list"]);
+    println!("{}", output.stdout);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert!(lines[0].ends_with(" Slide 1"));
+    assert!(!lines[0].contains("tag:"));
+    assert!(lines[1].ends_with(" (tag: chapter-2, tag: v1) Slide 2"));
+
+    let output = run(&dir, &["status"]);
+    assert!(output
+        .stdout
+        .contains(" (tag: chapter-2, tag: v1) Slide 2\n"));
+}
+
+#[test]
+fn branches_decoration() {
+    let dir = git::init("branches_decoration");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::exec(&dir, &["branch", "exercise-1", "HEAD~"]);
+    git::exec(&dir, &["branch", "answers", "HEAD~"]);
+    git::exec(&dir, &["tag", "chapter-1", "HEAD~"]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list"]);
+    println!("{}", output.stdout);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert!(lines[0].ends_with(" (answers, exercise-1, tag: chapter-1) Slide 1"));
+    assert!(lines[1].ends_with(" (main) Slide 2"));
+}
+
+#[test]
+fn hide_and_unhide() {
+    let dir = git::init("hide_and_unhide");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+    git::commit(&dir, "Slide 5");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["hide", "2", "4"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.starts_with("Hidden slides: 2.\n"));
+    assert_eq!(run(&dir, &["count"]).stdout, "3\n");
+
+    // Skipped, and renumbered.
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert_eq!(run(&dir, &["status", "--index"]).stdout, "2 3\n");
+    // The prompt and completion agree.
+    assert_eq!(run(&dir, &["prompt"]).stdout, "[slides 2/3]\n");
+    assert_eq!(
+        run(&dir, &["__complete"]).stdout,
+        "1\tSlide 1\n2\tSlide 3\n3\tSlide 5\n"
+    );
+
+    let output = run(&dir, &["hide"]);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("1 ") && lines[0].ends_with(" Slide 2"));
+    assert!(lines[1].starts_with("2 ") && lines[1].ends_with(" Slide 4"));
+
+    let output = run(&dir, &["hide", "2"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(output.stderr, "error: Cannot hide the current slide.\n");
+
+    let output = run(&dir, &["unhide", "1"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(run(&dir, &["status", "--index"]).stdout, "3 4\n");
+
+    let output = run(&dir, &["unhide", "2"]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .starts_with("error: There is no hidden slide 2.\n"));
+
+    run(&dir, &["unhide"]);
+    assert_eq!(run(&dir, &["count"]).stdout, "5\n");
+}
+
+#[test]
+fn hide_with_sections() {
+    let dir = git::init("hide_with_sections");
+    git::commit(&dir, "Intro");
+    git::exec(&dir, &["checkout", "-b", "chapter-1"]);
+    git::commit(&dir, "Chapter 1, slide 1");
+    git::commit(&dir, "Chapter 1, slide 2");
+    git::commit(&dir, "Chapter 1, slide 3");
+    git::checkout(&dir, "main");
+
+    run(&dir, &["start", "main", "chapter-1"]);
+
+    // As displayed.
+    let output = run(&dir, &["hide", "2.2"]);
+    assert_eq!(output.exit_code, 0);
+    let output = run(&dir, &["hide"]);
+    assert_eq!(output.stdout.lines().count(), 1);
+    assert!(output.stdout.ends_with(" Chapter 1, slide 2\n"));
+
+    // Across the whole presentation still works.
+    run(&dir, &["hide", "3"]);
+    let output = run(&dir, &["hide"]);
+    assert!(output.stdout.ends_with(" Chapter 1, slide 3\n"));
+
+    let output = run(&dir, &["hide", "2.9"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Bad slide index. Slide 2.9 does not exist.\n"
+    );
+
+    let output = run(&dir, &["hide", "2."]);
+    assert_eq!(output.exit_code, 2);
+    assert_eq!(output.stderr, "fatal: Bad slide number: '2.'.\n");
+}
+
+#[test]
+fn list_number_padding() {
+    let dir = git::init("list_number_padding");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+    git::commit(&dir, "Slide 4");
+    git::commit(&dir, "Slide 5");
+    git::commit(&dir, "Slide 6");
+    git::commit(&dir, "Slide 7");
+    git::commit(&dir, "Slide 8");
+    git::commit(&dir, "Slide 9");
+    git::commit(&dir, "Slide 10");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list"]);
+    println!("{}", output.stdout);
+    assert!(output.stdout.contains("*  1/10"));
+    assert!(output.stdout.contains("  10/10"));
+}
+
+#[test]
+fn title_prefix_is_stripped() {
+    let dir = git::init("title_prefix_is_stripped");
+    git::commit(&dir, "slides: 01 – Introduction");
+    git::commit(&dir, "slides: 02 – Closures");
+    git::commit(&dir, "chore: Unrelated");
+    git::exec(&dir, &["config", "slides.titlePrefix", "slides: * – "]);
+    git::exec(&dir, &["config", "--add", "slides.titlePrefix", "chore:"]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["status"]);
+    assert!(output.stdout.contains(" Introduction\n"));
+    assert!(output.stdout.contains(" Closures\n"));
+    assert!(output.stdout.contains(" Unrelated\n"));
+    assert!(!output.stdout.contains("slides:"));
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains(" Closures\n"));
+    assert!(!output.stdout.contains("slides:"));
+    assert!(!output.stdout.contains("chore:"));
+}
+
+#[test]
+fn title_prefix_not_matching() {
+    let dir = git::init("title_prefix_not_matching");
+    git::commit(&dir, "Introduction");
+    git::commit(&dir, "slides:");
+    git::exec(&dir, &["config", "slides.titlePrefix", "slides:"]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains(" Introduction\n"));
+    // Not stripped if nothing would remain.
+    assert!(output.stdout.contains(" slides:\n"));
+}
+
+#[test]
+fn list_error_getting_current_commit() {
+    let dir = git::init("list_error_getting_current_commit");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Not in presentation");
+
+    run(&dir, &["start", "HEAD~"]);
+
+    git::checkout(&dir, "main"); // Commit not in presentation.
+
+    let output = run(&dir, &["list"]);
+
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Current HEAD not part of presentation.\n\
+         hint: Use 'git slides --recover <command>' to go back to the nearest slide.\n"
+    );
+}
+
+#[test]
+fn reorder() {
+    let dir = git::init("reorder");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+    let before = git::exec(&dir, &["rev-parse", "HEAD"]);
+
+    // Move slide 1 after slide 2, and drop slide 3.
+    let editor = ("GIT_EDITOR", "sed -i -e '1{h;d}' -e '2G' -e '3d'");
+
+    let output = run_with_env(&dir, &["reorder"], &[editor]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Slides reordered."));
+    // Still on the same slide, now first.
+    assert!(output.stdout.contains("* 1/2"));
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    // History was not rewritten.
+    assert_eq!(git::exec(&dir, &["rev-parse", "HEAD"]), before);
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("* 1/2"));
+    assert!(output.stdout.contains("Slide 1"));
+    assert!(!output.stdout.contains("Slide 3"));
+
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 1");
+}
+
+#[test]
+fn reorder_drop_current_slide() {
+    let dir = git::init("reorder_drop_current_slide");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    let editor = ("GIT_EDITOR", "sed -i -e '1d'");
+
+    let output = run_with_env(&dir, &["reorder"], &[editor]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/1"));
+    assert_eq!(git::status(&dir), "Slide 2");
+}
+
+#[test]
+fn reorder_keeps_presentation_state() {
+    let dir = git::init("reorder_keeps_presentation_state");
+    git::commit(&dir, "Intro");
+    git::exec(&dir, &["checkout", "-b", "chapter-1"]);
+    git::commit(&dir, "Chapter 1, slide 1");
+    git::commit(&dir, "Chapter 1, slide 2");
+    git::commit(&dir, "Chapter 1, slide 3");
+    git::checkout(&dir, "main");
+    let worktree = dir.join(".git/git-slides-worktree");
+    // Keep the order.
+    let editor = ("GIT_EDITOR", "true");
+
+    run(&dir, &["start", "--worktree", "main", "chapter-1"]);
+    run(&dir, &["hide", "3"]);
+    run(&dir, &["next"]);
+
+    let output = run_with_env(&dir, &["reorder"], &[editor]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 2.1/2.2 "));
+
+    // Still in the worktree.
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&worktree), "Chapter 1, slide 3");
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.lines().any(|line| line == "chapter-1"));
+    let output = run(&dir, &["hide"]);
+    assert!(output.stdout.contains("Chapter 1, slide 2"));
+    let output = run(&dir, &["timeline"]);
+    assert_eq!(output.stdout.lines().count(), 4);
+    let output = run(&dir, &["verify"]);
+    assert_eq!(output.exit_code, 0);
+
+    let output = run(&dir, &["stop"]);
+    assert!(output.stdout.contains("Removed worktree '"));
+    assert!(!worktree.exists());
+
+    // Changes stashed on start are still restored on stop.
+    let file = dir.join("hello.txt");
+    fs::write(&file, "mid-edit").unwrap();
+    git::add(&dir, &file);
+    run(&dir, &["start", "--stash"]);
+    run_with_env(&dir, &["reorder"], &[editor]);
+    let output = run(&dir, &["stop"]);
+    assert!(output
+        .stdout
+        .ends_with("Restored changes from before the presentation.\n"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "mid-edit");
+}
+
+#[test]
+fn reorder_empty_list_aborts() {
+    let dir = git::init("reorder_empty_list_aborts");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+    let editor = ("GIT_EDITOR", "sed -i -e '/^[^#]/d'");
+
+    let output = run_with_env(&dir, &["reorder"], &[editor]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(
+        output.stdout,
+        "Nothing to present, keeping the current order.\n"
+    );
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("2/2"));
+}
+
+#[test]
+fn insert() {
+    let dir = git::init("insert");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let output = run(&dir, &["insert", "-m", "Slide 2.5"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Updated branch 'main'."));
+    assert!(output.stdout.contains("* 3/4"));
+    assert_eq!(git::status(&dir), "Slide 2.5");
+
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    // Rebased on top of the new slide.
+    assert_eq!(
+        git::exec(&dir, &["log", "-1", "--format=%s", "HEAD~"]),
+        "Slide 2.5"
+    );
+
+    let output = run(&dir, &["stop"]);
+    assert!(output.stdout.contains("Going back to branch 'main'."));
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert_eq!(
+        git::exec(&dir, &["log", "--format=%s"]),
+        "Slide 3\nSlide 2.5\nSlide 2\nSlide 1"
+    );
+}
+
+#[test]
+fn insert_after_last_slide() {
+    let dir = git::init("insert_after_last_slide");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["insert", "--message=Slide 2"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 2/2"));
+
+    run(&dir, &["stop"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+}
+
+#[test]
+fn insert_with_staged_changes() {
+    let dir = git::init("insert_with_staged_changes");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    let new_file = dir.join("hello.txt");
+    fs::write(&new_file, ":)").unwrap();
+    git::add(&dir, &new_file);
+
+    let output = run(&dir, &["insert", "-m", "Slide 1.5"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(!output.stdout.contains("Stashed"));
+    assert_eq!(git::status(&dir), "Slide 1.5");
+    assert_eq!(
+        git::exec(&dir, &["show", "--format=", "--name-only"]),
+        "hello.txt"
+    );
+    assert!(!git::has_stashed_changes(&dir));
+}
+
+#[test]
+fn insert_started_from_ref_does_not_move_branch() {
+    let dir = git::init("insert_started_from_ref_does_not_move_branch");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Not in presentation");
+
+    run(&dir, &["start", "HEAD~"]);
+
+    let output = run(&dir, &["insert", "-m", "Slide 1.5"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(!output.stdout.contains("Updated branch"));
+    assert!(output.stdout.contains("* 2/3"));
+
+    assert_eq!(
+        git::exec(&dir, &["log", "-1", "--format=%s", "main"]),
+        "Not in presentation"
+    );
+}
+
+#[test]
+fn insert_with_conflict() {
+    let dir = git::init("insert_with_conflict");
+    let file = dir.join("file.txt");
+    fs::write(&file, "1").unwrap();
+    git::add(&dir, &file);
+    git::commit(&dir, "Slide 1");
+    fs::write(&file, "2").unwrap();
+    git::add(&dir, &file);
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    fs::write(&file, "conflict").unwrap();
+    git::add(&dir, &file);
+
+    let output = run(&dir, &["insert", "-m", "Slide 1.5"]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .starts_with("error: Could not rebase the following slides onto the new commit.\n"));
+    assert!(output.stderr.contains("The presentation is unchanged."));
+
+    // Back on the current slide, presentation untouched.
+    assert_eq!(git::status(&dir), "Slide 1");
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("* 1/2"));
+    assert_eq!(
+        git::exec(&dir, &["log", "-1", "--format=%s", "main"]),
+        "Slide 2"
+    );
+}
+
+#[test]
+fn insert_in_explicit_slides() {
+    let dir = git::init("insert_in_explicit_slides");
+    git::commit(&dir, "Slide 1");
+    git::exec(&dir, &["tag", "slide-1"]);
+    git::commit(&dir, "Slide 2");
+    git::exec(&dir, &["tag", "slide-2"]);
+
+    run(&dir, &["start", "--tags", "slide-*"]);
+
+    let output = run(&dir, &["insert", "-m", "Slide 1.5"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 2/3"));
+    assert_eq!(git::status(&dir), "Slide 1.5");
+
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+}
+
+#[test]
+fn edit() {
+    let dir = git::init("edit");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let new_file = dir.join("typo.txt");
+    fs::write(&new_file, "fixed").unwrap();
+    git::add(&dir, &new_file);
+
+    let output = run(&dir, &["edit"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("Updated branch 'main'."));
+    assert!(output.stdout.contains("* 2/3"));
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(
+        git::exec(&dir, &["show", "--format=", "--name-only"]),
+        "typo.txt"
+    );
+
+    // Following slides include the fix.
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert!(dir.join("typo.txt").is_file());
+
+    run(&dir, &["stop"]);
+    assert_eq!(
+        git::exec(&dir, &["log", "--format=%s"]),
+        "Slide 3\nSlide 2\nSlide 1"
+    );
+}
+
+#[test]
+fn edit_message() {
+    let dir = git::init("edit_message");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["edit", "-m", "Introduction"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/2"));
+    assert_eq!(git::status(&dir), "Introduction");
+}
+
+#[test]
+fn edit_first_slide_with_from() {
+    let dir = git::init("edit_first_slide_with_from");
+    git::commit(&dir, "Setup");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start", "--from", "HEAD~"]);
+
+    let output = run(&dir, &["edit", "-m", "Introduction"]);
+    assert_eq!(output.exit_code, 0);
+    // The boundary follows the amended slide.
+    assert!(output.stdout.contains("* 1/2"));
+    assert!(!output.stdout.contains("Setup"));
+}
+
+#[test]
+fn edit_nothing_staged() {
+    let dir = git::init("edit_nothing_staged");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+    let before = git::exec(&dir, &["rev-parse", "HEAD"]);
+
+    let output = run(&dir, &["edit"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(
+        output.stdout,
+        "No staged changes, the slide is unchanged.\n"
+    );
+    assert_eq!(git::exec(&dir, &["rev-parse", "HEAD"]), before);
+}
+
+#[test]
+fn absorb() {
+    let dir = git::init("absorb");
+    let slide = dir.join("slide.txt");
+    fs::write(&slide, "Slide 1").unwrap();
+    git::add(&dir, &slide);
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    fs::write(&slide, "Slide 2").unwrap();
+
+    let output = run(&dir, &["absorb"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 2/3"));
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert_eq!(
+        git::exec(&dir, &["show", "--format=", "--name-only"]),
+        "slide.txt"
+    );
+    assert!(!git::has_stashed_changes(&dir));
+
+    run(&dir, &["stop"]);
+    assert_eq!(
+        git::exec(&dir, &["log", "--format=%s"]),
+        "Slide 3\nSlide 2\nSlide 1"
+    );
+}
+
+#[test]
+fn absorb_no_changes() {
+    let dir = git::init("absorb_no_changes");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["absorb"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "No changes, the slide is unchanged.\n");
+}
+
+#[test]
+fn quiet() {
+    let dir = git::init("quiet");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    let output = run(&dir, &["--quiet", "start"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "");
+
+    fs::write(dir.join("dirty.txt"), "dirty").unwrap();
+    git::add(&dir, &dir.join("dirty.txt"));
+
+    let output = run(&dir, &["-q", "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "");
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(git::has_stashed_changes(&dir));
+
+    // Explicitly requested output is still shown.
+    let output = run(&dir, &["-q", "status"]);
+    assert!(output.stdout.contains("* 2/2"));
+
+    let output = run(&dir, &["-q", "stop"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "");
+}
+
+#[test]
+fn no_color_when_not_a_terminal() {
+    let dir = git::init("no_color_when_not_a_terminal");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    run(&dir, &["start"]);
+
+    // Tests capture the output, so it is not a terminal.
+    let output = run(&dir, &["list"]);
+    assert!(!output.stdout.contains('\x1b'));
+
+    let output = run(&dir, &["status"]);
+    assert!(!output.stdout.contains('\x1b'));
+
+    let output = run(&dir, &["--no-color", "status"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(!output.stdout.contains('\x1b'));
+}
+
+#[test]
+fn color_bad_config_value() {
+    let dir = git::init("color_bad_config_value");
+    git::commit(&dir, "Slide 1");
+    git::exec(&dir, &["config", "slides.color.theme", "neon"]);
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Bad config value for 'slides.color.theme': 'neon'.\n"
+    );
+
+    git::exec(&dir, &["config", "slides.color.theme", "high-contrast"]);
+    git::exec(&dir, &["config", "slides.color.hash", "not-a-color"]);
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Bad config value for 'slides.color.hash': 'not-a-color'.\n"
+    );
+
+    git::exec(&dir, &["config", "slides.color.hash", "bold blue"]);
+
+    let output = run(&dir, &["start"]);
+    assert_eq!(output.exit_code, 0);
+}
+
+#[test]
+fn list_verbose() {
+    let dir = git::init("list_verbose");
+    git::commit(&dir, "Slide 1\n\nFirst line.\nSecond line.");
+    git::commit(
+        &dir,
+        "Slide 2\n\nOne.\nTwo.\nThree.\nFour.\n\nSlide-Duration: 5m\nSigned-off-by: Me <me@example.com>",
+    );
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list", "--verbose"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert!(lines[0].ends_with("Slide 1"));
+    assert_eq!(lines[1], "      First line.");
+    assert_eq!(lines[2], "      Second line.");
+    assert!(lines[3].ends_with("Slide 2"));
+    assert_eq!(lines[4], "      One.");
+    assert_eq!(lines[5], "      Two.");
+    assert_eq!(lines[6], "      Three.");
+    assert_eq!(lines[7], "      [...]");
+    assert_eq!(lines[8], "      Slide-Duration: 5m");
+    assert!(lines[9].ends_with("Slide 3"));
+    assert_eq!(lines.len(), 10);
+
+    // Not verbose.
+    let output = run(&dir, &["list"]);
+    assert_eq!(output.stdout.lines().count(), 3);
+}
+
+#[test]
+fn author_and_date_columns() {
+    let dir = git::init("author_and_date_columns");
+    git::commit(&dir, "Slide 1");
+    git::exec(
+        &dir,
+        &[
+            "-c",
+            "user.name=Someone Else",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "Slide 2",
+        ],
+    );
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["list", "--author", "--date"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    let author = git::exec(&dir, &["log", "-1", "--format=%an", "HEAD"]);
+    let padding = " ".repeat("Someone Else".len() - author.len());
+    assert!(lines[0].contains(&format!(" {author}{padding}  ")));
+    assert!(lines[0].ends_with(" ago  Slide 1"));
+    assert!(lines[1].contains(" Someone Else  "));
+    assert!(lines[1].ends_with(" ago  Slide 2"));
+
+    let output = run(&dir, &["status", "--author"]);
+    assert!(output.stdout.contains(" Someone Else  Slide 2"));
+
+    // From config.
+    git::exec(&dir, &["config", "slides.showAuthor", "true"]);
+    let output = run(&dir, &["next"]);
+    assert!(output.stdout.contains(" Someone Else  Slide 2"));
+
+    git::exec(&dir, &["config", "slides.showAuthor", "false"]);
+    let output = run(&dir, &["list"]);
+    assert!(!output.stdout.contains("Someone Else"));
+}
+
+#[test]
+fn strict_boundaries() {
+    let dir = git::init("strict_boundaries");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["--strict", "previous"]);
+    assert_eq!(output.exit_code, 3);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    // Reaching the end is fine, going past it isn't.
+    let output = run(&dir, &["--strict", "next", "2"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    let output = run(&dir, &["--strict", "next"]);
+    assert_eq!(output.exit_code, 3);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    // Cut short.
+    let output = run(&dir, &["--strict", "previous", "5"]);
+    assert_eq!(output.exit_code, 3);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    // Not strict.
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.exit_code, 0);
+
+    git::exec(&dir, &["config", "slides.strict", "true"]);
+    let output = run(&dir, &["previous"]);
+    assert_eq!(output.exit_code, 3);
+}
+
+#[test]
+fn step() {
+    let dir = git::init("step");
+    for n in 1..=5 {
+        git::commit(&dir, &format!("Slide {n}"));
+    }
+
+    run(&dir, &["start"]);
+    git::exec(&dir, &["config", "slides.step", "2"]);
+
+    run(&dir, &["next"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    run(&dir, &["next", "1"]);
+    assert_eq!(git::status(&dir), "Slide 4");
+    run(&dir, &["previous"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    git::exec(&dir, &["config", "slides.step", "0"]);
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "fatal: Bad config value for 'slides.step': '0'.\n"
+    );
+}
+
+#[test]
+fn loop_navigation() {
+    let dir = git::init("loop_navigation");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["--loop", "previous"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    let output = run(&dir, &["--loop", "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    run(&dir, &["--loop", "next", "4"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    run(&dir, &["--loop", "previous", "5"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+
+    // Takes precedence over strict.
+    git::exec(&dir, &["config", "slides.loop", "true"]);
+    let output = run(&dir, &["--strict", "next"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 1");
+}
+
+#[test]
+fn store_location_override() {
+    let dir = git::init("store_location_override");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    let state = dir.join("state");
+    fs::create_dir(&state).unwrap();
+    fs::write(dir.join(".git/info/exclude"), "state/\n").unwrap();
+    let store_file = state.join("deck");
+
+    let output = run_with_env(&dir, &["start"], &[("GIT_SLIDES_STORE", "state/deck")]);
+    assert_eq!(output.exit_code, 0);
+    assert!(store_file.is_file());
+    assert!(!dir.join(".git/git-slides").exists());
+
+    let output = run_with_env(&dir, &["next"], &[("GIT_SLIDES_STORE", "state/deck")]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(git::status(&dir), "Slide 2");
+
+    // Not started, as far as the default location is concerned.
+    let output = run(&dir, &["status"]);
+    assert_eq!(output.exit_code, 1);
+
+    run_with_env(&dir, &["stop"], &[("GIT_SLIDES_STORE", "state/deck")]);
+    assert!(!store_file.exists());
+    assert!(state.join("deck-runs").is_dir());
+}
+
+#[test]
+fn info() {
+    let dir = git::init("info");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    let head = git::exec(&dir, &["rev-parse", "HEAD"]);
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["info"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains(&format!("Head:          {head}\n")));
+    assert!(output.stdout.contains("Branch:        main\n"));
+    assert!(output.stdout.contains(" UTC ("));
+    assert!(output.stdout.contains("s ago)\n"));
+    assert!(output.stdout.contains("Slides:        2\n"));
+    assert!(output.stdout.contains(".git/git-slides\n"));
+    assert!(output.stdout.contains("Store format:  2\n"));
+}
+
+#[test]
+fn store_migrates_from_v1() {
+    let dir = git::init("store_migrates_from_v1");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    let head = git::exec(&dir, &["rev-parse", "HEAD"]);
+    let first = git::exec(&dir, &["rev-parse", "HEAD~2"]);
+    let second = git::exec(&dir, &["rev-parse", "HEAD~"]);
+
+    // As written by older versions.
+    let store_file = dir.join(".git/git-slides");
+    fs::write(&store_file, format!("main:{head}\nfrom:{second}\n")).unwrap();
+    git::checkout(&dir, &second);
+
+    let output = run(&dir, &["status"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("* 1/2"));
+    assert!(!output.stdout.contains(&first[..7]));
+
+    let store = fs::read_to_string(&store_file).unwrap();
+    assert!(store.contains("version = 2\n"));
+    assert!(store.contains("branch = \"main\"\n"));
+    assert!(store.contains(&format!("head = \"{head}\"\n")));
+    assert!(store.contains(&format!("from = \"{second}\"\n")));
+
+    run(&dir, &["stop"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+}
+
+#[test]
+fn store_from_newer_version() {
+    let dir = git::init("store_from_newer_version");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+
+    let store_file = dir.join(".git/git-slides");
+    fs::write(&store_file, "version = 99\nhead = \"abc\"\n").unwrap();
+
+    let output = run(&dir, &["status"]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .starts_with("error: '.git/git-slides' uses format version 99, which is not supported.\n"));
+}
+
+#[test]
+fn store_escapes_titles() {
+    let dir = git::init("store_escapes_titles");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    let manifest = dir.join("slides.txt");
+    fs::write(&manifest, "HEAD~ Say \"hi\" \\o/\nHEAD\n").unwrap();
+
+    run(&dir, &["start", "--manifest", manifest.to_str().unwrap()]);
+
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("Say \"hi\" \\o/\n"));
+}
+
+#[test]
+fn repair_after_rebase() {
+    let dir = git::init("repair_after_rebase");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    // Rewrite the whole branch, mid-presentation.
+    git::checkout(&dir, "main");
+    git::exec(
+        &dir,
+        &[
+            "rebase",
+            "--root",
+            "--exec",
+            "echo x >> rewritten.txt && git add rewritten.txt && git commit --amend --no-edit",
+        ],
+    );
+
+    let output = run(&dir, &["status"]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stderr
+        .contains("hint: Use 'git slides repair' to remap the presentation.\n"));
+
+    let output = run(&dir, &["repair"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("found 3 of 3 slides."));
+    assert!(output.stdout.contains("* 3/3"));
+
+    run(&dir, &["previous"]);
+    assert_eq!(git::status(&dir), "Slide 2");
+    assert!(dir.join("rewritten.txt").is_file());
+
+    run(&dir, &["stop"]);
+    assert_eq!(git::status(&dir), "Slide 3");
+    assert_eq!(git::exec(&dir, &["branch", "--show-current"]), "main");
+}
+
+#[test]
+fn repair_nothing_found() {
+    let dir = git::init("repair_nothing_found");
+    git::commit(&dir, "Slide 1");
+
+    run(&dir, &["start"]);
+
+    git::exec(&dir, &["checkout", "--orphan", "other"]);
+    git::commit(&dir, "Unrelated");
+
+    let output = run(&dir, &["repair", "other"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: No slide found in 'other'. The presentation is unchanged.\n"
+    );
+}
+
+#[test]
+fn doctor() {
+    let dir = git::init("doctor");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+
+    let output = run(&dir, &["doctor"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("ok: Git version "));
+    assert!(output.stdout.contains("ok: No presentation in progress."));
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["doctor"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("ok: Store file is valid."));
+    assert!(output.stdout.contains("ok: Presentation head exists."));
+    assert!(output.stdout.contains("ok: HEAD is on a slide."));
+
+    git::checkout(&dir, "main");
+
+    let output = run(&dir, &["doctor"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stdout
+        .contains("problem: HEAD is on branch 'main', instead of detached on a slide.\n"));
+    assert!(output
+        .stdout
+        .contains("    fix: Use 'git slides go <n>' to go back to a slide.\n"));
+
+    fs::write(dir.join(".git/git-slides"), "garbage").unwrap();
+
+    let output = run(&dir, &["doctor"]);
+    assert_eq!(output.exit_code, 1);
+    assert!(output
+        .stdout
+        .contains("problem: Store file is unreadable or corrupt.\n"));
+}
+
+#[test]
+fn verify() {
+    let dir = git::init("verify");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
+
+    run(&dir, &["start"]);
+
+    let output = run(&dir, &["verify"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 0);
+    assert!(output
+        .stdout
+        .contains("ok: Slides match the checksum (3 slides)."));
+    assert!(output.stdout.contains("ok: All 3 slides exist."));
+
+    // Drop slides 2 and 3 from the branch, and prune them.
+    let slide_2 = git::exec(&dir, &["rev-parse", "main~1"]);
+    let slide_1 = git::exec(&dir, &["rev-parse", "HEAD"]);
+    git::exec(&dir, &["branch", "--force", "main", &slide_1]);
+    git::exec(&dir, &["reflog", "expire", "--expire=now", "--all"]);
+    git::exec(&dir, &["gc", "--quiet", "--prune=now"]);
+
+    let output = run(&dir, &["verify"]);
+    println!("{}", output.stdout);
+    assert_eq!(output.exit_code, 1);
+    assert!(output.stdout.contains("missing: 2/3 "));
+    assert!(output.stdout.contains(" Slide 2\n"));
+    assert!(output.stdout.contains("missing: 3/3 "));
+    assert!(!output.stdout.contains("missing: 1/3 "));
+    assert!(output
+        .stdout
+        .contains("problem: 2 of 3 slides are missing.\n"));
+
+    git::exec(&dir, &["config", "slides.abbrev", "10"]);
+    let output = run(&dir, &["verify"]);
+    assert!(output
+        .stdout
+        .contains(&format!("missing: 2/3 {} Slide 2\n", &slide_2[..10])));
+
+    let store_file = dir.join(".git/git-slides");
+    let store = fs::read_to_string(&store_file).unwrap();
+    fs::write(
+        &store_file,
+        store.replace("slide-count = 3", "slide-count = 4"),
+    )
+    .unwrap();
+
+    let output = run(&dir, &["verify"]);
+    assert!(output
+        .stdout
+        .contains("problem: Slides do not match the checksum (4 slides recorded, 3 now).\n"));
+}
 
 #[test]
-fn go_in_dirty_working_directory() {
-    let dir = git::init("go_in_dirty_working_directory");
+fn concurrent_invocations_are_locked_out() {
+    let dir = git::init("concurrent_invocations_are_locked_out");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
 
     run(&dir, &["start"]);
 
-    let new_file = dir.join("hello.txt");
+    // Simulate another invocation holding the lock.
+    let lock = fs::File::create(dir.join(".git/git-slides.lock")).unwrap();
+    lock.lock().unwrap();
 
-    let _ = fs::write(&new_file, ":)");
-    git::add(&dir, &new_file);
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Another git-slides process is running. Aborting.\n"
+    );
+    assert_eq!(git::status(&dir), "Slide 1");
 
-    assert!(!git::has_stashed_changes(&dir));
+    // Read-only commands still work.
+    for args in [
+        &["status"][..],
+        &["count"],
+        &["current"],
+        &["env"],
+        &["export-state", dir.join("state").to_str().unwrap()],
+    ] {
+        let output = run(&dir, args);
+        assert_eq!(output.exit_code, 0, "{args:?}");
+    }
 
-    let output = run(&dir, &["go", "2"]);
+    drop(lock);
 
+    let output = run(&dir, &["next"]);
     assert_eq!(output.exit_code, 0);
-    assert!(output.stdout.contains("Stashed uncommitted changes."));
-    assert!(output.stdout.contains("* 2/2"));
-
-    assert!(git::has_stashed_changes(&dir));
+    assert_eq!(git::status(&dir), "Slide 2");
 }
 
+#[cfg(unix)]
 #[test]
-fn go_no_index() {
-    let dir = git::init("go_no_index");
+fn concurrent_invocations_are_locked_out_by_another_process() {
+    use std::process::Stdio;
+    use std::thread;
+    use std::time::Duration;
 
-    let output = run(&dir, &["go"]);
+    let dir = git::init("concurrent_invocations_are_locked_out_by_another_process");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Slide 2");
 
-    assert_eq!(output.exit_code, 2);
-    assert_eq!(output.stderr, "fatal: Need a slide number.\n");
+    run(&dir, &["start"]);
+
+    // `reorder` holds the lock while the editor is open.
+    let mut reorder = Command::new(GIT_SLIDES)
+        .arg("reorder")
+        .env("GIT_EDITOR", "sleep 5; :")
+        .current_dir(&dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let todo_file = dir.join(".git/git-slides-reorder");
+    while !todo_file.exists() {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 1);
+    assert_eq!(
+        output.stderr,
+        "error: Another git-slides process is running. Aborting.\n"
+    );
+    assert_eq!(git::status(&dir), "Slide 1");
+
+    reorder.kill().unwrap();
+    reorder.wait().unwrap();
+
+    let output = run(&dir, &["next"]);
+    assert_eq!(output.exit_code, 0);
 }
 
 #[test]
-fn go_bad_index() {
-    let dir = git::init("go_bad_index");
+fn language() {
+    let dir = git::init("language");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
 
+    let output = run_with_env(&dir, &["status"], &[("LANG", "fr_FR.UTF-8")]);
+    assert_eq!(
+        output.stderr,
+        "Vous devez commencer par 'git slides start'.\n"
+    );
+
+    git::exec(&dir, &["config", "slides.language", "fr"]);
     run(&dir, &["start"]);
 
-    let output = run(&dir, &["go", "0"]);
-    assert_eq!(output.exit_code, 1);
+    let output = run(&dir, &["next"]);
+    assert!(output
+        .stdout
+        .starts_with("Vous êtes arrivé à la fin de la présentation.\n"));
+    assert!(output.stdout.contains("(Fin)"));
+
+    // Errors too, including those of argument parsing.
+    let output = run(&dir, &["go", "nonexistent"]);
     assert_eq!(
         output.stderr,
-        "error: Bad slide index. Slide 0 does not exist.\nPossible values range from 1 to 2.\n"
+        "erreur : Mauvaise référence : 'nonexistent'.\n"
+    );
+    let output = run(&dir, &["hide", "abc"]);
+    assert_eq!(
+        output.stderr,
+        "fatal : Mauvais numéro de diapositive : 'abc'.\n"
     );
 
-    let output = run(&dir, &["go", "3"]);
+    // Config wins over the environment.
+    git::exec(&dir, &["config", "slides.language", "en"]);
+    let output = run_with_env(&dir, &["previous"], &[("LC_ALL", "fr_FR.UTF-8")]);
+    assert!(output.stdout.contains("(Start)"));
+
+    git::exec(&dir, &["config", "slides.language", "klingon"]);
+    let output = run(&dir, &["status"]);
     assert_eq!(output.exit_code, 1);
     assert_eq!(
         output.stderr,
-        "error: Bad slide index. Slide 3 does not exist.\nPossible values range from 1 to 2.\n"
+        "fatal: Bad config value for 'slides.language': 'klingon'.\n"
     );
 }
 
 #[test]
-fn status_full() {
-    let dir = git::init("status_full");
+fn list_not_paged_when_not_a_terminal() {
+    let dir = git::init("list_not_paged_when_not_a_terminal");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Slide 2");
-    git::commit(&dir, "Slide 3");
 
     run(&dir, &["start"]);
 
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-
+    // A pager that would swallow the output.
+    let output = run_with_env(&dir, &["list"], &[("GIT_PAGER", "cat > /dev/null")]);
     assert_eq!(output.exit_code, 0);
-    assert!(output.stdout.contains("(Start)"));
-    assert!(output.stdout.contains("* 1/3"));
-    assert!(output.stdout.contains("2/3"));
-    assert!(output.stdout.contains("3/3"));
-    assert!(output.stdout.contains("(End)"));
+    assert!(output.stdout.contains("1/1"));
 }
 
 #[test]
-#[allow(clippy::cognitive_complexity)]
-fn status_cut() {
-    let dir = git::init("status_cut");
+fn list_stat() {
+    let dir = git::init("list_stat");
+    let a = dir.join("a.txt");
+    let b = dir.join("b.txt");
+    fs::write(&a, "1\n2\n").unwrap();
+    git::add(&dir, &a);
     git::commit(&dir, "Slide 1");
+    fs::write(&a, "1\n3\n").unwrap();
+    fs::write(&b, "1\n").unwrap();
+    git::add(&dir, &a);
+    git::add(&dir, &b);
     git::commit(&dir, "Slide 2");
     git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
-    git::commit(&dir, "Slide 5");
-    git::commit(&dir, "Slide 6");
-    git::commit(&dir, "Slide 7");
 
     run(&dir, &["start"]);
 
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(output.stdout.contains("(Start)"));
-    assert!(output.stdout.contains("* 1/7"));
-    assert!(output.stdout.contains("2/7"));
-    assert!(output.stdout.contains("3/7"));
-    assert!(output.stdout.contains("4/7"));
-    assert!(!output.stdout.contains("5/7"));
-
-    run(&dir, &["go", "2"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(output.stdout.contains("(Start)"));
-    assert!(output.stdout.contains("1/7"));
-    assert!(output.stdout.contains("* 2/7"));
-    assert!(output.stdout.contains("3/7"));
-    assert!(output.stdout.contains("4/7"));
-    assert!(output.stdout.contains("5/7"));
-    assert!(!output.stdout.contains("6/7"));
-
-    run(&dir, &["go", "3"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(!output.stdout.contains("(Start)"));
-    assert!(output.stdout.contains("1/7"));
-    assert!(output.stdout.contains("2/7"));
-    assert!(output.stdout.contains("* 3/7"));
-    assert!(output.stdout.contains("4/7"));
-    assert!(output.stdout.contains("5/7"));
-    assert!(output.stdout.contains("6/7"));
-    assert!(!output.stdout.contains("7/7"));
+    let output = run(&dir, &["list", "--stat"]);
+    assert_eq!(output.exit_code, 0);
+    let lines: Vec<&str> = output.stdout.lines().collect();
+    assert!(lines[0].ends_with(" 1 file   +2 -0  Slide 1"));
+    assert!(lines[1].ends_with(" 2 files  +2 -1  Slide 2"));
+    assert!(lines[2].ends_with(" 0 files  +0 -0  Slide 3"));
+}
 
-    run(&dir, &["go", "4"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(!output.stdout.contains("1/7"));
-    assert!(output.stdout.contains("2/7"));
-    assert!(output.stdout.contains("3/7"));
-    assert!(output.stdout.contains("* 4/7"));
-    assert!(output.stdout.contains("5/7"));
-    assert!(output.stdout.contains("6/7"));
-    assert!(output.stdout.contains("7/7"));
-    assert!(!output.stdout.contains("(End)"));
+#[test]
+fn list_no_pager() {
+    let dir = git::init("list_no_pager");
+    git::commit(&dir, "Slide 1");
 
-    run(&dir, &["go", "5"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(!output.stdout.contains("2/7"));
-    assert!(output.stdout.contains("3/7"));
-    assert!(output.stdout.contains("4/7"));
-    assert!(output.stdout.contains("* 5/7"));
-    assert!(output.stdout.contains("6/7"));
-    assert!(output.stdout.contains("7/7"));
-    assert!(output.stdout.contains("(End)"));
+    run(&dir, &["start"]);
 
-    run(&dir, &["go", "6"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(!output.stdout.contains("3/7"));
-    assert!(output.stdout.contains("4/7"));
-    assert!(output.stdout.contains("5/7"));
-    assert!(output.stdout.contains("* 6/7"));
-    assert!(output.stdout.contains("7/7"));
-    assert!(output.stdout.contains("(End)"));
+    let output = run(&dir, &["list", "--no-pager"]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("1/1"));
 
-    run(&dir, &["go", "7"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(!output.stdout.contains("4/7"));
-    assert!(output.stdout.contains("5/7"));
-    assert!(output.stdout.contains("6/7"));
-    assert!(output.stdout.contains("* 7/7"));
-    assert!(output.stdout.contains("(End)"));
+    let output = run_with_env(&dir, &["list"], &[("GIT_SLIDES_NO_PAGER", "1")]);
+    assert_eq!(output.exit_code, 0);
+    assert!(output.stdout.contains("1/1"));
 }
 
 #[test]
-fn status_number_padding() {
-    let dir = git::init("status_number_padding");
+fn count() {
+    let dir = git::init("count");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
     git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
-    git::commit(&dir, "Slide 5");
-    git::commit(&dir, "Slide 6");
-    git::commit(&dir, "Slide 7");
-    git::commit(&dir, "Slide 8");
-    git::commit(&dir, "Slide 9");
-    git::commit(&dir, "Slide 10");
 
     run(&dir, &["start"]);
+    run(&dir, &["next"]);
 
-    // Padded, even if bigger number not in output.
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(output.stdout.contains("*  1/10"));
-    assert!(output.stdout.contains("   2/10"));
-    assert!(!output.stdout.contains("  10/10"));
+    let output = run(&dir, &["count"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "3\n");
 
-    run(&dir, &["go", "9"]);
-    let output = run(&dir, &["status"]);
-    println!("{}", output.stdout);
-    assert!(!output.stdout.contains("6/10"));
-    assert!(output.stdout.contains("*  9/10"));
-    assert!(output.stdout.contains("  10/10"));
+    let output = run(&dir, &["count", "--remaining"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "1\n");
 }
 
 #[test]
-fn status_error_getting_current_commit() {
-    let dir = git::init("status_error_getting_current_commit");
+fn current() {
+    let dir = git::init("current");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Not in presentation");
+    git::commit(&dir, "Slide 2");
 
-    run(&dir, &["start", "HEAD~"]);
+    run(&dir, &["start"]);
 
-    git::checkout(&dir, "main"); // Commit not in presentation.
+    let output = run(&dir, &["current"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "Slide 1\n");
 
-    let output = run(&dir, &["status"]);
+    let hash = git::exec(&dir, &["rev-parse", "HEAD"]);
+    let output = run(&dir, &["current", "--format", "[%i/%t] %h %H %s 100%% %x"]);
+    assert_eq!(
+        output.stdout,
+        format!("[1/2] {} {hash} Slide 1 100% %x\n", &hash[..7])
+    );
+}
 
-    assert_eq!(output.exit_code, 1);
+#[test]
+fn env_variables() {
+    let dir = git::init("env_variables");
+    git::commit(&dir, "Slide 1");
+    git::commit(&dir, "Don't panic");
+
+    run(&dir, &["start"]);
+    run(&dir, &["next"]);
+
+    let hash = git::exec(&dir, &["rev-parse", "HEAD"]);
+    let output = run(&dir, &["env"]);
+    assert_eq!(output.exit_code, 0);
     assert_eq!(
-        output.stderr,
-        "error: Current HEAD not part of presentation.\n"
+        output.stdout,
+        format!("SLIDE_INDEX=2\nSLIDE_TOTAL=2\nSLIDE_HASH={hash}\nSLIDE_TITLE='Don'\\''t panic'\n")
     );
 }
 
 #[test]
-fn list() {
-    let dir = git::init("list");
+fn prompt() {
+    let dir = git::init("prompt");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
     git::commit(&dir, "Slide 3");
 
-    run(&dir, &["start"]);
+    // Nothing if no presentation.
+    let output = run(&dir, &["prompt"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "");
+    assert_eq!(output.stderr, "");
 
-    let output = run(&dir, &["list"]);
-    println!("{}", output.stdout);
+    run(&dir, &["start", "--tags", "*"]);
+    let output = run(&dir, &["prompt"]);
+    assert_eq!(output.stdout, "");
 
+    git::exec(&dir, &["tag", "a", "HEAD~2"]);
+    git::exec(&dir, &["tag", "b", "HEAD"]);
+    run(&dir, &["start", "--tags", "*"]);
+    run(&dir, &["next"]);
+
+    let output = run(&dir, &["prompt"]);
     assert_eq!(output.exit_code, 0);
-    assert!(output.stdout.contains("* 1/3"));
-    assert!(output.stdout.contains("2/3"));
-    assert!(output.stdout.contains("3/3"));
+    assert_eq!(output.stdout, "[slides 2/2]\n");
 }
 
 #[test]
-fn list_number_padding() {
-    let dir = git::init("list_number_padding");
+fn prompt_outside_of_repository() {
+    let dir = git::init("prompt_outside_of_repository");
+    let not_a_repo = dir.join("not-a-repo");
+    fs::create_dir(&not_a_repo).unwrap();
+
+    let output = run_with_env(
+        &not_a_repo,
+        &["prompt"],
+        &[("GIT_CEILING_DIRECTORIES", dir.to_str().unwrap())],
+    );
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "");
+    assert_eq!(output.stderr, "");
+}
+
+#[test]
+fn complete() {
+    let dir = git::init("complete");
     git::commit(&dir, "Slide 1");
     git::commit(&dir, "Slide 2");
     git::commit(&dir, "Slide 3");
-    git::commit(&dir, "Slide 4");
-    git::commit(&dir, "Slide 5");
-    git::commit(&dir, "Slide 6");
-    git::commit(&dir, "Slide 7");
-    git::commit(&dir, "Slide 8");
-    git::commit(&dir, "Slide 9");
-    git::commit(&dir, "Slide 10");
+
+    // Nothing if no presentation.
+    let output = run(&dir, &["__complete"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "");
+    assert_eq!(output.stderr, "");
 
     run(&dir, &["start"]);
 
-    let output = run(&dir, &["list"]);
-    println!("{}", output.stdout);
-    assert!(output.stdout.contains("*  1/10"));
-    assert!(output.stdout.contains("  10/10"));
+    let output = run(&dir, &["__complete"]);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "1\tSlide 1\n2\tSlide 2\n3\tSlide 3\n");
+
+    // Hidden.
+    let output = run(&dir, &["--help"]);
+    assert!(!output.stdout.contains("__complete"));
 }
 
 #[test]
-fn list_error_getting_current_commit() {
-    let dir = git::init("list_error_getting_current_commit");
+fn history_is_cached_in_store() {
+    let dir = git::init("history_is_cached_in_store");
     git::commit(&dir, "Slide 1");
-    git::commit(&dir, "Not in presentation");
+    git::commit(&dir, "Slide 2");
+    git::commit(&dir, "Slide 3");
 
-    run(&dir, &["start", "HEAD~"]);
+    run(&dir, &["start"]);
 
-    git::checkout(&dir, "main"); // Commit not in presentation.
+    let store_file = dir.join(".git/git-slides");
+    let store = fs::read_to_string(&store_file).unwrap();
+    assert!(store.contains("cached-head = "));
+    assert_eq!(store.matches("[[cached-slide]]").count(), 3);
 
+    // Resolved from the cache, without Git.
+    run(&dir, &["next"]);
+    let output = run(&dir, &["prompt"]);
+    assert_eq!(output.stdout, "[slides 2/3]\n");
+
+    // The cache is used as is.
+    fs::write(&store_file, store.replace("Slide 3", "Cached title")).unwrap();
     let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("Cached title"));
 
-    assert_eq!(output.exit_code, 1);
-    assert_eq!(
-        output.stderr,
-        "error: Current HEAD not part of presentation.\n"
-    );
+    // And refreshed when the head changes.
+    run(&dir, &["insert", "-m", "Slide 2.5"]);
+    let output = run(&dir, &["list"]);
+    assert!(output.stdout.contains("Slide 2.5"));
+    assert!(output.stdout.contains("Slide 3"));
+    assert!(!output.stdout.contains("Cached title"));
+    let output = run(&dir, &["prompt"]);
+    assert_eq!(output.stdout, "[slides 3/4]\n");
 }